@@ -0,0 +1,115 @@
+label-observed = Beobachtet
+label-temperature = Temperatur
+label-dewpoint = Taupunkt
+label-frost-point = Reifpunkt
+label-humidity = Luftfeuchtigkeit
+label-wind = Wind
+label-visibility = Sichtweite
+label-sky = Himmel
+label-ceiling = Wolkenuntergrenze
+label-cloud-base-estimate = Wolkenbasis (geschätzt)
+label-weather = Wetter
+label-pressure = Luftdruck
+label-advisory = Hinweis
+label-warmest = Am wärmsten
+label-coldest = Am kältesten
+label-windiest = Am windigsten
+
+wind-calm = windstill
+wind-variable = umlaufend mit { $speed }
+wind-from = aus { $direction } ({ $azimuth }°) mit { $speed }
+wind-gusting = { $speed }, in Böen { $gust }
+wind-varying = { $wind }, schwankend zwischen { $from }° und { $to }°
+
+advisory-fog = Nebel wahrscheinlich
+
+age-just-now = gerade eben
+age-minutes = { $count ->
+    [one] vor einer Minute
+   *[other] vor { $count } Minuten
+}
+age-hours = { $count ->
+    [one] vor einer Stunde
+   *[other] vor { $count } Stunden
+}
+age-days = { $count ->
+    [one] vor einem Tag
+   *[other] vor { $count } Tagen
+}
+
+compass-n = N
+compass-e = O
+compass-s = S
+compass-w = W
+
+sky-clear = klar
+sky-mostly-clear = überwiegend klar
+sky-partly-cloudy = teilweise bewölkt
+sky-mostly-cloudy = überwiegend bewölkt
+sky-overcast = bedeckt
+sky-obscured = nicht erkennbar
+
+intensity-light = leichter { $weather }
+intensity-heavy = starker { $weather }
+intensity-vicinity = { $weather } in der Umgebung
+
+descriptor-shallow = flacher { $phenomenon }
+descriptor-partial = teilweise { $phenomenon }
+descriptor-patches = { $phenomenon }schwaden
+descriptor-low-drifting = { $phenomenon }fegen
+descriptor-blowing = { $phenomenon }treiben
+descriptor-showers = { $phenomenon }schauer
+descriptor-thunderstorm = Gewitter mit { $phenomenon }
+descriptor-freezing = gefrierender { $phenomenon }
+
+weather-showers = Schauer
+weather-thunderstorm = Gewitter
+weather-and = und
+
+phenomenon-drizzle = Nieselregen
+phenomenon-rain = Regen
+phenomenon-snow = Schnee
+phenomenon-snow-grains = Schneegriesel
+phenomenon-ice-crystals = Eisnadeln
+phenomenon-ice-pellets = Eiskörner
+phenomenon-hail = Hagel
+phenomenon-small-hail = Graupel
+phenomenon-unknown-precipitation = unbekannter Niederschlag
+phenomenon-mist = feuchter Dunst
+phenomenon-fog = Nebel
+phenomenon-smoke = Rauch
+phenomenon-volcanic-ash = Vulkanasche
+phenomenon-widespread-dust = verbreiteter Staub
+phenomenon-sand = Sand
+phenomenon-haze = Dunst
+phenomenon-spray = Gischt
+phenomenon-dust-whirls = Staubwirbel
+phenomenon-squalls = Böen
+phenomenon-funnel-cloud = Trichterwolke
+phenomenon-sandstorm = Sandsturm
+phenomenon-duststorm = Staubsturm
+
+station-code = Station
+station-name = Name
+station-state = Bundesstaat
+station-country = Land
+station-coordinates = Koordinaten
+station-elevation = Höhe
+station-wmo-id = WMO-Kennung
+station-last-report = Letzter Bericht
+station-unavailable = nicht verfügbar
+station-reliability = Zuverlässigkeit
+station-reliability-score = { $percent } % pünktlich bei { $checks } Prüfungen
+station-cadence = Takt
+station-cadence-unknown = unbekannt
+
+wind-rose-summary = Windstille { $calm } %, umlaufend { $variable } %, vorherrschend { $prevailing }, { $total ->
+    [one] { $total } Beobachtung
+   *[other] { $total } Beobachtungen
+}
+wind-rose-none = keine
+
+degree-days-total = Summe
+degree-days-base = Basistemperatur: { $temperature } °C
+
+wttr-header = Wetterbericht: { $place }
@@ -0,0 +1,115 @@
+label-observed = Observed
+label-temperature = Temperature
+label-dewpoint = Dew point
+label-frost-point = Frost point
+label-humidity = Humidity
+label-wind = Wind
+label-visibility = Visibility
+label-sky = Sky
+label-ceiling = Ceiling
+label-cloud-base-estimate = Cloud base (estimate)
+label-weather = Weather
+label-pressure = Pressure
+label-advisory = Advisory
+label-warmest = Warmest
+label-coldest = Coldest
+label-windiest = Windiest
+
+wind-calm = calm
+wind-variable = variable at { $speed }
+wind-from = from the { $direction } ({ $azimuth }°) at { $speed }
+wind-gusting = { $speed }, gusting to { $gust }
+wind-varying = { $wind }, varying between { $from }° and { $to }°
+
+advisory-fog = fog likely
+
+age-just-now = just now
+age-minutes = { $count ->
+    [one] { $count } minute ago
+   *[other] { $count } minutes ago
+}
+age-hours = { $count ->
+    [one] { $count } hour ago
+   *[other] { $count } hours ago
+}
+age-days = { $count ->
+    [one] { $count } day ago
+   *[other] { $count } days ago
+}
+
+compass-n = N
+compass-e = E
+compass-s = S
+compass-w = W
+
+sky-clear = clear
+sky-mostly-clear = mostly clear
+sky-partly-cloudy = partly cloudy
+sky-mostly-cloudy = mostly cloudy
+sky-overcast = overcast
+sky-obscured = obscured
+
+intensity-light = light { $weather }
+intensity-heavy = heavy { $weather }
+intensity-vicinity = { $weather } in the vicinity
+
+descriptor-shallow = shallow { $phenomenon }
+descriptor-partial = partial { $phenomenon }
+descriptor-patches = patches of { $phenomenon }
+descriptor-low-drifting = low drifting { $phenomenon }
+descriptor-blowing = blowing { $phenomenon }
+descriptor-showers = { $phenomenon } showers
+descriptor-thunderstorm = thunderstorm with { $phenomenon }
+descriptor-freezing = freezing { $phenomenon }
+
+weather-showers = showers
+weather-thunderstorm = thunderstorm
+weather-and = and
+
+phenomenon-drizzle = drizzle
+phenomenon-rain = rain
+phenomenon-snow = snow
+phenomenon-snow-grains = snow grains
+phenomenon-ice-crystals = ice crystals
+phenomenon-ice-pellets = ice pellets
+phenomenon-hail = hail
+phenomenon-small-hail = small hail
+phenomenon-unknown-precipitation = unknown precipitation
+phenomenon-mist = mist
+phenomenon-fog = fog
+phenomenon-smoke = smoke
+phenomenon-volcanic-ash = volcanic ash
+phenomenon-widespread-dust = widespread dust
+phenomenon-sand = sand
+phenomenon-haze = haze
+phenomenon-spray = spray
+phenomenon-dust-whirls = dust whirls
+phenomenon-squalls = squalls
+phenomenon-funnel-cloud = funnel cloud
+phenomenon-sandstorm = sandstorm
+phenomenon-duststorm = duststorm
+
+station-code = Station
+station-name = Name
+station-state = State
+station-country = Country
+station-coordinates = Coordinates
+station-elevation = Elevation
+station-wmo-id = WMO id
+station-last-report = Last report
+station-unavailable = unavailable
+station-reliability = Reliability
+station-reliability-score = { $percent }% on schedule over { $checks } checks
+station-cadence = Cadence
+station-cadence-unknown = unknown
+
+wind-rose-summary = Calm { $calm }%, variable { $variable }%, prevailing { $prevailing }, { $total ->
+    [one] { $total } observation
+   *[other] { $total } observations
+}
+wind-rose-none = none
+
+degree-days-total = Total
+degree-days-base = Base temperature: { $temperature } °C
+
+wttr-header = Weather report: { $place }
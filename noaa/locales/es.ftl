@@ -0,0 +1,115 @@
+label-observed = Observado
+label-temperature = Temperatura
+label-dewpoint = Punto de rocío
+label-frost-point = Punto de escarcha
+label-humidity = Humedad
+label-wind = Viento
+label-visibility = Visibilidad
+label-sky = Cielo
+label-ceiling = Techo de nubes
+label-cloud-base-estimate = Base de nubes (estimada)
+label-weather = Tiempo
+label-pressure = Presión
+label-advisory = Aviso
+label-warmest = Más cálido
+label-coldest = Más frío
+label-windiest = Más ventoso
+
+wind-calm = calma
+wind-variable = variable a { $speed }
+wind-from = del { $direction } ({ $azimuth }°) a { $speed }
+wind-gusting = { $speed }, con rachas de { $gust }
+wind-varying = { $wind }, variando entre { $from }° y { $to }°
+
+advisory-fog = probable niebla
+
+age-just-now = ahora mismo
+age-minutes = { $count ->
+    [one] hace { $count } minuto
+   *[other] hace { $count } minutos
+}
+age-hours = { $count ->
+    [one] hace { $count } hora
+   *[other] hace { $count } horas
+}
+age-days = { $count ->
+    [one] hace { $count } día
+   *[other] hace { $count } días
+}
+
+compass-n = N
+compass-e = E
+compass-s = S
+compass-w = O
+
+sky-clear = despejado
+sky-mostly-clear = mayormente despejado
+sky-partly-cloudy = parcialmente nublado
+sky-mostly-cloudy = mayormente nublado
+sky-overcast = cubierto
+sky-obscured = oscurecido
+
+intensity-light = { $weather } débil
+intensity-heavy = { $weather } fuerte
+intensity-vicinity = { $weather } en las proximidades
+
+descriptor-shallow = { $phenomenon } en capa baja
+descriptor-partial = { $phenomenon } parcial
+descriptor-patches = bancos de { $phenomenon }
+descriptor-low-drifting = ventisca baja de { $phenomenon }
+descriptor-blowing = ventisca alta de { $phenomenon }
+descriptor-showers = chubascos de { $phenomenon }
+descriptor-thunderstorm = tormenta con { $phenomenon }
+descriptor-freezing = { $phenomenon } engelante
+
+weather-showers = chubascos
+weather-thunderstorm = tormenta
+weather-and = y
+
+phenomenon-drizzle = llovizna
+phenomenon-rain = lluvia
+phenomenon-snow = nieve
+phenomenon-snow-grains = cinarra
+phenomenon-ice-crystals = cristales de hielo
+phenomenon-ice-pellets = hielo granulado
+phenomenon-hail = granizo
+phenomenon-small-hail = granizo pequeño
+phenomenon-unknown-precipitation = precipitación desconocida
+phenomenon-mist = neblina
+phenomenon-fog = niebla
+phenomenon-smoke = humo
+phenomenon-volcanic-ash = ceniza volcánica
+phenomenon-widespread-dust = polvo extendido
+phenomenon-sand = arena
+phenomenon-haze = calima
+phenomenon-spray = rociones
+phenomenon-dust-whirls = remolinos de polvo
+phenomenon-squalls = turbonadas
+phenomenon-funnel-cloud = nube embudo
+phenomenon-sandstorm = tempestad de arena
+phenomenon-duststorm = tempestad de polvo
+
+station-code = Estación
+station-name = Nombre
+station-state = Estado
+station-country = País
+station-coordinates = Coordenadas
+station-elevation = Altitud
+station-wmo-id = Indicativo OMM
+station-last-report = Último informe
+station-unavailable = no disponible
+station-reliability = Fiabilidad
+station-reliability-score = { $percent } % puntual en { $checks } comprobaciones
+station-cadence = Frecuencia
+station-cadence-unknown = desconocida
+
+wind-rose-summary = Calma { $calm } %, variable { $variable } %, predominante { $prevailing }, { $total ->
+    [one] { $total } observación
+   *[other] { $total } observaciones
+}
+wind-rose-none = ninguna
+
+degree-days-total = Total
+degree-days-base = Temperatura base: { $temperature } °C
+
+wttr-header = Informe meteorológico: { $place }
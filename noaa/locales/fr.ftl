@@ -0,0 +1,115 @@
+label-observed = Observé
+label-temperature = Température
+label-dewpoint = Point de rosée
+label-frost-point = Point de givrage
+label-humidity = Humidité
+label-wind = Vent
+label-visibility = Visibilité
+label-sky = Ciel
+label-ceiling = Plafond
+label-cloud-base-estimate = Base des nuages (estimée)
+label-weather = Temps
+label-pressure = Pression
+label-advisory = Avis
+label-warmest = Le plus chaud
+label-coldest = Le plus froid
+label-windiest = Le plus venteux
+
+wind-calm = calme
+wind-variable = variable à { $speed }
+wind-from = du { $direction } ({ $azimuth }°) à { $speed }
+wind-gusting = { $speed }, rafales à { $gust }
+wind-varying = { $wind }, variable entre { $from }° et { $to }°
+
+advisory-fog = brouillard probable
+
+age-just-now = à l'instant
+age-minutes = { $count ->
+    [one] il y a { $count } minute
+   *[other] il y a { $count } minutes
+}
+age-hours = { $count ->
+    [one] il y a { $count } heure
+   *[other] il y a { $count } heures
+}
+age-days = { $count ->
+    [one] il y a { $count } jour
+   *[other] il y a { $count } jours
+}
+
+compass-n = N
+compass-e = E
+compass-s = S
+compass-w = O
+
+sky-clear = dégagé
+sky-mostly-clear = plutôt dégagé
+sky-partly-cloudy = partiellement nuageux
+sky-mostly-cloudy = plutôt nuageux
+sky-overcast = couvert
+sky-obscured = obscurci
+
+intensity-light = { $weather } de faible intensité
+intensity-heavy = { $weather } de forte intensité
+intensity-vicinity = { $weather } au voisinage
+
+descriptor-shallow = { $phenomenon } mince
+descriptor-partial = { $phenomenon } partiel
+descriptor-patches = bancs de { $phenomenon }
+descriptor-low-drifting = chasse-basse de { $phenomenon }
+descriptor-blowing = chasse-haute de { $phenomenon }
+descriptor-showers = averses de { $phenomenon }
+descriptor-thunderstorm = orage avec { $phenomenon }
+descriptor-freezing = { $phenomenon } se congelant
+
+weather-showers = averses
+weather-thunderstorm = orage
+weather-and = et
+
+phenomenon-drizzle = bruine
+phenomenon-rain = pluie
+phenomenon-snow = neige
+phenomenon-snow-grains = neige en grains
+phenomenon-ice-crystals = cristaux de glace
+phenomenon-ice-pellets = granules de glace
+phenomenon-hail = grêle
+phenomenon-small-hail = grésil
+phenomenon-unknown-precipitation = précipitations inconnues
+phenomenon-mist = brume
+phenomenon-fog = brouillard
+phenomenon-smoke = fumée
+phenomenon-volcanic-ash = cendres volcaniques
+phenomenon-widespread-dust = poussière généralisée
+phenomenon-sand = sable
+phenomenon-haze = brume sèche
+phenomenon-spray = embruns
+phenomenon-dust-whirls = tourbillons de poussière
+phenomenon-squalls = grains
+phenomenon-funnel-cloud = nuage en entonnoir
+phenomenon-sandstorm = tempête de sable
+phenomenon-duststorm = tempête de poussière
+
+station-code = Station
+station-name = Nom
+station-state = État
+station-country = Pays
+station-coordinates = Coordonnées
+station-elevation = Altitude
+station-wmo-id = Indicatif OMM
+station-last-report = Dernier rapport
+station-unavailable = indisponible
+station-reliability = Fiabilité
+station-reliability-score = { $percent } % à l’heure sur { $checks } vérifications
+station-cadence = Cadence
+station-cadence-unknown = inconnue
+
+wind-rose-summary = Calme { $calm } %, variable { $variable } %, dominant { $prevailing }, { $total ->
+    [one] { $total } observation
+   *[other] { $total } observations
+}
+wind-rose-none = aucun
+
+degree-days-total = Total
+degree-days-base = Température de base : { $temperature } °C
+
+wttr-header = Bulletin météo : { $place }
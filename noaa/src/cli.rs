@@ -1,4 +1,11 @@
-use clap::{Parser, Subcommand};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::str::FromStr;
+use weathernoaa::format::FormatOptions;
+use weathernoaa::statistics::DEGREE_DAY_BASE_CELSIUS;
 
 #[derive(Parser, Debug)]
 pub struct Cmd {
@@ -14,18 +21,401 @@ pub struct Opt {
     /// Turn on verbose output
     #[clap(short, long, global = true)]
     pub verbose: bool,
+    /// Maximum number of stations fetched concurrently
+    #[clap(short, long, global = true, default_value_t = weathernoaa::weather::DEFAULT_CONCURRENCY)]
+    pub jobs: usize,
+    /// Number of times a request is retried when the network fails or
+    /// NOAA's servers are overloaded
+    #[clap(long, global = true, default_value_t = 2)]
+    pub retries: u32,
+    /// Write the result to this file instead of standard output. The
+    /// file is replaced atomically, so readers never see a partial
+    /// result.
+    #[clap(long, global = true)]
+    pub output_file: Option<PathBuf>,
+    /// Output format. With `json` or `geojson`, errors are also reported
+    /// as JSON on standard error.
+    #[clap(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+    /// Language of the text output. Eg: de, fr. Defaults to the
+    /// language of the environment, falling back to English.
+    #[clap(long, global = true)]
+    pub lang: Option<String>,
+    /// Decimal places of temperatures
+    #[clap(long, global = true, value_name = "DIGITS")]
+    pub temperature_precision: Option<usize>,
+    /// Decimal places of wind speeds
+    #[clap(long, global = true, value_name = "DIGITS")]
+    pub wind_precision: Option<usize>,
+    /// Decimal places of pressures
+    #[clap(long, global = true, value_name = "DIGITS")]
+    pub pressure_precision: Option<usize>,
+    /// Round every value to an integer, for compact output
+    #[clap(long, global = true)]
+    pub round: bool,
+    /// Show the observation time in this timezone instead of UTC. Takes
+    /// an IANA timezone name, eg: Europe/Berlin, or `local` for the
+    /// timezone of the system.
+    #[clap(long, global = true)]
+    pub timezone: Option<Timezone>,
+    /// Show each station on a single line, for prompts and window
+    /// titles. Eg: KSFO 12°C ↗14kt 10mi OVC 1017hPa (25m ago)
+    #[clap(long, global = true)]
+    pub oneline: bool,
+    /// Use Nerd Font glyphs instead of abbreviations for the condition
+    /// in `--oneline` output
+    #[clap(long, global = true)]
+    pub nerd_font: bool,
+    /// List the stations of tables by the severity of their weather,
+    /// worst first, eg: thunderstorms before rain
+    #[clap(long, global = true)]
+    pub worst_first: bool,
+    /// Configuration file holding the favorite stations and groups. Defaults to
+    /// noaa/config.toml in the user's configuration directory.
+    #[clap(long, global = true)]
+    pub config: Option<PathBuf>,
+}
+
+impl Opt {
+    pub(crate) fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            temperature_precision: self.temperature_precision,
+            wind_precision: self.wind_precision,
+            pressure_precision: self.pressure_precision,
+            round: self.round,
+        }
+    }
+
+    /// Global options to pass on to a command run in the background, such
+    /// as the refresh of `prompt`. Only those changing how observations
+    /// are fetched and recorded are passed, the command's output being
+    /// discarded.
+    pub(crate) fn background_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            "--jobs".into(),
+            self.jobs.to_string().into(),
+            "--retries".into(),
+            self.retries.to_string().into(),
+        ];
+        if let Some(config) = &self.config {
+            args.extend(["--config".into(), config.into()]);
+        }
+        args
+    }
+}
+
+/// Timezone in which times are displayed.
+#[derive(Clone, Copy, Debug)]
+pub enum Timezone {
+    /// Timezone of the system
+    Local,
+    /// Timezone from the IANA database
+    Named(Tz),
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(Timezone::Local);
+        }
+        s.parse()
+            .map(Timezone::Named)
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable output
+    Text,
+    /// JSON output
+    Json,
+    /// Rust debug representation of the weather information
+    Debug,
+    /// Current conditions in the layout of wttr.in
+    Wttr,
+    /// GeoJSON FeatureCollection of the observations, for maps
+    Geojson,
+    /// KML placemarks of the observations, for Google Earth
+    Kml,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma separated values, with a header row
+    Csv,
+    /// JSON array of observations
+    Json,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum SubCommand {
-    /// Display Weather Information
+    /// Display Weather Information. Without stations, the weather of
+    /// the favorite stations is displayed.
     Info {
+        #[clap(flatten)]
+        stations: StationArgs,
+        #[clap(flatten)]
+        webhook: WebhookArgs,
+    },
+    /// Display the metadata of a station from the station directory
+    Station {
+        /// Station code
+        station_id: String,
+    },
+    /// Display the weather of a group of stations along with its
+    /// extremes: the warmest, coldest and windiest stations. Eg: noaa
+    /// region @bayarea
+    Region {
+        #[clap(flatten)]
+        stations: StationArgs,
+    },
+    /// Display the weather of all reporting stations in a country
+    Country {
+        /// ISO 3166 alpha-2 code of the country, or its name as used in
+        /// NOAA's station directory. Eg: IN, India, "United States"
+        country: String,
+    },
+    /// Display the weather along the great-circle path between two
+    /// stations, eg: for a flight briefing. The reporting stations of
+    /// the corridor are listed in the order met. Eg: noaa route KSFO KDEN
+    Route {
+        /// Station of departure
+        from: String,
+        /// Station of arrival
+        to: String,
+        /// Largest distance of the stations from the path in nautical
+        /// miles
+        #[clap(long, default_value_t = 50.0)]
+        width_nm: f64,
+        /// Also display the TAFs of the stations issuing one
+        #[clap(long)]
+        taf: bool,
+    },
+    /// Display the weather of a station as a banner, with its
+    /// temperature in large characters next to a drawing of the
+    /// condition, for terminal dashboards and MOTD scripts
+    Banner {
+        /// Station code
+        station_id: String,
+    },
+    /// Print the condition and temperature of a station for embedding in
+    /// shell prompts. Never waits for the network: the last recorded
+    /// observation is shown, and refreshed in the background. Prints
+    /// nothing until the first refresh is done.
+    Prompt {
+        /// Station code
+        station_id: String,
+        /// Print without escape codes, ie: without colors. Also set by
+        /// the NO_COLOR environment variable.
+        #[clap(long)]
+        plain: bool,
+        /// Fetch the observation and record it, as done in the
+        /// background
+        #[clap(long, hide = true)]
+        refresh: bool,
+    },
+    /// Print the observation file of a station as published by NOAA,
+    /// without parsing it
+    Raw {
+        /// Station code
+        station_id: String,
+        /// Print the raw METAR file instead of the decoded file
+        #[clap(long)]
+        metar: bool,
+    },
+    /// Display the observations of a station over the last hours, from
+    /// NOAA's hourly cycle files and, beyond a day, the archive of the
+    /// Aviation Weather Center
+    History {
         /// Station code
-        #[clap(long, default_value = "VOBL")]
         station_id: String,
+        /// Number of hours of observations. NOAA keeps those of the last
+        /// 360 hours, older ones come from the observations recorded by
+        /// previous runs. Each of the last 24 hours is a download of a
+        /// few megabytes.
+        #[clap(long, default_value_t = 6)]
+        hours: u32,
+    },
+    /// Display how often the wind of a station blew from each direction
+    /// over the last hours, and how strongly, as a wind rose
+    WindRose {
+        /// Station code
+        station_id: String,
+        /// Number of hours of observations, at most 360
+        #[clap(long, default_value_t = 24)]
+        hours: u32,
+    },
+    /// Export the observations of a station over a period, for offline
+    /// analysis. NOAA keeps those of the last 15 days, older ones come
+    /// from the observations recorded by previous runs, which are merged
+    /// in, or from an archive given with --iem-csv.
+    Export {
+        /// Station code
+        #[clap(long)]
+        station: String,
+        /// First day of the period, in UTC. Eg: 2024-06-01
+        #[clap(long)]
+        from: NaiveDate,
+        /// Last day of the period, in UTC. Defaults to today.
+        #[clap(long)]
+        to: Option<NaiveDate>,
+        /// Format of the export
+        #[clap(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Read the observations from a CSV archive of the Iowa
+        /// Environmental Mesonet instead of fetching them, for periods
+        /// older than 15 days. Its rows must have the METAR. The
+        /// observations are recorded, for later runs to merge them in.
+        #[clap(long, value_name = "FILE")]
+        iem_csv: Option<PathBuf>,
+    },
+    /// Display the heating and cooling degree days of a station over a
+    /// period, which tell the energy needed for heating and cooling
+    /// buildings
+    #[clap(alias = "degreedays")]
+    DegreeDays {
+        /// Station code
+        #[clap(long)]
+        station: String,
+        /// First day of the period, in UTC. Eg: 2024-06-01
+        #[clap(long)]
+        from: NaiveDate,
+        /// Last day of the period, in UTC. Defaults to today.
+        #[clap(long)]
+        to: Option<NaiveDate>,
+        /// Base temperature in celsius
+        #[clap(long, default_value_t = DEGREE_DAY_BASE_CELSIUS)]
+        base: f64,
+        /// Read the observations from a CSV archive of the Iowa
+        /// Environmental Mesonet instead of fetching them, for periods
+        /// older than 15 days. Its rows must have the METAR. The
+        /// observations are recorded, for later runs to merge them in.
+        #[clap(long, value_name = "FILE")]
+        iem_csv: Option<PathBuf>,
+    },
+    /// Manage the favorite stations. Their names can be used in place of
+    /// a station code.
+    Favorites {
+        #[clap(subcommand)]
+        action: FavoritesAction,
+    },
+    /// Check the configuration file, the connectivity to NOAA's servers
+    /// and the parser, for finding out why fetching fails
+    Doctor,
+    /// Save the observation files of stations into an archive, and
+    /// display the observations of an archive offline. Useful for bug
+    /// reports.
+    Archive {
+        #[clap(subcommand)]
+        action: ArchiveAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FavoritesAction {
+    /// Add a station to the favorites
+    Add {
+        /// Station code
+        station_id: String,
+        /// Name of the favorite, eg: home. Defaults to the station code.
+        #[clap(long)]
+        name: Option<String>,
+    },
+    /// Remove a favorite
+    Remove {
+        /// Name of the favorite
+        name: String,
+    },
+    /// List the favorites
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ArchiveAction {
+    /// Fetch the decoded observation files of the stations into a
+    /// compressed archive
+    Create {
+        /// Path of the archive. Eg: reports.tar.zst
+        file: PathBuf,
+        #[clap(flatten)]
+        stations: StationArgs,
+    },
+    /// Parse and display the observations of an archive
+    Replay {
+        /// Path of the archive
+        file: PathBuf,
     },
 }
 
+/// Arguments for selecting one or more stations.
+#[derive(Args, Debug)]
+pub struct StationArgs {
+    /// Station code, name of a favorite or `@name` of a group of stations
+    /// from the configuration file
+    #[clap(value_name = "STATION")]
+    pub station: Vec<String>,
+    /// Station code, name of a favorite or `@name` of a group. Can be
+    /// repeated or comma separated to fetch multiple stations. Defaults
+    /// to the favorites, or VOBL when there are none.
+    #[clap(long, value_delimiter = ',')]
+    pub station_id: Vec<String>,
+    /// Read station codes from a file, one per line. Text after `#` is
+    /// treated as a comment.
+    #[clap(long)]
+    pub stations_file: Option<PathBuf>,
+    /// Read station codes from standard input, in the same format as
+    /// `--stations-file`
+    #[clap(long)]
+    pub stdin: bool,
+}
+
+/// Arguments for posting the observations to a webhook.
+#[derive(Args, Debug)]
+pub struct WebhookArgs {
+    /// POST each observation as JSON to this URL, after displaying them
+    #[clap(long, value_name = "URL")]
+    pub webhook: Option<String>,
+    /// Sign the webhook requests with HMAC-SHA256 using this secret, in
+    /// the x-weathernoaa-signature-256 header. Ignored without
+    /// `--webhook`, so that it can be exported in a shell profile
+    #[clap(long, env = "NOAA_WEBHOOK_SECRET", hide_env_values = true)]
+    pub webhook_secret: Option<String>,
+}
+
 pub(crate) fn init() -> Cmd {
     Cmd::parse()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background_args() {
+        let cmd = Cmd::parse_from([
+            "noaa",
+            "prompt",
+            "KSFO",
+            "--config",
+            "/tmp/noaa.toml",
+            "--retries",
+            "5",
+            "--output-file",
+            "/tmp/prompt.txt",
+        ]);
+        let mut args = cmd.opt.background_args();
+        args.extend(["prompt".into(), "KSFO".into(), "--refresh".into()]);
+        let refresh = Cmd::parse_from(std::iter::once("noaa".into()).chain(args));
+        assert_eq!(refresh.opt.config, Some(PathBuf::from("/tmp/noaa.toml")));
+        assert_eq!(refresh.opt.retries, 5);
+        assert_eq!(refresh.opt.jobs, cmd.opt.jobs);
+        assert_eq!(refresh.opt.output_file, None);
+        assert!(matches!(
+            refresh.sub,
+            SubCommand::Prompt { refresh: true, .. }
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::output;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Days after which the cached station directory is refreshed, by
+/// default.
+const DIRECTORY_MAX_AGE_DAYS: u64 = 7;
+
+/// Settings kept between runs in a TOML file. Eg:
+///
+/// ```toml
+/// directory_max_age_days = 30
+///
+/// [favorites]
+/// home = "KSFO"
+/// VOBL = "VOBL"
+///
+/// [groups.bayarea]
+/// stations = ["KSFO", "KOAK", "KSJC"]
+/// ```
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub(crate) struct Config {
+    /// Days after which the cached station directory is refreshed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) directory_max_age_days: Option<u64>,
+    /// Favorite stations by name. The name can be used in place of the
+    /// station code.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) favorites: BTreeMap<String, String>,
+    /// Groups of stations by name, addressed as `@name` in place of a
+    /// station code.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) groups: BTreeMap<String, Group>,
+}
+
+/// A named group of stations.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub(crate) struct Group {
+    /// Station codes or names of favorites
+    pub(crate) stations: Vec<String>,
+}
+
+impl Config {
+    /// Location of the configuration file in the user's configuration
+    /// directory. Eg: ~/.config/noaa/config.toml on Linux
+    pub(crate) fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("noaa").join("config.toml"))
+    }
+
+    /// Location of the cached station directory in the user's cache
+    /// directory. Eg: ~/.cache/noaa/nsd_cccc.txt on Linux
+    pub(crate) fn directory_cache_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("noaa").join("nsd_cccc.txt"))
+    }
+
+    /// Location of the observations kept for showing trends, in the
+    /// user's cache directory. Eg: ~/.cache/noaa/observations on Linux
+    pub(crate) fn observation_store_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("noaa").join("observations"))
+    }
+
+    /// Age after which the cached station directory is refreshed.
+    pub(crate) fn directory_max_age(&self) -> Duration {
+        let days = self
+            .directory_max_age_days
+            .unwrap_or(DIRECTORY_MAX_AGE_DAYS);
+        Duration::from_secs(days.saturating_mul(24 * 60 * 60))
+    }
+
+    /// Reads the configuration file, which doesn't need to exist.
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("Invalid configuration file {}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(err)
+                .with_context(|| format!("Failed to read configuration file {}", path.display())),
+        }
+    }
+
+    /// Writes the configuration file, creating its directory if needed.
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+        }
+        output::write(Some(path), &toml::to_string_pretty(self)?)
+    }
+
+    /// The station code of a favorite's name, or the argument itself
+    /// when it isn't the name of a favorite.
+    pub(crate) fn resolve(&self, station: &str) -> String {
+        self.favorites
+            .get(station)
+            .cloned()
+            .unwrap_or_else(|| station.to_owned())
+    }
+
+    /// The station codes of a group when the argument is `@name`, or else
+    /// the resolved station code.
+    pub(crate) fn expand(&self, station: &str) -> Result<Vec<String>> {
+        let Some(name) = station.strip_prefix('@') else {
+            return Ok(vec![self.resolve(station)]);
+        };
+        let Some(group) = self.groups.get(name) else {
+            bail!("No group named {} in the configuration file", name);
+        };
+        Ok(group
+            .stations
+            .iter()
+            .map(|station| self.resolve(station))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let config: Config = toml::from_str(
+            r#"
+[favorites]
+home = "KSFO"
+
+[groups.bayarea]
+stations = ["home", "KOAK", "KSJC"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.resolve("home"), "KSFO");
+        assert_eq!(config.resolve("VOBL"), "VOBL");
+        assert_eq!(config.expand("home").unwrap(), ["KSFO"]);
+        assert_eq!(config.expand("VOBL").unwrap(), ["VOBL"]);
+        assert_eq!(config.expand("@bayarea").unwrap(), ["KSFO", "KOAK", "KSJC"]);
+        let err = config.expand("@nowhere").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No group named nowhere in the configuration file"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use crate::config::Config;
+use serde::Serialize;
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::net::{lookup_host, TcpStream};
+use weathernoaa::directory::DirectoryCache;
+use weathernoaa::format::humanize_duration;
+use weathernoaa::weather::{parse_weather, NoaaApp, ParseError};
+
+/// Host serving the observations and the station directory.
+const NOAA_HOST: &str = "tgftp.nws.noaa.gov";
+
+/// Station whose file is requested for checking HTTPS.
+const PROBE_STATION: &str = "VOBL";
+
+/// Decoded report checked against the parser.
+const FIXTURE: &str = include_str!("../fixtures/VOBL.TXT");
+
+/// Time allowed to each network check.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of a diagnostic check.
+#[derive(Serialize, Debug)]
+pub(crate) struct Check {
+    pub(crate) name: &'static str,
+    pub(crate) passed: bool,
+    pub(crate) detail: String,
+    /// Time taken by network checks, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) millis: Option<u128>,
+}
+
+impl Check {
+    fn new(name: &'static str, result: Result<String, String>) -> Self {
+        let passed = result.is_ok();
+        Check {
+            name,
+            passed,
+            detail: result.unwrap_or_else(|err| err),
+            millis: None,
+        }
+    }
+
+    /// Runs a network check, failing it when it takes longer than
+    /// [TIMEOUT].
+    async fn timed<F>(name: &'static str, check: F) -> Self
+    where
+        F: Future<Output = Result<String, String>>,
+    {
+        let start = Instant::now();
+        let result = match tokio::time::timeout(TIMEOUT, check).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("timed out after {}s", TIMEOUT.as_secs())),
+        };
+        Check {
+            millis: Some(start.elapsed().as_millis()),
+            ..Check::new(name, result)
+        }
+    }
+}
+
+/// Runs the checks in order, network checks being skipped once the host
+/// can't be reached.
+pub(crate) async fn run(
+    app: &NoaaApp,
+    config_path: Option<&Path>,
+    directory_cache: Option<&DirectoryCache>,
+) -> Vec<Check> {
+    let mut checks = vec![
+        check_config(config_path),
+        check_directory_cache(directory_cache),
+        check_parser(),
+    ];
+    let dns = Check::timed("dns", async {
+        let mut addresses = lookup_host((NOAA_HOST, 443))
+            .await
+            .map_err(|err| format!("{}: {}", NOAA_HOST, err))?;
+        let address = addresses
+            .next()
+            .ok_or_else(|| format!("{} has no address", NOAA_HOST))?;
+        Ok(format!("{} resolves to {}", NOAA_HOST, address.ip()))
+    })
+    .await;
+    let reachable = dns.passed;
+    checks.push(dns);
+    if !reachable {
+        return checks;
+    }
+    let tcp = Check::timed("tcp", async {
+        TcpStream::connect((NOAA_HOST, 443))
+            .await
+            .map_err(|err| format!("connecting to {}:443: {}", NOAA_HOST, err))?;
+        Ok(format!("connected to {}:443", NOAA_HOST))
+    })
+    .await;
+    let reachable = tcp.passed;
+    checks.push(tcp);
+    if !reachable {
+        return checks;
+    }
+    checks.push(
+        Check::timed("https", async {
+            app.get_last_modified(PROBE_STATION)
+                .await
+                .map_err(|err| err.to_string())?;
+            Ok(format!("TLS handshake and request of {}", PROBE_STATION))
+        })
+        .await,
+    );
+    checks.push(
+        Check::timed("directory", async {
+            let directory = app
+                .get_station_directory()
+                .await
+                .map_err(|err| err.to_string())?;
+            match directory.stations().len() {
+                0 => Err("the station directory is empty".to_owned()),
+                count => Ok(format!("{} stations", count)),
+            }
+        })
+        .await,
+    );
+    checks
+}
+
+fn check_config(path: Option<&Path>) -> Check {
+    let result = match path {
+        None => Ok("no configuration directory, using defaults".to_owned()),
+        Some(path) if !path.exists() => Ok(format!("{} doesn't exist", path.display())),
+        Some(path) => Config::load(path)
+            .map(|config| {
+                format!(
+                    "{}: {} favorite(s), {} group(s)",
+                    path.display(),
+                    config.favorites.len(),
+                    config.groups.len()
+                )
+            })
+            .map_err(|err| one_line(&format!("{:#}", err))),
+    };
+    Check::new("config", result)
+}
+
+/// Keeps the first and last lines of a message, dropping the source
+/// excerpt TOML errors show in between.
+fn one_line(message: &str) -> String {
+    let mut lines = message.lines();
+    let first = lines.next().unwrap_or_default();
+    match lines.last() {
+        Some(last) => format!("{}: {}", first, last),
+        None => first.to_owned(),
+    }
+}
+
+fn check_directory_cache(cache: Option<&DirectoryCache>) -> Check {
+    let result = match cache {
+        None => Ok("no cache directory, the directory is downloaded on use".to_owned()),
+        Some(cache) if !cache.path.exists() => {
+            Ok(format!("{} doesn't exist yet", cache.path.display()))
+        }
+        Some(cache) => match cache.read() {
+            Some((directory, _)) if directory.stations().is_empty() => {
+                Err(format!("{} has no stations", cache.path.display()))
+            }
+            Some((directory, age)) => Ok(format!(
+                "{} station(s), updated {}{}",
+                directory.stations().len(),
+                humanize_duration(age),
+                if age >= cache.max_age {
+                    ", refreshing"
+                } else {
+                    ""
+                }
+            )),
+            None => Err(format!("{} can't be read", cache.path.display())),
+        },
+    };
+    Check::new("cache", result)
+}
+
+fn check_parser() -> Check {
+    let result = match parse_weather(FIXTURE) {
+        Ok((_, info)) if info.pressure == 1017 => Ok("parsed the bundled report".to_owned()),
+        Ok(_) => Err("the bundled report was parsed incorrectly".to_owned()),
+        Err(err) => Err(ParseError::decoded(FIXTURE, err).to_string()),
+    };
+    Check::new("parser", result)
+}
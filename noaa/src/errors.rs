@@ -0,0 +1,157 @@
+use crate::cli::OutputFormat;
+use std::fmt;
+use weathernoaa::weather::WeatherError;
+
+/// Broad class of a failure. Each class maps to its own exit code so
+/// that wrapper scripts can branch on failures without parsing the
+/// error message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ErrorClass {
+    Other,
+    Network,
+    StationNotFound,
+    Http,
+    Parse,
+    NoReport,
+}
+
+impl ErrorClass {
+    pub(crate) fn of(error: &WeatherError) -> Self {
+        match error {
+            WeatherError::Http { status, .. } if status.as_u16() == 404 => {
+                ErrorClass::StationNotFound
+            }
+            WeatherError::Http { .. } => ErrorClass::Http,
+            WeatherError::ReqwestError(err) => match err.status() {
+                Some(status) if status.as_u16() == 404 => ErrorClass::StationNotFound,
+                Some(_) => ErrorClass::Http,
+                None if err.is_decode() => ErrorClass::Parse,
+                None => ErrorClass::Network,
+            },
+            WeatherError::Parse(_) | WeatherError::Implausible { .. } => ErrorClass::Parse,
+            WeatherError::NoReport { .. } => ErrorClass::NoReport,
+            WeatherError::Cancelled | WeatherError::Cassette(_) => ErrorClass::Other,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ErrorClass::Other => "other",
+            ErrorClass::Network => "network",
+            ErrorClass::StationNotFound => "station_not_found",
+            ErrorClass::Http => "http",
+            ErrorClass::Parse => "parse",
+            ErrorClass::NoReport => "no_report",
+        }
+    }
+
+    /// Exit code of the process. 2 is skipped since clap uses it for
+    /// usage errors.
+    pub(crate) fn exit_code(self) -> i32 {
+        match self {
+            ErrorClass::Other => 1,
+            ErrorClass::Network => 3,
+            ErrorClass::StationNotFound => 4,
+            ErrorClass::Http => 5,
+            ErrorClass::Parse => 6,
+            ErrorClass::NoReport => 7,
+        }
+    }
+}
+
+/// Returned when fetching one or more stations failed. The individual
+/// failures have already been reported when this is constructed.
+#[derive(Debug)]
+pub(crate) struct FetchFailed {
+    pub failures: usize,
+    /// Class of the first failure, which decides the exit code.
+    pub class: ErrorClass,
+}
+
+impl fmt::Display for FetchFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to fetch weather for {} station(s)",
+            self.failures
+        )
+    }
+}
+
+impl std::error::Error for FetchFailed {}
+
+/// Reports the failure of a single station on standard error.
+pub(crate) fn report_station(format: OutputFormat, station: &str, error: &WeatherError) {
+    match format {
+        OutputFormat::Json | OutputFormat::Geojson => {
+            let value = serde_json::json!({
+                "error": ErrorClass::of(error).name(),
+                "station": station,
+                "message": error.to_string(),
+            });
+            eprintln!("{}", value);
+        }
+        OutputFormat::Text | OutputFormat::Debug | OutputFormat::Wttr | OutputFormat::Kml => {
+            eprintln!("{}: {}", station, error)
+        }
+    }
+}
+
+/// Reports an error that made the whole command fail and returns the
+/// exit code to use.
+pub(crate) fn report(format: OutputFormat, error: &anyhow::Error) -> i32 {
+    if let Some(failed) = error.downcast_ref::<FetchFailed>() {
+        if !matches!(format, OutputFormat::Json | OutputFormat::Geojson) {
+            eprintln!("Error: {}", failed);
+        }
+        return failed.class.exit_code();
+    }
+    match format {
+        OutputFormat::Json | OutputFormat::Geojson => {
+            let value = serde_json::json!({
+                "error": ErrorClass::Other.name(),
+                "message": format!("{:#}", error),
+            });
+            eprintln!("{}", value);
+        }
+        OutputFormat::Text | OutputFormat::Debug | OutputFormat::Wttr | OutputFormat::Kml => {
+            eprintln!("Error: {:?}", error)
+        }
+    }
+    ErrorClass::Other.exit_code()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_exit_codes() {
+        let codes = [
+            (ErrorClass::Other, 1),
+            (ErrorClass::Network, 3),
+            (ErrorClass::StationNotFound, 4),
+            (ErrorClass::Http, 5),
+            (ErrorClass::Parse, 6),
+            (ErrorClass::NoReport, 7),
+        ];
+        for (class, code) in codes {
+            assert_eq!(class.exit_code(), code, "{}", class.name());
+        }
+
+        let http = |status: u16| WeatherError::Http {
+            status: StatusCode::from_u16(status).unwrap(),
+            url: "https://tgftp.nws.noaa.gov/data/observations/metar/decoded/XXXX.TXT".into(),
+            retry_after: None,
+            last_modified: None,
+        };
+        assert_eq!(ErrorClass::of(&http(404)), ErrorClass::StationNotFound);
+        assert_eq!(ErrorClass::of(&http(503)), ErrorClass::Http);
+        let no_report = WeatherError::NoReport {
+            station: "XXXX".into(),
+        };
+        assert_eq!(ErrorClass::of(&no_report), ErrorClass::NoReport);
+        assert_eq!(ErrorClass::of(&WeatherError::Cancelled), ErrorClass::Other);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::trends::{self, ObservationStore};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use weathernoaa::iem::read_iem_csv;
+use weathernoaa::weather::{NoaaApp, WeatherInfo, ARCHIVE_HOURS};
+
+const CSV_HEADER: [&str; 15] = [
+    "station",
+    "time",
+    "temperature_c",
+    "temperature_f",
+    "dewpoint_c",
+    "dewpoint_depression_c",
+    "relative_humidity",
+    "wind_direction",
+    "wind_azimuth",
+    "wind_knots",
+    "wind_mph",
+    "gust_knots",
+    "visibility",
+    "pressure_hpa",
+    "weather",
+];
+
+/// Start and end, exclusive, of the days from `from` to `to` in UTC.
+/// Fails when the period ends before the observations available from
+/// NOAA's cycle files and the archive, unless older observations are
+/// available locally, from `oldest_local` on.
+pub(crate) fn period(
+    from: NaiveDate,
+    to: NaiveDate,
+    now: DateTime<Utc>,
+    oldest_local: Option<DateTime<Utc>>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    if from > to {
+        bail!(
+            "The start of the period ({}) is after its end ({})",
+            from,
+            to
+        );
+    }
+    let midnight =
+        |date: NaiveDate| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default());
+    let (start, end) = (midnight(from), midnight(to) + Duration::days(1));
+    let local = oldest_local.is_some_and(|oldest| oldest < end);
+    if !local && !is_available(end, now) {
+        bail!(
+            "Only the observations of the last {} hours are available",
+            ARCHIVE_HOURS
+        );
+    }
+    Ok((start, end))
+}
+
+/// Whether NOAA still has observations of a period ending at `end`.
+fn is_available(end: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    now - end <= Duration::hours(i64::from(ARCHIVE_HOURS))
+}
+
+/// Observations of a station over the days from `from` to `to`, which
+/// defaults to today, in chronological order. They're read from the CSV
+/// archive of the Iowa Environmental Mesonet at `iem_csv`, which are
+/// loaded into the `store` for later runs, or else are those recorded in
+/// the `store` merged with those fetched.
+pub(crate) async fn observations(
+    app: &NoaaApp,
+    station: &str,
+    from: NaiveDate,
+    to: Option<NaiveDate>,
+    iem_csv: Option<&Path>,
+    store: Option<&ObservationStore>,
+) -> Result<Vec<WeatherInfo>> {
+    let now = Utc::now();
+    let to = to.unwrap_or(now.date_naive());
+    let (start, end, history) = match iem_csv {
+        Some(path) => {
+            let archive = File::open(path)
+                .map(BufReader::new)
+                .and_then(read_iem_csv)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            if let Some(store) = store {
+                import(store, &archive);
+            }
+            let mut history: Vec<WeatherInfo> = archive
+                .into_iter()
+                .filter(|info| {
+                    info.metar
+                        .as_ref()
+                        .is_some_and(|metar| metar.station.eq_ignore_ascii_case(station))
+                })
+                .collect();
+            // Archives are in chronological order, whereas the history
+            // is newest first
+            history.reverse();
+            // Imported archives are assumed to cover the period
+            let (start, end) = period(from, to, now, Some(DateTime::<Utc>::MIN_UTC))?;
+            (start, end, history)
+        }
+        None => {
+            let recorded = store
+                .map(|store| store.history(station))
+                .unwrap_or_default();
+            let oldest = recorded
+                .iter()
+                .filter_map(|info| info.weather_time.to_datetime())
+                .min();
+            let (start, end) = period(from, to, now, oldest)?;
+            let fetched = if is_available(end, now) {
+                app.get_history(station, hours_since(start, now)).await?
+            } else {
+                vec![]
+            };
+            if let Some(store) = store {
+                store.record(station, &fetched);
+            }
+            (start, end, trends::merge(&fetched, recorded))
+        }
+    };
+    let mut period: Vec<WeatherInfo> = history
+        .into_iter()
+        .filter(|info| {
+            info.weather_time
+                .to_datetime()
+                .is_some_and(|time| start <= time && time < end)
+        })
+        .collect();
+    period.reverse();
+    Ok(period)
+}
+
+/// Records the observations of an archive in the store, by station.
+fn import(store: &ObservationStore, archive: &[WeatherInfo]) {
+    let mut stations: BTreeMap<&str, Vec<WeatherInfo>> = BTreeMap::new();
+    for info in archive {
+        if let Some(metar) = &info.metar {
+            stations
+                .entry(&metar.station)
+                .or_default()
+                .push(info.clone());
+        }
+    }
+    for (station, observations) in stations {
+        store.record(station, &observations);
+    }
+}
+
+/// Hours of history to fetch for covering a period starting at `start`,
+/// as far as available.
+pub(crate) fn hours_since(start: DateTime<Utc>, now: DateTime<Utc>) -> u32 {
+    let minutes = (now - start).num_minutes().max(0);
+    ((minutes + 59) / 60).min(i64::from(ARCHIVE_HOURS)) as u32
+}
+
+/// Formats observations as CSV, with one observation per row along with
+/// the values derived from it.
+pub(crate) fn csv(station: &str, history: &[WeatherInfo]) -> Result<String> {
+    let mut out = String::new();
+    writeln!(out, "{}", CSV_HEADER.join(","))?;
+    for info in history {
+        let time = info
+            .weather_time
+            .to_datetime()
+            .map(|time| time.to_rfc3339())
+            .unwrap_or_else(|| info.weather_time.to_string());
+        let weather: Vec<String> = info
+            .weather_groups()
+            .iter()
+            .map(|g| g.to_string())
+            .collect();
+        let row = [
+            station.to_owned(),
+            time,
+            info.temperature.celsius.to_string(),
+            info.temperature.fahrenheit.to_string(),
+            info.dewpoint.celsius.to_string(),
+            (info.temperature.celsius - info.dewpoint.celsius).to_string(),
+            info.relative_humidity.to_string(),
+            info.wind.cardinal.clone(),
+            info.wind.azimuth.to_string(),
+            info.wind.knots.to_string(),
+            info.wind.mph.to_string(),
+            info.wind
+                .gust_knots
+                .map(|g| g.to_string())
+                .unwrap_or_default(),
+            info.visibility.trim_end_matches(":0").to_owned(),
+            info.pressure.to_string(),
+            weather.join("; "),
+        ];
+        let row: Vec<String> = row.iter().map(|field| escape(field)).collect();
+        writeln!(out, "{}", row.join(","))?;
+    }
+    Ok(out)
+}
+
+/// Quotes a CSV field when it contains a separator, quote or newline.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use weathernoaa::weather::parse_weather;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 12, day).unwrap()
+    }
+
+    #[test]
+    fn test_period() {
+        let now = Utc.with_ymd_and_hms(2023, 12, 30, 12, 0, 0).unwrap();
+        assert!(period(date(30), date(29), now, None).is_err());
+        let (start, end) = period(date(29), date(30), now, None).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2023, 12, 29, 0, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2023, 12, 31, 0, 0, 0).unwrap());
+        // Older than the archive, unless recorded or imported
+        assert!(period(date(1), date(2), now, None).is_err());
+        let recorded = Utc.with_ymd_and_hms(2023, 12, 2, 12, 0, 0).unwrap();
+        assert!(period(date(1), date(2), now, Some(recorded)).is_ok());
+        assert!(period(date(1), date(1), now, Some(recorded)).is_err());
+    }
+
+    #[test]
+    fn test_hours_since() {
+        let now = Utc.with_ymd_and_hms(2023, 12, 30, 12, 0, 0).unwrap();
+        let ago = |minutes: i64| hours_since(now - Duration::minutes(minutes), now);
+        assert_eq!(ago(60), 1);
+        assert_eq!(ago(61), 2);
+        assert_eq!(ago(-30), 0);
+        assert_eq!(ago(60 * 24 * 30), ARCHIVE_HOURS);
+    }
+
+    #[test]
+    fn test_import() {
+        let archive = "station,valid,tmpf,metar
+SFO,2023-12-30 13:56,53.60,KSFO 301356Z 22014KT 10SM OVC008 12/11 A3002
+OAK,2023-12-30 13:53,55.00,KOAK 301353Z 24010KT 10SM FEW010 13/10 A3003
+SFO,2023-12-30 14:56,53.60,KSFO 301456Z 22014KT 10SM OVC008 12/11 A3002
+";
+        let dir = std::env::temp_dir().join(format!("noaa-import-{}", std::process::id()));
+        let store = ObservationStore::new(dir.clone());
+        let archive = read_iem_csv(archive.as_bytes()).unwrap();
+        import(&store, &archive);
+        // Importing twice doesn't record them twice
+        import(&store, &archive);
+        assert_eq!(store.history("KSFO").len(), 2);
+        assert_eq!(store.history("KOAK").len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(escape("VOGO"), "VOGO");
+        assert_eq!(escape("Goa, India"), "\"Goa, India\"");
+        assert_eq!(escape("5\" of rain"), "\"5\"\" of rain\"");
+
+        let report = "Station name not available
+Dec 30, 2023 - 07:30 AM EST / 2023.12.30 1230 UTC
+Wind: from the NNW (340 degrees) at 7 MPH (6 KT):0
+Visibility: 3 mile(s):0
+Temperature: 69 F (21 C)
+Dew Point: 64 F (18 C)
+Relative Humidity: 83%
+Pressure (altimeter): 29.83 in. Hg (1010 hPa)
+ob: VOGO 301230Z 34006KT 5000 HZ NSC 21/18 Q1010
+cycle: 12
+";
+        let (_, info) = parse_weather(report).unwrap();
+        let out = csv("VOGO", std::slice::from_ref(&info)).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(',').count(), CSV_HEADER.len());
+        assert_eq!(lines[1].split(',').count(), CSV_HEADER.len());
+        assert!(lines[1].starts_with("VOGO,2023-12-30T12:30:00+00:00,21,"));
+        let out = csv("Goa, India", &[info]).unwrap();
+        assert!(out.lines().nth(1).unwrap().starts_with("\"Goa, India\","));
+    }
+}
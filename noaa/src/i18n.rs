@@ -0,0 +1,242 @@
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use std::time::Duration;
+use unic_langid::LanguageIdentifier;
+use weathernoaa::metar::{Descriptor, Intensity, Phenomenon, WeatherGroup};
+
+/// Languages with a translation, along with their Fluent resource.
+const LOCALES: [(&str, &str); 4] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+    ("es", include_str!("../locales/es.ftl")),
+    ("fr", include_str!("../locales/fr.ftl")),
+];
+
+/// Translates the labels and condition descriptions of the text
+/// output. Messages missing from a translation fall back to English.
+pub(crate) struct Localizer {
+    bundle: FluentBundle<FluentResource>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+impl Localizer {
+    /// Creates a localizer for `lang`, or for the language of the
+    /// environment (`LC_ALL`, then `LANG`) when not given. Unsupported
+    /// languages fall back to English.
+    pub(crate) fn new(lang: Option<&str>) -> Self {
+        let lang = lang
+            .map(|lang| lang.to_owned())
+            .or_else(|| std::env::var("LC_ALL").ok().filter(|l| !l.is_empty()))
+            .or_else(|| std::env::var("LANG").ok())
+            .unwrap_or_default();
+        // Strip the territory and encoding, eg: de_DE.UTF-8
+        let lang = lang
+            .split(['_', '.', '-', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        Localizer {
+            bundle: bundle(&lang),
+            fallback: bundle("en"),
+        }
+    }
+
+    /// Translates a message without arguments.
+    pub(crate) fn tr(&self, id: &str) -> String {
+        self.tr_args(id, None)
+    }
+
+    /// Translates a message. Returns the message id when it is missing
+    /// from both the translation and English.
+    pub(crate) fn tr_args(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in [&self.bundle, &self.fallback] {
+            if let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) {
+                let mut errors = vec![];
+                return bundle
+                    .format_pattern(pattern, args, &mut errors)
+                    .into_owned();
+            }
+        }
+        id.to_owned()
+    }
+
+    /// Translates a message with a single argument.
+    pub(crate) fn tr_with(&self, id: &str, name: &'static str, value: String) -> String {
+        let mut args = FluentArgs::new();
+        args.set(name, value);
+        self.tr_args(id, Some(&args))
+    }
+
+    /// Describes how long ago something happened, eg: 23 minutes ago.
+    pub(crate) fn age(&self, age: Duration) -> String {
+        let minutes = age.as_secs() / 60;
+        let (id, count) = match minutes {
+            0 => return self.tr("age-just-now"),
+            1..=59 => ("age-minutes", minutes),
+            60..=2879 => ("age-hours", minutes / 60),
+            _ => ("age-days", minutes / 1440),
+        };
+        let mut args = FluentArgs::new();
+        args.set("count", count);
+        self.tr_args(id, Some(&args))
+    }
+
+    /// Translates a cardinal direction such as "NNW" letter by letter.
+    pub(crate) fn cardinal(&self, cardinal: &str) -> String {
+        cardinal
+            .chars()
+            .map(|c| match c {
+                'N' => self.tr("compass-n"),
+                'E' => self.tr("compass-e"),
+                'S' => self.tr("compass-s"),
+                'W' => self.tr("compass-w"),
+                c => c.to_string(),
+            })
+            .collect()
+    }
+
+    /// Translates a sky condition in the wording of NOAA's decoded
+    /// files. Unknown wordings are returned as is.
+    pub(crate) fn sky_condition(&self, sky: &str) -> String {
+        let id = match sky {
+            "clear" => "sky-clear",
+            "mostly clear" => "sky-mostly-clear",
+            "partly cloudy" => "sky-partly-cloudy",
+            "mostly cloudy" => "sky-mostly-cloudy",
+            "overcast" => "sky-overcast",
+            "obscured" => "sky-obscured",
+            _ => return sky.to_owned(),
+        };
+        self.tr(id)
+    }
+
+    /// Describes a weather group, eg: light rain showers.
+    pub(crate) fn weather_group(&self, group: &WeatherGroup) -> String {
+        let phenomena: Vec<String> = group
+            .phenomena
+            .iter()
+            .map(|p| self.tr(phenomenon_id(*p)))
+            .collect();
+        let phenomena = phenomena.join(&format!(" {} ", self.tr("weather-and")));
+        let weather = match (group.descriptor, phenomena.is_empty()) {
+            (Some(Descriptor::Showers), true) => self.tr("weather-showers"),
+            (Some(Descriptor::Thunderstorm), true) => self.tr("weather-thunderstorm"),
+            (Some(descriptor), _) => self
+                .tr_with(descriptor_id(descriptor), "phenomenon", phenomena)
+                .trim()
+                .to_owned(),
+            (None, _) => phenomena,
+        };
+        match group.intensity {
+            Intensity::Moderate => weather,
+            Intensity::Light => self.tr_with("intensity-light", "weather", weather),
+            Intensity::Heavy => self.tr_with("intensity-heavy", "weather", weather),
+            Intensity::Vicinity => self.tr_with("intensity-vicinity", "weather", weather),
+        }
+    }
+}
+
+fn bundle(lang: &str) -> FluentBundle<FluentResource> {
+    let (lang, source) = LOCALES
+        .iter()
+        .find(|(l, _)| *l == lang)
+        .unwrap_or(&LOCALES[0]);
+    let langid: LanguageIdentifier = lang.parse().expect("Invalid language identifier");
+    let mut bundle = FluentBundle::new(vec![langid]);
+    // Unicode isolation marks only get in the way on a terminal
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string()).expect("Invalid Fluent resource");
+    bundle
+        .add_resource(resource)
+        .expect("Duplicate Fluent message");
+    bundle
+}
+
+fn descriptor_id(descriptor: Descriptor) -> &'static str {
+    match descriptor {
+        Descriptor::Shallow => "descriptor-shallow",
+        Descriptor::Partial => "descriptor-partial",
+        Descriptor::Patches => "descriptor-patches",
+        Descriptor::LowDrifting => "descriptor-low-drifting",
+        Descriptor::Blowing => "descriptor-blowing",
+        Descriptor::Showers => "descriptor-showers",
+        Descriptor::Thunderstorm => "descriptor-thunderstorm",
+        Descriptor::Freezing => "descriptor-freezing",
+    }
+}
+
+fn phenomenon_id(phenomenon: Phenomenon) -> &'static str {
+    match phenomenon {
+        Phenomenon::Drizzle => "phenomenon-drizzle",
+        Phenomenon::Rain => "phenomenon-rain",
+        Phenomenon::Snow => "phenomenon-snow",
+        Phenomenon::SnowGrains => "phenomenon-snow-grains",
+        Phenomenon::IceCrystals => "phenomenon-ice-crystals",
+        Phenomenon::IcePellets => "phenomenon-ice-pellets",
+        Phenomenon::Hail => "phenomenon-hail",
+        Phenomenon::SmallHail => "phenomenon-small-hail",
+        Phenomenon::UnknownPrecipitation => "phenomenon-unknown-precipitation",
+        Phenomenon::Mist => "phenomenon-mist",
+        Phenomenon::Fog => "phenomenon-fog",
+        Phenomenon::Smoke => "phenomenon-smoke",
+        Phenomenon::VolcanicAsh => "phenomenon-volcanic-ash",
+        Phenomenon::WidespreadDust => "phenomenon-widespread-dust",
+        Phenomenon::Sand => "phenomenon-sand",
+        Phenomenon::Haze => "phenomenon-haze",
+        Phenomenon::Spray => "phenomenon-spray",
+        Phenomenon::DustWhirls => "phenomenon-dust-whirls",
+        Phenomenon::Squalls => "phenomenon-squalls",
+        Phenomenon::FunnelCloud => "phenomenon-funnel-cloud",
+        Phenomenon::Sandstorm => "phenomenon-sandstorm",
+        Phenomenon::Duststorm => "phenomenon-duststorm",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language() {
+        let german = Localizer::new(Some("de_DE.UTF-8"));
+        assert_eq!(german.tr("label-humidity"), "Luftfeuchtigkeit");
+        assert_eq!(german.cardinal("ENE"), "ONO");
+        let unsupported = Localizer::new(Some("xx_XX"));
+        assert_eq!(unsupported.tr("label-humidity"), "Humidity");
+    }
+
+    #[test]
+    fn test_fallback() {
+        let resource = FluentResource::try_new("label-wind = Vent\n".to_owned()).unwrap();
+        let mut bundle = FluentBundle::new(vec!["fr".parse().unwrap()]);
+        bundle.add_resource(resource).unwrap();
+        let partial = Localizer {
+            bundle,
+            fallback: super::bundle("en"),
+        };
+        assert_eq!(partial.tr("label-wind"), "Vent");
+        assert_eq!(partial.tr("label-humidity"), "Humidity");
+        assert_eq!(partial.tr("label-missing"), "label-missing");
+    }
+
+    #[test]
+    fn test_weather_group() {
+        let group = WeatherGroup {
+            intensity: Intensity::Light,
+            descriptor: Some(Descriptor::Showers),
+            phenomena: vec![Phenomenon::Rain, Phenomenon::Snow],
+        };
+        let english = Localizer::new(Some("en"));
+        assert_eq!(english.weather_group(&group), "light rain and snow showers");
+        let german = Localizer::new(Some("de_DE.UTF-8"));
+        assert_eq!(
+            german.weather_group(&group),
+            "leichter Regen und Schneeschauer"
+        );
+        let showers = WeatherGroup {
+            intensity: Intensity::Vicinity,
+            descriptor: Some(Descriptor::Showers),
+            phenomena: vec![],
+        };
+        assert_eq!(english.weather_group(&showers), "showers in the vicinity");
+    }
+}
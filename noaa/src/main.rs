@@ -1,18 +1,457 @@
 mod cli;
+mod config;
+mod doctor;
+mod errors;
+mod export;
+mod i18n;
+mod output;
+mod render;
+mod stations;
+mod trends;
 
-use anyhow::Result;
-use cli::SubCommand;
+use anyhow::{bail, Context, Result};
+use cli::{ArchiveAction, Cmd, ExportFormat, FavoritesAction, Opt, OutputFormat, SubCommand};
+use config::Config;
+use errors::{ErrorClass, FetchFailed};
+use futures::stream::{self, StreamExt};
+use std::fs::File;
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
+use trends::ObservationStore;
+use weathernoaa::archive::{read_archive, ArchiveWriter};
+use weathernoaa::directory::DirectoryCache;
+use weathernoaa::geo::KM_PER_NAUTICAL_MILE;
+use weathernoaa::region::RegionReport;
+use weathernoaa::retry::RetryPolicy;
+use weathernoaa::sink::{Sink, WebhookSink};
+use weathernoaa::statistics::DegreeDays;
 use weathernoaa::weather::*;
+use weathernoaa::windrose::WindRose;
+
+/// Interval between the background refreshes of the observation shown
+/// by `prompt`. NOAA publishes one observation per station and hour.
+const PROMPT_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     let cmd = cli::init();
-    let app = NoaaApp::new();
+    let format = cmd.opt.output;
+    let app = new_app(&cmd.opt);
+    let result = run(cmd, app.clone()).await;
+    // The clones share the refresh of the station directory cache
+    app.wait_for_directory_refresh().await;
+    if let Err(err) = result {
+        std::process::exit(errors::report(format, &err));
+    }
+}
+
+fn new_app(opt: &Opt) -> NoaaApp {
+    let mut app = NoaaApp::new()
+        .with_metar_fallback(true)
+        .with_retry_policy(RetryPolicy {
+            max_retries: opt.retries,
+            ..RetryPolicy::default()
+        });
+    if opt.verbose {
+        app = app.with_retry_observer(|event| {
+            eprintln!(
+                "Retrying {} in {}s: {}",
+                event.url,
+                event.wait.as_secs(),
+                event.error
+            );
+        });
+    }
+    app
+}
+
+async fn run(cmd: Cmd, mut app: NoaaApp) -> Result<()> {
+    let format = cmd.opt.output;
+    let renderer = render::Renderer::new(&cmd.opt);
+    let config_path = cmd.opt.config.clone().or_else(Config::default_path);
+    let mut config = match (&cmd.sub, &config_path) {
+        // The doctor reports problems with the configuration file itself
+        (SubCommand::Doctor, _) | (_, None) => Config::default(),
+        (_, Some(path)) => Config::load(path)?,
+    };
+    let directory_cache = Config::directory_cache_path()
+        .map(|path| DirectoryCache::new(path, config.directory_max_age()));
+    match (&cmd.sub, &directory_cache) {
+        // The doctor downloads the directory for checking connectivity
+        (SubCommand::Doctor, _) | (_, None) => {}
+        (_, Some(cache)) => app = app.with_directory_cache(cache.clone()),
+    }
     match cmd.sub {
-        SubCommand::Info { station_id } => {
-            let result = app.get_weather(&station_id).await?;
-            println!("{:#?}", result);
+        SubCommand::Info { stations, webhook } => {
+            let station_id = stations.stations(&config)?;
+            let results = app.get_weather_many(&station_id, cmd.opt.jobs).await;
+            let (reports, failed) = collect_reports(format, results, false);
+            let renderer = match Config::observation_store_path() {
+                Some(path) => renderer.with_trends(ObservationStore::new(path).update(&reports)),
+                None => renderer,
+            };
+            // Groups are compared side by side
+            let out = match format {
+                OutputFormat::Text if stations.has_group() && !cmd.opt.oneline => {
+                    renderer.render_table(&reports)?
+                }
+                _ => renderer.render(&reports, station_id.len() == 1)?,
+            };
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+            if let Some(url) = webhook.webhook {
+                let mut sink = WebhookSink::new(url).with_retry_policy(RetryPolicy {
+                    max_retries: cmd.opt.retries,
+                    ..RetryPolicy::default()
+                });
+                if let Some(secret) = webhook.webhook_secret {
+                    sink = sink.with_secret(secret);
+                }
+                // The sink sends blocking requests
+                tokio::task::spawn_blocking(move || {
+                    reports
+                        .iter()
+                        .try_for_each(|(station, info)| sink.write(station, info))
+                })
+                .await?
+                .context("Failed to post the observations to the webhook")?;
+            }
+            if let Some(failed) = failed {
+                return Err(failed.into());
+            }
+        }
+        SubCommand::Station { station_id } => {
+            let station_id = config.resolve(&station_id);
+            let (directory, last_modified) = tokio::join!(
+                app.get_station_directory(),
+                app.get_last_modified(&station_id)
+            );
+            let directory = directory?;
+            let Some(station) = directory.get(&station_id) else {
+                bail!("Station {} not found in the directory", station_id);
+            };
+            let age = match last_modified {
+                Ok(time) => time.and_then(|time| SystemTime::now().duration_since(time).ok()),
+                Err(err) => {
+                    errors::report_station(format, &station_id, &err);
+                    None
+                }
+            };
+            let reliability = Config::observation_store_path()
+                .and_then(|path| ObservationStore::new(path).reliability(&station.icao));
+            let out = renderer.render_station(station, age, reliability.as_ref())?;
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+        }
+        SubCommand::Region { stations } => {
+            let station_id = stations.stations(&config)?;
+            let results = app.get_weather_many(&station_id, cmd.opt.jobs).await;
+            let (reports, failed) = collect_reports(format, results, false);
+            let out = renderer.render_region(&RegionReport::new(reports))?;
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+            if let Some(failed) = failed {
+                return Err(failed.into());
+            }
+        }
+        SubCommand::Country { country } => {
+            let results = app.get_weather_for_country(&country, cmd.opt.jobs).await?;
+            if results.is_empty() {
+                bail!("No stations found for country {}", country);
+            }
+            // Most stations in the directory aren't reporting, so
+            // missing observations aren't treated as failures.
+            let (reports, failed) = collect_reports(format, results, true);
+            let out = match format {
+                OutputFormat::Text if !cmd.opt.oneline => renderer.render_table(&reports)?,
+                _ => renderer.render(&reports, false)?,
+            };
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+            if let Some(failed) = failed {
+                return Err(failed.into());
+            }
+        }
+        SubCommand::Route {
+            from,
+            to,
+            width_nm,
+            taf,
+        } => {
+            let (from, to) = (config.resolve(&from), config.resolve(&to));
+            let directory = app.get_station_directory().await?;
+            let point = |station: &str| {
+                directory
+                    .get(station)
+                    .map(|s| s.point())
+                    .with_context(|| format!("Station {} not found in the directory", station))
+            };
+            let (start, end) = (point(&from)?, point(&to)?);
+            let corridor = directory.along_route(&start, &end, width_nm * KM_PER_NAUTICAL_MILE);
+            let codes: Vec<&str> = corridor.iter().map(|(s, _)| s.icao.as_str()).collect();
+            let results = app.get_weather_many(&codes, cmd.opt.jobs).await;
+            // Many stations of the directory don't report, which isn't
+            // a failure
+            let (reports, failed) = collect_reports(format, results, true);
+            let legs: Vec<(String, f64, WeatherInfo)> = reports
+                .into_iter()
+                .map(|(station, info)| {
+                    let along = corridor
+                        .iter()
+                        .find(|(s, _)| s.icao == station)
+                        .map_or(0.0, |(_, along)| along / KM_PER_NAUTICAL_MILE);
+                    (station, along, info)
+                })
+                .collect();
+            let tafs = if taf {
+                let app = &app;
+                let results: Vec<_> = stream::iter(&legs)
+                    .map(|(station, _, _)| async move {
+                        (station.clone(), app.get_taf_report(station).await)
+                    })
+                    .buffered(cmd.opt.jobs.max(1))
+                    .collect()
+                    .await;
+                // Only airports issue TAFs
+                collect_reports(format, results, true).0
+            } else {
+                Vec::new()
+            };
+            let out = renderer.render_route(&legs, &tafs)?;
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+            if let Some(failed) = failed {
+                return Err(failed.into());
+            }
+        }
+        SubCommand::Banner { station_id } => {
+            let station_id = config.resolve(&station_id);
+            let info = app.get_weather(&station_id).await?;
+            let out = renderer.render_banner(&station_id, &info)?;
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
         }
+        SubCommand::Prompt {
+            station_id,
+            plain,
+            refresh,
+        } => {
+            let station_id = config.resolve(&station_id);
+            let Some(path) = Config::observation_store_path() else {
+                bail!("No cache directory found for recording the observations");
+            };
+            let store = ObservationStore::new(path);
+            if refresh {
+                let info = app.get_weather(&station_id).await?;
+                store.update(&[(station_id, info)]);
+                return Ok(());
+            }
+            if store.claim_refresh(&station_id, PROMPT_REFRESH_INTERVAL) {
+                // Not waited for, the refresh outlives this process
+                let args = cmd.opt.background_args();
+                let spawned = std::env::current_exe().and_then(|exe| {
+                    Command::new(exe)
+                        .args(args)
+                        .args(["prompt", &station_id, "--refresh"])
+                        .stdin(Stdio::null())
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .spawn()
+                });
+                // Prompts stay quiet, the previous observation being
+                // shown until a refresh succeeds
+                if let (Err(err), true) = (spawned, cmd.opt.verbose) {
+                    eprintln!(
+                        "Failed to refresh {} in the background: {}",
+                        station_id, err
+                    );
+                }
+            }
+            let plain =
+                plain || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            let out = store
+                .latest(&station_id)
+                .map(|info| renderer.render_prompt(&info, plain))
+                .unwrap_or_default();
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+        }
+        SubCommand::Raw { station_id, metar } => {
+            let station_id = config.resolve(&station_id);
+            let report = if metar {
+                app.get_metar_report(&station_id).await?
+            } else {
+                app.get_decoded_report(&station_id).await?
+            };
+            output::write(cmd.opt.output_file.as_deref(), &report)?;
+        }
+        SubCommand::History { station_id, hours } => {
+            let station_id = config.resolve(&station_id);
+            let fetched = app.get_history(&station_id, hours).await?;
+            // Merged with the observations recorded by previous runs,
+            // which go further back than NOAA's
+            let history = match Config::observation_store_path() {
+                Some(path) => {
+                    let since = chrono::Utc::now() - chrono::Duration::hours(i64::from(hours));
+                    ObservationStore::new(path).merge_history(&station_id, &fetched, since)
+                }
+                None => fetched,
+            };
+            if history.is_empty() {
+                bail!(
+                    "No observations of {} in the last {} hours",
+                    station_id,
+                    hours
+                );
+            }
+            let out = renderer.render_history(&station_id, &history)?;
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+        }
+        SubCommand::WindRose { station_id, hours } => {
+            let station_id = config.resolve(&station_id);
+            let history = app.get_history(&station_id, hours).await?;
+            if history.is_empty() {
+                bail!(
+                    "No observations of {} in the last {} hours",
+                    station_id,
+                    hours
+                );
+            }
+            let out = renderer.render_wind_rose(&WindRose::new(&history))?;
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+        }
+        SubCommand::Export {
+            station,
+            from,
+            to,
+            format,
+            iem_csv,
+        } => {
+            let station = config.resolve(&station);
+            let store = Config::observation_store_path().map(ObservationStore::new);
+            let period =
+                export::observations(&app, &station, from, to, iem_csv.as_deref(), store.as_ref())
+                    .await?;
+            let out = match format {
+                ExportFormat::Csv => export::csv(&station, &period)?,
+                ExportFormat::Json => format!("{}\n", serde_json::to_string_pretty(&period)?),
+            };
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+        }
+        SubCommand::DegreeDays {
+            station,
+            from,
+            to,
+            base,
+            iem_csv,
+        } => {
+            let station = config.resolve(&station);
+            let store = Config::observation_store_path().map(ObservationStore::new);
+            let period =
+                export::observations(&app, &station, from, to, iem_csv.as_deref(), store.as_ref())
+                    .await?;
+            if period.is_empty() {
+                bail!("No observations of {} in the period", station);
+            }
+            let out = renderer.render_degree_days(&DegreeDays::new(&period, base))?;
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+        }
+        SubCommand::Favorites { action } => {
+            let Some(path) = config_path else {
+                bail!("No configuration directory found, pass the file with --config");
+            };
+            match action {
+                FavoritesAction::Add { station_id, name } => {
+                    let station_id = station_id.to_uppercase();
+                    let name = name.unwrap_or_else(|| station_id.clone());
+                    config.favorites.insert(name, station_id);
+                    config.save(&path)?;
+                }
+                FavoritesAction::Remove { name } => {
+                    if config.favorites.remove(&name).is_none() {
+                        bail!("No favorite named {}", name);
+                    }
+                    config.save(&path)?;
+                }
+                FavoritesAction::List => {
+                    let out = renderer.render_favorites(&config.favorites)?;
+                    output::write(cmd.opt.output_file.as_deref(), &out)?;
+                }
+            }
+        }
+        SubCommand::Doctor => {
+            let checks = doctor::run(&app, config_path.as_deref(), directory_cache.as_ref()).await;
+            let out = renderer.render_checks(&checks)?;
+            output::write(cmd.opt.output_file.as_deref(), &out)?;
+            let failed = checks.iter().filter(|check| !check.passed).count();
+            if failed > 0 {
+                bail!("{} of {} checks failed", failed, checks.len());
+            }
+        }
+        SubCommand::Archive { action } => match action {
+            ArchiveAction::Create { file, stations } => {
+                let station_id = stations.stations(&config)?;
+                let app = &app;
+                let results: Vec<_> = stream::iter(&station_id)
+                    .map(|code| async move {
+                        let report = app.get_decoded_report(code).await;
+                        (
+                            code.clone(),
+                            report.map(|report| (report, SystemTime::now())),
+                        )
+                    })
+                    .buffered(cmd.opt.jobs.max(1))
+                    .collect()
+                    .await;
+                let (reports, failed) = collect_reports(format, results, false);
+                let mut writer = ArchiveWriter::new(Vec::new())?;
+                for (station, (report, fetched_at)) in &reports {
+                    writer.add(station, report, *fetched_at)?;
+                }
+                // Written atomically, an interrupted run leaving no
+                // truncated archive
+                output::write(Some(&file), writer.finish()?)?;
+                if let Some(failed) = failed {
+                    return Err(failed.into());
+                }
+            }
+            ArchiveAction::Replay { file } => {
+                let archive = File::open(&file)
+                    .and_then(read_archive)
+                    .with_context(|| format!("Failed to read {}", file.display()))?;
+                let results = archive
+                    .iter()
+                    .map(|report| (report.station.clone(), report.parse()))
+                    .collect();
+                let (reports, failed) = collect_reports(format, results, false);
+                let out = renderer.render(&reports, archive.len() == 1)?;
+                output::write(cmd.opt.output_file.as_deref(), &out)?;
+                if let Some(failed) = failed {
+                    return Err(failed.into());
+                }
+            }
+        },
     }
     Ok(())
 }
+
+/// Separates the successfully fetched reports from the failures, which
+/// are reported as they are encountered.
+fn collect_reports<T>(
+    format: OutputFormat,
+    results: Vec<(String, Result<T, WeatherError>)>,
+    skip_not_found: bool,
+) -> (Vec<(String, T)>, Option<FetchFailed>) {
+    let mut reports = vec![];
+    let mut failed: Option<FetchFailed> = None;
+    for (station, result) in results {
+        match result {
+            Ok(report) => reports.push((station, report)),
+            Err(err) => {
+                let class = ErrorClass::of(&err);
+                let not_reporting =
+                    matches!(class, ErrorClass::StationNotFound | ErrorClass::NoReport);
+                if skip_not_found && not_reporting {
+                    continue;
+                }
+                errors::report_station(format, &station, &err);
+                let failed = failed.get_or_insert(FetchFailed { failures: 0, class });
+                failed.failures += 1;
+            }
+        }
+    }
+    (reports, failed)
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Writes the formatted result, text or binary, either to standard
+/// output or, when a path is given, atomically to that file.
+pub(crate) fn write(path: Option<&Path>, content: impl AsRef<[u8]>) -> Result<()> {
+    let content = content.as_ref();
+    match path {
+        Some(path) => write_atomic(path, content)
+            .with_context(|| format!("Failed to write output to {}", path.display())),
+        None => {
+            io::stdout().write_all(content)?;
+            Ok(())
+        }
+    }
+}
+
+/// Writes `content` to a temporary file next to `path` and renames it
+/// over the destination. The rename is atomic as long as both files are
+/// on the same filesystem, which is why the temporary file isn't placed
+/// in the system temp directory.
+fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a file path"))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = std::env::temp_dir().join(format!("noaa-output-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("weather.txt");
+        fs::write(&path, "old content, longer than the new one").unwrap();
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        // Only the destination is left
+        let names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["weather.txt"]);
+        assert!(write_atomic(Path::new("/"), b"new").is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
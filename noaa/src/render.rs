@@ -0,0 +1,790 @@
+use crate::cli::{Opt, OutputFormat, Timezone};
+use crate::doctor::Check;
+use crate::i18n::Localizer;
+use anyhow::Result;
+use chrono::{Local, Utc};
+use fluent_bundle::FluentArgs;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::time::Duration;
+use weathernoaa::change::{Trend, Trends};
+use weathernoaa::directory::StationInfo;
+use weathernoaa::format::FormatOptions;
+use weathernoaa::geojson;
+use weathernoaa::icon::{Condition, IconSet};
+use weathernoaa::kml;
+use weathernoaa::metar::MetarVisibility;
+use weathernoaa::region::{Extreme, RegionReport};
+use weathernoaa::reliability::Reliability;
+use weathernoaa::statistics::DegreeDays;
+use weathernoaa::weather::{Origin, WeatherInfo, WeatherTime, WindInfo};
+use weathernoaa::windrose::{WindRose, SECTORS, SPEED_CLASSES_KNOTS};
+
+/// Renders results in the output format, language and formatting
+/// options selected on the command line.
+pub(crate) struct Renderer {
+    format: OutputFormat,
+    localizer: Localizer,
+    options: FormatOptions,
+    timezone: Option<Timezone>,
+    oneline: bool,
+    icons: IconSet,
+    worst_first: bool,
+    /// Trends of the stations since their previous observation
+    trends: HashMap<String, Trends>,
+}
+
+impl Renderer {
+    pub(crate) fn new(opt: &Opt) -> Self {
+        Renderer {
+            format: opt.output,
+            localizer: Localizer::new(opt.lang.as_deref()),
+            options: opt.format_options(),
+            timezone: opt.timezone,
+            oneline: opt.oneline,
+            icons: if opt.nerd_font {
+                IconSet::NerdFont
+            } else {
+                IconSet::Ascii
+            },
+            worst_first: opt.worst_first,
+            trends: HashMap::new(),
+        }
+    }
+
+    /// Shows the trends of the stations since their previous
+    /// observation, by arrows after the temperature and pressure.
+    pub(crate) fn with_trends(mut self, trends: HashMap<String, Trends>) -> Self {
+        self.trends = trends;
+        self
+    }
+
+    /// Arrow of a trend of a station, with a leading space. Empty when
+    /// the trend isn't known.
+    fn trend_arrow(&self, station: &str, trend: fn(&Trends) -> Trend) -> String {
+        self.trends.get(station).map_or(String::new(), |trends| {
+            format!(" {}", trend(trends).arrow())
+        })
+    }
+
+    /// Renders the successfully fetched reports. When `single` is set,
+    /// the station code isn't repeated in the output.
+    pub(crate) fn render(&self, reports: &[(String, WeatherInfo)], single: bool) -> Result<String> {
+        let mut out = String::new();
+        match self.format {
+            OutputFormat::Text if self.oneline => {
+                let now = Utc::now();
+                for (station, info) in reports {
+                    let trends = self.trends.get(station).copied();
+                    let line =
+                        self.options
+                            .oneline_with_trends(info, self.icons, Some(now), trends);
+                    writeln!(out, "{} {}", station, line)?;
+                }
+            }
+            OutputFormat::Text => {
+                for (index, (station, info)) in reports.iter().enumerate() {
+                    if index > 0 {
+                        writeln!(out)?;
+                    }
+                    self.render_summary(&mut out, station, info)?;
+                }
+            }
+            OutputFormat::Wttr => {
+                for (index, (station, info)) in reports.iter().enumerate() {
+                    if index > 0 {
+                        writeln!(out)?;
+                    }
+                    let place = info.station.as_ref().map_or(station.as_str(), |s| &s.place);
+                    let header = self
+                        .localizer
+                        .tr_with("wttr-header", "place", place.to_owned());
+                    writeln!(out, "{}", header)?;
+                    writeln!(out)?;
+                    out.push_str(&self.options.wttr(info));
+                }
+            }
+            OutputFormat::Debug => {
+                for (station, info) in reports {
+                    if single {
+                        writeln!(out, "{:#?}", info)?;
+                    } else {
+                        writeln!(out, "{}: {:#?}", station, info)?;
+                    }
+                }
+            }
+            OutputFormat::Json => {
+                let value = match reports {
+                    [(_, info)] if single => serde_json::to_value(info)?,
+                    [] if single => return Ok(out),
+                    _ => reports
+                        .iter()
+                        .map(|(station, info)| serde_json::json!({ "station": station, "weather": info }))
+                        .collect(),
+                };
+                writeln!(out, "{}", serde_json::to_string_pretty(&value)?)?;
+            }
+            OutputFormat::Geojson => {
+                let collection = geojson::to_feature_collection(reports);
+                writeln!(out, "{}", serde_json::to_string_pretty(&collection)?)?;
+            }
+            OutputFormat::Kml => out.push_str(&kml::to_kml(reports, &self.options)),
+        }
+        Ok(out)
+    }
+
+    /// Renders a report as a banner headed by the place of the station.
+    /// Formats other than text render it as [Renderer::render] does.
+    pub(crate) fn render_banner(&self, station: &str, info: &WeatherInfo) -> Result<String> {
+        match self.format {
+            OutputFormat::Text | OutputFormat::Wttr => {
+                let place = info.station.as_ref().map_or(station, |s| &s.place);
+                Ok(format!("{}\n\n{}", place, self.options.banner(info)))
+            }
+            _ => self.render(&[(station.to_owned(), info.clone())], true),
+        }
+    }
+
+    /// Renders the condition and temperature of a report on a line for
+    /// shell prompts. Eg: OVC 12°C. Unless `plain`, the temperature is
+    /// colored from blue when freezing to red when hot.
+    pub(crate) fn render_prompt(&self, info: &WeatherInfo, plain: bool) -> String {
+        let celsius = info.temperature.celsius;
+        let temperature = format!("{}°C", FormatOptions::compact().temperature(celsius));
+        let temperature = if plain {
+            temperature
+        } else {
+            // ANSI foreground colors
+            let color = match celsius {
+                c if c < 0.0 => 34,
+                c if c < 10.0 => 36,
+                c if c < 25.0 => 32,
+                c if c < 30.0 => 33,
+                _ => 31,
+            };
+            format!("\x1b[{}m{}\x1b[0m", color, temperature)
+        };
+        format!("{} {}\n", info.glyph(self.icons), temperature)
+    }
+
+    /// Renders a report as labelled lines in the selected language.
+    fn render_summary(&self, out: &mut String, station: &str, info: &WeatherInfo) -> Result<()> {
+        let (localizer, options) = (&self.localizer, &self.options);
+        match &info.station {
+            Some(place) => writeln!(out, "{}, {} ({})", place.place, place.country, station)?,
+            None => writeln!(out, "{}", station)?,
+        }
+        let mut observed = self.observation_time(&info.weather_time);
+        if let Some(age) = info.weather_time.age(Utc::now()) {
+            write!(observed, " ({})", localizer.age(age))?;
+        }
+        let mut rows = vec![
+            ("label-observed", observed),
+            (
+                "label-temperature",
+                options.celsius_fahrenheit(&info.temperature)
+                    + &self.trend_arrow(station, |t| t.temperature),
+            ),
+            ("label-dewpoint", options.celsius_fahrenheit(&info.dewpoint)),
+        ];
+        // The frost point only differs from the dewpoint below freezing
+        if info.dewpoint.celsius < 0.0 {
+            rows.push((
+                "label-frost-point",
+                options.celsius_fahrenheit(&info.frost_point()),
+            ));
+        }
+        rows.extend([
+            ("label-humidity", format!("{}%", info.relative_humidity)),
+            ("label-wind", self.describe_wind(&info.wind)),
+            ("label-visibility", describe_visibility(info)),
+        ]);
+        if let Some(sky) = &info.sky_condition {
+            rows.push(("label-sky", localizer.sky_condition(sky)));
+        }
+        if let Some(ceiling) = info.ceiling_ft() {
+            rows.push(("label-ceiling", format!("{} ft", ceiling)));
+        }
+        if needs_cloud_base_estimate(info) {
+            let base = (info.estimated_cloud_base_ft() / 100.0).round() * 100.0;
+            rows.push(("label-cloud-base-estimate", format!("~{} ft", base)));
+        }
+        let groups = info.weather_groups();
+        if !groups.is_empty() {
+            let weather: Vec<String> = groups.iter().map(|g| localizer.weather_group(g)).collect();
+            rows.push(("label-weather", weather.join(", ")));
+        } else if let Some(weather) = &info.weather {
+            rows.push(("label-weather", weather.clone()));
+        }
+        rows.push((
+            "label-pressure",
+            format!(
+                "{} hPa{}",
+                options.pressure(f64::from(info.pressure)),
+                self.trend_arrow(station, |t| t.pressure)
+            ),
+        ));
+        // Not worth telling when fog is already reported
+        if info.fog_risk() && info.condition() != Condition::Fog {
+            rows.push(("label-advisory", localizer.tr("advisory-fog")));
+        }
+
+        self.write_labeled(out, "  ", rows)
+    }
+
+    /// Writes a row per value, headed by its translated label, the
+    /// values being aligned.
+    fn write_labeled(
+        &self,
+        out: &mut String,
+        indent: &str,
+        rows: Vec<(&str, String)>,
+    ) -> Result<()> {
+        let rows: Vec<(String, String)> = rows
+            .into_iter()
+            .map(|(label, value)| (self.localizer.tr(label), value))
+            .collect();
+        let width = rows
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or_default();
+        for (label, value) in rows {
+            let padding = width - label.chars().count();
+            writeln!(
+                out,
+                "{}{}:{}  {}",
+                indent,
+                label,
+                " ".repeat(padding),
+                value
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The observation time in the selected timezone, or as reported
+    /// when no timezone was selected.
+    fn observation_time(&self, time: &WeatherTime) -> String {
+        let converted = match self.timezone {
+            None => None,
+            Some(Timezone::Local) => time
+                .in_timezone(&Local)
+                .map(|time| time.format("%Y-%m-%d %H:%M %:z").to_string()),
+            Some(Timezone::Named(tz)) => time
+                .in_timezone(&tz)
+                .map(|time| time.format("%Y-%m-%d %H:%M %Z").to_string()),
+        };
+        converted.unwrap_or_else(|| time.to_string())
+    }
+
+    fn describe_wind(&self, wind: &WindInfo) -> String {
+        let localizer = &self.localizer;
+        if wind.knots == 0.0 {
+            return localizer.tr("wind-calm");
+        }
+        let mut args = FluentArgs::new();
+        let mut speed = self.options.wind_speed(wind);
+        if let Some(gust) = self.options.gust_speed(wind) {
+            let mut gust_args = FluentArgs::new();
+            gust_args.set("speed", speed);
+            gust_args.set("gust", gust);
+            speed = localizer.tr_args("wind-gusting", Some(&gust_args));
+        }
+        args.set("speed", speed);
+        // The decoded files have no direction for variable winds
+        if wind.cardinal == WindInfo::default().cardinal {
+            return localizer.tr_args("wind-variable", Some(&args));
+        }
+        args.set("direction", localizer.cardinal(&wind.cardinal));
+        args.set("azimuth", wind.azimuth.to_string());
+        let description = localizer.tr_args("wind-from", Some(&args));
+        match wind.variable_range {
+            Some((from, to)) => {
+                let mut args = FluentArgs::new();
+                args.set("wind", description);
+                args.set("from", from.to_string());
+                args.set("to", to.to_string());
+                localizer.tr_args("wind-varying", Some(&args))
+            }
+            None => description,
+        }
+    }
+
+    /// Renders the reports as a table with one station per row, in the
+    /// order given or by severity with `--worst-first`.
+    pub(crate) fn render_table(&self, reports: &[(String, WeatherInfo)]) -> Result<String> {
+        let options = &self.options;
+        let mut reports: Vec<&(String, WeatherInfo)> = reports.iter().collect();
+        if self.worst_first {
+            // Stable, stations of the same severity keep their order
+            reports.sort_by_key(|(_, info)| std::cmp::Reverse(info.severity()));
+        }
+        let mut rows = vec![[
+            "STATION".to_owned(),
+            "PLACE".to_owned(),
+            "TEMP".to_owned(),
+            "HUMIDITY".to_owned(),
+            "WIND".to_owned(),
+            "PRESSURE".to_owned(),
+            "SKY".to_owned(),
+        ]];
+        for (station, info) in reports {
+            rows.push([
+                station.clone(),
+                info.station
+                    .as_ref()
+                    .map(|s| s.place.clone())
+                    .unwrap_or_default(),
+                format!("{} °C", options.temperature(info.temperature.celsius)),
+                format!("{}%", info.relative_humidity),
+                format!(
+                    "{} {} kt",
+                    info.wind.cardinal,
+                    options.compact_wind_speed(&info.wind)
+                ),
+                format!("{} hPa", options.pressure(f64::from(info.pressure))),
+                info.sky_condition.clone().unwrap_or_default(),
+            ]);
+        }
+        write_table(&rows)
+    }
+
+    /// Renders the observations of a group of stations side by side,
+    /// followed by their extremes.
+    pub(crate) fn render_region(&self, report: &RegionReport) -> Result<String> {
+        match self.format {
+            OutputFormat::Text | OutputFormat::Wttr => {
+                let mut out = self.render_table(&report.observations)?;
+                let options = &self.options;
+                let temperature =
+                    |e: &Extreme| format!("{} ({} °C)", e.station, options.temperature(e.value));
+                let wind = |e: &Extreme| format!("{} ({} kt)", e.station, options.wind(e.value));
+                let extremes = vec![
+                    ("label-warmest", report.warmest.as_ref().map(temperature)),
+                    ("label-coldest", report.coldest.as_ref().map(temperature)),
+                    ("label-windiest", report.windiest.as_ref().map(wind)),
+                ];
+                let rows: Vec<(String, String)> = extremes
+                    .into_iter()
+                    .filter_map(|(label, value)| Some((self.localizer.tr(label), value?)))
+                    .collect();
+                let width = rows
+                    .iter()
+                    .map(|(label, _)| label.chars().count())
+                    .max()
+                    .unwrap_or_default();
+                if !rows.is_empty() {
+                    writeln!(out)?;
+                }
+                for (label, value) in rows {
+                    let padding = width - label.chars().count();
+                    writeln!(out, "{}:{}  {}", label, " ".repeat(padding), value)?;
+                }
+                Ok(out)
+            }
+            OutputFormat::Debug => Ok(format!("{:#?}\n", report)),
+            OutputFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(report)?)),
+            OutputFormat::Geojson => {
+                let collection = geojson::to_feature_collection(&report.observations);
+                Ok(format!("{}\n", serde_json::to_string_pretty(&collection)?))
+            }
+            OutputFormat::Kml => Ok(kml::to_kml(&report.observations, &self.options)),
+        }
+    }
+
+    /// Renders the observations of the stations along a route, with
+    /// their distance from the departure in nautical miles, followed by
+    /// their TAFs.
+    pub(crate) fn render_route(
+        &self,
+        legs: &[(String, f64, WeatherInfo)],
+        tafs: &[(String, String)],
+    ) -> Result<String> {
+        let taf = |station: &str| {
+            tafs.iter()
+                .find(|(s, _)| s == station)
+                .map(|(_, taf)| taf.trim().to_owned())
+        };
+        match self.format {
+            OutputFormat::Text | OutputFormat::Wttr => {
+                let now = Utc::now();
+                let mut rows = vec![["NM".to_owned(), "STATION".to_owned(), "WEATHER".to_owned()]];
+                for (station, along, info) in legs {
+                    rows.push([
+                        format!("{:.0}", along),
+                        station.clone(),
+                        self.options.oneline(info, self.icons, Some(now)),
+                    ]);
+                }
+                let mut out = write_table(&rows)?;
+                for (_, taf) in tafs {
+                    writeln!(out)?;
+                    writeln!(out, "{}", taf.trim())?;
+                }
+                Ok(out)
+            }
+            OutputFormat::Json => {
+                let legs: Vec<serde_json::Value> = legs
+                    .iter()
+                    .map(|(station, along, info)| {
+                        serde_json::json!({
+                            "station": station,
+                            "along_nm": along,
+                            "weather": info,
+                            "taf": taf(station),
+                        })
+                    })
+                    .collect();
+                Ok(format!("{}\n", serde_json::to_string_pretty(&legs)?))
+            }
+            OutputFormat::Debug | OutputFormat::Geojson | OutputFormat::Kml => {
+                let reports: Vec<(String, WeatherInfo)> = legs
+                    .iter()
+                    .map(|(station, _, info)| (station.clone(), info.clone()))
+                    .collect();
+                self.render(&reports, false)
+            }
+        }
+    }
+
+    /// Renders the observations of a station, newest first, as a table
+    /// with one observation per row.
+    pub(crate) fn render_history(&self, station: &str, history: &[WeatherInfo]) -> Result<String> {
+        let options = &self.options;
+        match self.format {
+            OutputFormat::Text | OutputFormat::Wttr => {
+                let mut rows = vec![[
+                    "TIME".to_owned(),
+                    "TEMP".to_owned(),
+                    "DEWPOINT".to_owned(),
+                    "HUMIDITY".to_owned(),
+                    "WIND".to_owned(),
+                    "VISIBILITY".to_owned(),
+                    "PRESSURE".to_owned(),
+                    "WEATHER".to_owned(),
+                    "SOURCE".to_owned(),
+                ]];
+                for info in history {
+                    let weather: Vec<String> = info
+                        .weather_groups()
+                        .iter()
+                        .map(|g| g.to_string())
+                        .collect();
+                    rows.push([
+                        self.observation_time(&info.weather_time),
+                        format!("{} °C", options.temperature(info.temperature.celsius)),
+                        format!("{} °C", options.temperature(info.dewpoint.celsius)),
+                        format!("{}%", info.relative_humidity),
+                        format!(
+                            "{} {} kt",
+                            info.wind.cardinal,
+                            options.compact_wind_speed(&info.wind)
+                        ),
+                        info.visibility.trim_end_matches(":0").to_owned(),
+                        format!("{} hPa", options.pressure(f64::from(info.pressure))),
+                        weather.join(", "),
+                        match info.provenance.origin {
+                            Origin::Station => "station",
+                            Origin::Cycle => "cycle",
+                            Origin::Archive => "archive",
+                            Origin::Import => "import",
+                        }
+                        .to_owned(),
+                    ]);
+                }
+                write_table(&rows)
+            }
+            OutputFormat::Debug => Ok(format!("{:#?}\n", history)),
+            OutputFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(history)?)),
+            OutputFormat::Geojson | OutputFormat::Kml => {
+                let observations: Vec<(String, WeatherInfo)> = history
+                    .iter()
+                    .map(|info| (station.to_owned(), info.clone()))
+                    .collect();
+                if self.format == OutputFormat::Kml {
+                    return Ok(kml::to_kml(&observations, &self.options));
+                }
+                let collection = geojson::to_feature_collection(&observations);
+                Ok(format!("{}\n", serde_json::to_string_pretty(&collection)?))
+            }
+        }
+    }
+
+    /// Renders a wind rose as a bar per sector, the characters of the
+    /// bars standing for the speed classes from the lightest to the
+    /// strongest.
+    pub(crate) fn render_wind_rose(&self, rose: &WindRose) -> Result<String> {
+        match self.format {
+            OutputFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(rose)?)),
+            OutputFormat::Debug => Ok(format!("{:#?}\n", rose)),
+            _ => {
+                let longest = (0..SECTORS.len())
+                    .map(|sector| rose.sector_count(sector))
+                    .max()
+                    .unwrap_or_default()
+                    .max(1);
+                let mut rows = vec![["DIR".to_owned(), "FREQ".to_owned(), String::new()]];
+                for (sector, name) in SECTORS.iter().enumerate() {
+                    // Rounding the cumulated counts keeps the length of
+                    // the bar proportional to the count of the sector
+                    let length = |count: usize| (count * WIND_ROSE_WIDTH + longest / 2) / longest;
+                    let mut bar = String::new();
+                    let mut cumulated = 0;
+                    for (count, glyph) in rose.bins[sector].iter().zip(WIND_ROSE_GLYPHS) {
+                        let start = length(cumulated);
+                        cumulated += count;
+                        bar.push_str(&glyph.repeat(length(cumulated) - start));
+                    }
+                    rows.push([
+                        (*name).to_owned(),
+                        format!("{:.0}%", rose.percent(rose.sector_count(sector))),
+                        bar,
+                    ]);
+                }
+                let mut out = write_table(&rows)?;
+                writeln!(out)?;
+                let mut args = FluentArgs::new();
+                args.set("calm", format!("{:.0}", rose.percent(rose.calm)));
+                args.set("variable", format!("{:.0}", rose.percent(rose.variable)));
+                let prevailing = rose.prevailing().map(str::to_owned);
+                args.set(
+                    "prevailing",
+                    prevailing.unwrap_or_else(|| self.localizer.tr("wind-rose-none")),
+                );
+                args.set("total", rose.total);
+                let summary = self.localizer.tr_args("wind-rose-summary", Some(&args));
+                writeln!(out, "{}", summary)?;
+                let legend: Vec<String> = SPEED_CLASSES_KNOTS
+                    .iter()
+                    .zip(WIND_ROSE_GLYPHS)
+                    .enumerate()
+                    .map(
+                        |(class, (lowest, glyph))| match SPEED_CLASSES_KNOTS.get(class + 1) {
+                            Some(next) => format!("{} {}-{} kt", glyph, lowest, next),
+                            None => format!("{} {}+ kt", glyph, lowest),
+                        },
+                    )
+                    .collect();
+                writeln!(out, "{}", legend.join("  "))?;
+                Ok(out)
+            }
+        }
+    }
+
+    /// Renders the degree days of each day, followed by their sums.
+    pub(crate) fn render_degree_days(&self, degree_days: &DegreeDays) -> Result<String> {
+        let options = &self.options;
+        match self.format {
+            OutputFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(degree_days)?)),
+            OutputFormat::Debug => Ok(format!("{:#?}\n", degree_days)),
+            _ => {
+                let mut rows = vec![[
+                    "DATE".to_owned(),
+                    "MEAN".to_owned(),
+                    "HDD".to_owned(),
+                    "CDD".to_owned(),
+                ]];
+                for day in &degree_days.days {
+                    rows.push([
+                        day.date.to_string(),
+                        format!("{} °C", options.temperature(day.mean_celsius)),
+                        options.temperature(day.heating),
+                        options.temperature(day.cooling),
+                    ]);
+                }
+                rows.push([
+                    self.localizer.tr("degree-days-total"),
+                    String::new(),
+                    options.temperature(degree_days.heating),
+                    options.temperature(degree_days.cooling),
+                ]);
+                let mut out = write_table(&rows)?;
+                let base = options.temperature(degree_days.base_celsius);
+                let base = self
+                    .localizer
+                    .tr_with("degree-days-base", "temperature", base);
+                writeln!(out, "\n{}", base)?;
+                Ok(out)
+            }
+        }
+    }
+
+    /// Renders the favorite stations by name.
+    pub(crate) fn render_favorites(&self, favorites: &BTreeMap<String, String>) -> Result<String> {
+        match self.format {
+            OutputFormat::Json | OutputFormat::Geojson => {
+                Ok(format!("{}\n", serde_json::to_string_pretty(favorites)?))
+            }
+            _ => {
+                let mut rows = vec![["NAME".to_owned(), "STATION".to_owned()]];
+                rows.extend(
+                    favorites
+                        .iter()
+                        .map(|(name, station)| [name.clone(), station.clone()]),
+                );
+                write_table(&rows)
+            }
+        }
+    }
+
+    /// Renders the results of the diagnostic checks.
+    pub(crate) fn render_checks(&self, checks: &[Check]) -> Result<String> {
+        match self.format {
+            OutputFormat::Json | OutputFormat::Geojson => {
+                Ok(format!("{}\n", serde_json::to_string_pretty(checks)?))
+            }
+            _ => {
+                let mut rows = vec![[
+                    "RESULT".to_owned(),
+                    "CHECK".to_owned(),
+                    "DETAIL".to_owned(),
+                    "TIME".to_owned(),
+                ]];
+                rows.extend(checks.iter().map(|check| {
+                    [
+                        if check.passed { "PASS" } else { "FAIL" }.to_owned(),
+                        check.name.to_owned(),
+                        check.detail.clone(),
+                        check
+                            .millis
+                            .map(|millis| format!("{} ms", millis))
+                            .unwrap_or_default(),
+                    ]
+                }));
+                write_table(&rows)
+            }
+        }
+    }
+
+    /// Renders the metadata of a station, along with the age of its latest
+    /// observation when known.
+    pub(crate) fn render_station(
+        &self,
+        station: &StationInfo,
+        age: Option<Duration>,
+        reliability: Option<&Reliability>,
+    ) -> Result<String> {
+        let mut out = String::new();
+        match self.format {
+            OutputFormat::Text | OutputFormat::Debug | OutputFormat::Wttr | OutputFormat::Kml => {
+                let localizer = &self.localizer;
+                let mut rows = vec![
+                    ("station-code", station.icao.clone()),
+                    ("station-name", station.name.clone()),
+                ];
+                if let Some(state) = &station.state {
+                    rows.push(("station-state", state.clone()));
+                }
+                rows.extend([
+                    ("station-country", station.country.clone()),
+                    (
+                        "station-coordinates",
+                        format!("{:.4}, {:.4}", station.latitude, station.longitude),
+                    ),
+                ]);
+                if let Some(elevation) = station.elevation {
+                    rows.push(("station-elevation", format!("{} m", elevation)));
+                }
+                if let Some(wmo_id) = &station.wmo_id {
+                    rows.push(("station-wmo-id", wmo_id.clone()));
+                }
+                let age = age.map_or_else(
+                    || localizer.tr("station-unavailable"),
+                    |age| localizer.age(age),
+                );
+                rows.push(("station-last-report", age));
+                if let Some(reliability) = reliability {
+                    let cadence = reliability.cadence.map_or_else(
+                        || localizer.tr("station-cadence-unknown"),
+                        |cadence| format!("{} min", cadence.as_secs() / 60),
+                    );
+                    let mut args = FluentArgs::new();
+                    args.set("percent", format!("{:.0}", 100.0 * reliability.score));
+                    args.set("checks", reliability.checks);
+                    rows.extend([
+                        (
+                            "station-reliability",
+                            localizer.tr_args("station-reliability-score", Some(&args)),
+                        ),
+                        ("station-cadence", cadence),
+                    ]);
+                }
+                self.write_labeled(&mut out, "", rows)?;
+            }
+            OutputFormat::Json => {
+                let mut value = serde_json::to_value(station)?;
+                value["last_report_age_seconds"] = age.map(|age| age.as_secs()).into();
+                value["reliability"] = serde_json::to_value(reliability)?;
+                writeln!(out, "{}", serde_json::to_string_pretty(&value)?)?;
+            }
+            OutputFormat::Geojson => {
+                let mut properties = serde_json::to_value(station)?;
+                properties["last_report_age_seconds"] = age.map(|age| age.as_secs()).into();
+                properties["reliability"] = serde_json::to_value(reliability)?;
+                let feature = serde_json::json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Point",
+                        "coordinates": [station.longitude, station.latitude],
+                    },
+                    "properties": properties,
+                });
+                writeln!(out, "{}", serde_json::to_string_pretty(&feature)?)?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Whether there are clouds whose height the station didn't report, in
+/// which case their base is estimated.
+fn needs_cloud_base_estimate(info: &WeatherInfo) -> bool {
+    let measured = info
+        .metar
+        .as_ref()
+        .is_some_and(|metar| metar.clouds.iter().any(|layer| layer.base_ft.is_some()));
+    let cloudy = info
+        .sky_condition
+        .as_deref()
+        .is_some_and(|sky| sky != "clear");
+    cloudy && !measured
+}
+
+/// The visibility as decoded, along with the exact value in meters for
+/// stations reporting it in meters.
+fn describe_visibility(info: &WeatherInfo) -> String {
+    let decoded = info.visibility.trim_end_matches(":0");
+    match info.metar.as_ref().and_then(|metar| metar.visibility) {
+        // 9999 stands for 10 km or more, which the decoded text has
+        Some(MetarVisibility::Meters(meters)) if meters < 9999 => {
+            format!("{} ({} m)", decoded, meters)
+        }
+        _ => decoded.to_owned(),
+    }
+}
+
+/// Characters of the bars of a wind rose, by speed class
+const WIND_ROSE_GLYPHS: [&str; SPEED_CLASSES_KNOTS.len()] = [".", "-", "=", "#"];
+
+/// Length of the bar of the sector with the most observations
+const WIND_ROSE_WIDTH: usize = 40;
+
+/// Lays out rows in left aligned columns, the first row being the header.
+fn write_table<const N: usize>(rows: &[[String; N]]) -> Result<String> {
+    let mut widths = [0; N];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for row in rows {
+        let mut line = String::new();
+        for (width, cell) in widths.iter().zip(row) {
+            write!(line, "{:<width$}  ", cell, width = width)?;
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(out)
+}
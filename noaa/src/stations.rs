@@ -0,0 +1,72 @@
+use crate::cli::StationArgs;
+use crate::config::Config;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// Station used when none is specified on the command line.
+const DEFAULT_STATION: &str = "VOBL";
+
+impl StationArgs {
+    /// Collects the stations given as arguments, through flags, the
+    /// stations file and standard input, in that order, with the names of
+    /// favorites resolved and groups expanded. Without any, the favorites
+    /// are used.
+    pub(crate) fn stations(&self, config: &Config) -> Result<Vec<String>> {
+        let mut stations = self.station.clone();
+        stations.extend(self.station_id.iter().cloned());
+        if let Some(path) = &self.stations_file {
+            let file = File::open(path)
+                .with_context(|| format!("Failed to open stations file {}", path.display()))?;
+            stations.extend(read_station_list(BufReader::new(file))?);
+        }
+        if self.stdin {
+            stations.extend(read_station_list(io::stdin().lock())?);
+        }
+        if stations.is_empty() {
+            stations.extend(config.favorites.values().cloned());
+        }
+        if stations.is_empty() {
+            stations.push(DEFAULT_STATION.into());
+        }
+        let mut expanded = vec![];
+        for station in &stations {
+            expanded.extend(config.expand(station)?);
+        }
+        Ok(expanded)
+    }
+
+    /// Whether a group of stations is given on the command line.
+    pub(crate) fn has_group(&self) -> bool {
+        self.station
+            .iter()
+            .chain(&self.station_id)
+            .any(|station| station.starts_with('@'))
+    }
+}
+
+/// Reads station codes from a list containing one code per line. Blank
+/// lines and anything following a `#` are ignored.
+fn read_station_list<R: BufRead>(reader: R) -> Result<Vec<String>> {
+    let mut stations = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let code = line.split('#').next().unwrap_or_default().trim();
+        if !code.is_empty() {
+            stations.push(code.to_owned());
+        }
+    }
+    Ok(stations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_station_list() {
+        let list: &[u8] = b"# Airports to check\nKSFO\n\n  VOBL  # Bangalore\n   \n#VOGO\nKOAK#\n";
+        assert_eq!(read_station_list(list).unwrap(), ["KSFO", "VOBL", "KOAK"]);
+        assert!(read_station_list(&b""[..]).unwrap().is_empty());
+    }
+}
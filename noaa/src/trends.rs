@@ -0,0 +1,293 @@
+use crate::output;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use weathernoaa::change::Trends;
+use weathernoaa::reliability::{Check, Reliability};
+use weathernoaa::weather::WeatherInfo;
+
+/// Number of checks of a station kept for telling its reliability
+const MAX_CHECKS: usize = 96;
+
+/// Observations of the stations, kept between runs for showing the
+/// trends since the previous observation, how reliably the stations
+/// report, and their history beyond what NOAA keeps. Each station has
+/// its own JSON file in the directory, with its latest observations,
+/// and a JSON Lines file with all those recorded.
+pub(crate) struct ObservationStore {
+    dir: PathBuf,
+}
+
+/// Last two observations of a station, and the latest checks of it.
+#[derive(Serialize, Deserialize)]
+struct Stored {
+    previous: Option<WeatherInfo>,
+    latest: WeatherInfo,
+    #[serde(default)]
+    checks: Vec<Check>,
+}
+
+impl ObservationStore {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        ObservationStore { dir }
+    }
+
+    /// Records the observations, returning the trends of the stations
+    /// since their previous observation. Failing to read or write the
+    /// store only leaves out the trends, since they're a nicety.
+    pub(crate) fn update(&self, reports: &[(String, WeatherInfo)]) -> HashMap<String, Trends> {
+        reports
+            .iter()
+            .filter_map(|(station, info)| {
+                self.record(station, std::slice::from_ref(info));
+                let trends = self.update_station(&self.path(station, "json")?, info);
+                trends.map(|trends| (station.clone(), trends))
+            })
+            .collect()
+    }
+
+    /// Adds the observations of a station to its history, leaving out
+    /// those already recorded and those whose time isn't known. Failing
+    /// to write the history is ignored, as for the trends.
+    pub(crate) fn record(&self, station: &str, observations: &[WeatherInfo]) {
+        let Some(path) = self.path(station, "jsonl") else {
+            return;
+        };
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        let mut recorded: HashSet<DateTime<Utc>> = parse_history(&content)
+            .filter_map(|info| info.weather_time.to_datetime())
+            .collect();
+        let mut lines = String::new();
+        for info in observations {
+            let Some(time) = info.weather_time.to_datetime() else {
+                continue;
+            };
+            if !recorded.insert(time) {
+                continue;
+            }
+            if let Ok(line) = serde_json::to_string(info) {
+                lines.push_str(&line);
+                lines.push('\n');
+            }
+        }
+        if lines.is_empty() {
+            return;
+        }
+        // A line cut short by an interrupted run is skipped when read,
+        // and ended so that the new ones aren't
+        if !content.is_empty() && !content.ends_with('\n') {
+            lines.insert(0, '\n');
+        }
+        let _ = fs::create_dir_all(&self.dir).and_then(|()| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+                .write_all(lines.as_bytes())
+        });
+    }
+
+    /// Recorded observations of a station, newest first.
+    pub(crate) fn history(&self, station: &str) -> Vec<WeatherInfo> {
+        let Some(content) = self
+            .path(station, "jsonl")
+            .and_then(|path| fs::read_to_string(path).ok())
+        else {
+            return vec![];
+        };
+        let mut history: Vec<WeatherInfo> = parse_history(&content).collect();
+        history.sort_by_key(|info| std::cmp::Reverse(info.weather_time.to_datetime()));
+        history
+    }
+
+    /// Records observations fetched from NOAA and returns the history of
+    /// the station since `since`, newest first: those observations
+    /// merged with the ones recorded before.
+    pub(crate) fn merge_history(
+        &self,
+        station: &str,
+        fetched: &[WeatherInfo],
+        since: DateTime<Utc>,
+    ) -> Vec<WeatherInfo> {
+        self.record(station, fetched);
+        let mut history = merge(fetched, self.history(station));
+        history.retain(|info| {
+            info.weather_time
+                .to_datetime()
+                .is_some_and(|time| time > since)
+        });
+        history
+    }
+
+    /// Latest recorded observation of a station.
+    pub(crate) fn latest(&self, station: &str) -> Option<WeatherInfo> {
+        Some(self.stored(station)?.latest)
+    }
+
+    /// How reliably a station reported when it was recorded. `None`
+    /// when it never was.
+    pub(crate) fn reliability(&self, station: &str) -> Option<Reliability> {
+        Reliability::new(&self.stored(station)?.checks)
+    }
+
+    fn stored(&self, station: &str) -> Option<Stored> {
+        let content = fs::read_to_string(self.path(station, "json")?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Whether the observation of a station is due for a refresh, not
+    /// having been refreshed within `interval`. The refresh is then
+    /// recorded, so that concurrent callers don't refresh it as well.
+    pub(crate) fn claim_refresh(&self, station: &str, interval: Duration) -> bool {
+        let Some(path) = self.path(station, "refresh") else {
+            return false;
+        };
+        let refreshed = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.elapsed().ok());
+        if refreshed.is_some_and(|elapsed| elapsed < interval) {
+            return false;
+        }
+        fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::write(&path, ""))
+            .is_ok()
+    }
+
+    /// File of a station with the extension. `None` for station codes
+    /// which aren't plain file names.
+    fn path(&self, station: &str, extension: &str) -> Option<PathBuf> {
+        let valid = !station.is_empty() && station.chars().all(|c| c.is_ascii_alphanumeric());
+        valid.then(|| self.dir.join(format!("{}.{}", station, extension)))
+    }
+
+    fn update_station(&self, path: &Path, info: &WeatherInfo) -> Option<Trends> {
+        let stored: Option<Stored> = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        let mut checks = stored
+            .as_ref()
+            .map(|stored| stored.checks.clone())
+            .unwrap_or_default();
+        if let Some(observed) = info.weather_time.to_datetime() {
+            checks.push(Check {
+                checked: chrono::Utc::now(),
+                observed,
+            });
+            let excess = checks.len().saturating_sub(MAX_CHECKS);
+            checks.drain(..excess);
+        }
+        let updated = match stored {
+            // Already recorded, the trends are still those since the
+            // observation before it
+            Some(stored) if stored.latest.weather_time == info.weather_time => {
+                Stored { checks, ..stored }
+            }
+            stored => Stored {
+                previous: stored.map(|stored| stored.latest),
+                latest: info.clone(),
+                checks,
+            },
+        };
+        if let Ok(content) = serde_json::to_string(&updated) {
+            // Written atomically, since a refresh in the background may
+            // update it while another run reads it
+            if fs::create_dir_all(&self.dir).is_ok() {
+                let _ = output::write(Some(path), &content);
+            }
+        }
+        updated
+            .previous
+            .map(|previous| Trends::between(&previous, info))
+    }
+}
+
+/// Observations of a history file, skipping the lines which can't be
+/// parsed.
+fn parse_history(content: &str) -> impl Iterator<Item = WeatherInfo> + '_ {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+}
+
+/// Observations fetched merged with recorded ones, newest first. The
+/// fetched ones are kept over recorded ones of the same time, and the
+/// recorded ones whose time isn't known are left out.
+pub(crate) fn merge(fetched: &[WeatherInfo], recorded: Vec<WeatherInfo>) -> Vec<WeatherInfo> {
+    let times: HashSet<DateTime<Utc>> = fetched
+        .iter()
+        .filter_map(|info| info.weather_time.to_datetime())
+        .collect();
+    let mut history = fetched.to_vec();
+    history.extend(recorded.into_iter().filter(|info| {
+        info.weather_time
+            .to_datetime()
+            .is_some_and(|time| !times.contains(&time))
+    }));
+    // Stable, the fetched observations stay first among those of
+    // unknown time
+    history.sort_by_key(|info| std::cmp::Reverse(info.weather_time.to_datetime()));
+    history
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use weathernoaa::weather::parse_weather;
+
+    fn observation(time: &str, celsius: u8) -> WeatherInfo {
+        let report = format!(
+            "Station name not available
+Dec 30, 2023 - 07:30 AM EST / 2023.12.30 {} UTC
+Wind: from the NNW (340 degrees) at 7 MPH (6 KT):0
+Visibility: 3 mile(s):0
+Temperature: 69 F ({} C)
+Dew Point: 64 F (18 C)
+Relative Humidity: 83%
+Pressure (altimeter): 29.83 in. Hg (1010 hPa)
+cycle: 12
+",
+            time, celsius
+        );
+        parse_weather(&report).unwrap().1
+    }
+
+    #[test]
+    fn test_history() {
+        let dir = std::env::temp_dir().join(format!("noaa-store-{}", std::process::id()));
+        let store = ObservationStore::new(dir.clone());
+        assert!(store.history("VOGO").is_empty());
+        store.record("VOGO", &[observation("1200", 20), observation("1230", 21)]);
+        // Already recorded, and a line cut short
+        store.record("VOGO", &[observation("1230", 21)]);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.join("VOGO.jsonl"))
+            .unwrap();
+        file.write_all(b"{\"station\":").unwrap();
+        let times = |history: &[WeatherInfo]| -> Vec<String> {
+            history
+                .iter()
+                .map(|info| info.weather_time.time.clone())
+                .collect()
+        };
+        assert_eq!(times(&store.history("VOGO")), ["1230 UTC", "1200 UTC"]);
+
+        // Fetched observations win over recorded ones of the same time
+        let since = observation("1100", 0).weather_time.to_datetime().unwrap();
+        let merged = store.merge_history(
+            "VOGO",
+            &[observation("1300", 22), observation("1230", 25)],
+            since,
+        );
+        assert_eq!(times(&merged), ["1300 UTC", "1230 UTC", "1200 UTC"]);
+        assert_eq!(merged[1].temperature.celsius, 25.0);
+        let since = observation("1200", 0).weather_time.to_datetime().unwrap();
+        assert_eq!(store.merge_history("VOGO", &[], since).len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,336 @@
+//! Python bindings of the weathernoaa crate. The weather types are
+//! exposed as frozen classes with read-only attributes, mirroring the
+//! fields of their Rust counterparts.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::sync::OnceLock;
+use weathernoaa::metar;
+use weathernoaa::weather::{self, NoaaApp, ParseError, DEFAULT_CONCURRENCY};
+
+create_exception!(
+    weathernoaa,
+    WeatherError,
+    PyException,
+    "Failure to fetch or parse a station's observation."
+);
+
+fn to_py_err(err: weather::WeatherError) -> PyErr {
+    WeatherError::new_err(err.to_string())
+}
+
+/// App shared by the calls, so that connections are reused.
+fn app() -> &'static NoaaApp {
+    static APP: OnceLock<NoaaApp> = OnceLock::new();
+    APP.get_or_init(|| NoaaApp::new().with_metar_fallback(true))
+}
+
+/// Runtime driving the asynchronous calls of [app]. It lives as long as
+/// the app, whose pooled connections are bound to the runtime they were
+/// opened on.
+fn runtime() -> PyResult<&'static tokio::runtime::Runtime> {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Timestamp of an observation.
+#[pyclass(name = "WeatherTime", module = "weathernoaa", frozen, get_all)]
+#[derive(Clone)]
+struct PyWeatherTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    /// Eg: 1330 UTC
+    time: String,
+}
+
+#[pymethods]
+impl PyWeatherTime {
+    fn __repr__(&self) -> String {
+        format!(
+            "WeatherTime(year={}, month={}, day={}, time={:?})",
+            self.year, self.month, self.day, self.time
+        )
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "{}-{:02}-{:02} {}",
+            self.year, self.month, self.day, self.time
+        )
+    }
+}
+
+/// Temperature in both celsius and Fahrenheit units.
+#[pyclass(name = "Temperature", module = "weathernoaa", frozen, get_all)]
+#[derive(Clone)]
+struct PyTemperature {
+    celsius: f64,
+    fahrenheit: f64,
+}
+
+#[pymethods]
+impl PyTemperature {
+    fn __repr__(&self) -> String {
+        format!(
+            "Temperature(celsius={:?}, fahrenheit={:?})",
+            self.celsius, self.fahrenheit
+        )
+    }
+}
+
+/// Place and country of a station.
+#[pyclass(name = "Station", module = "weathernoaa", frozen, get_all)]
+#[derive(Clone)]
+struct PyStation {
+    place: String,
+    country: String,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+#[pymethods]
+impl PyStation {
+    fn __repr__(&self) -> String {
+        format!(
+            "Station(place={:?}, country={:?})",
+            self.place, self.country
+        )
+    }
+}
+
+/// Wind information.
+#[pyclass(name = "Wind", module = "weathernoaa", frozen, get_all)]
+#[derive(Clone)]
+struct PyWind {
+    cardinal: String,
+    azimuth: f64,
+    mph: f64,
+    knots: f64,
+    gust_mph: Option<f64>,
+    gust_knots: Option<f64>,
+    direction_variable: bool,
+    variable_range: Option<(u16, u16)>,
+}
+
+#[pymethods]
+impl PyWind {
+    fn __repr__(&self) -> String {
+        format!(
+            "Wind(cardinal={:?}, azimuth={:?}, knots={:?}, gust_knots={:?})",
+            self.cardinal, self.azimuth, self.knots, self.gust_knots
+        )
+    }
+}
+
+/// Weather information of a station.
+#[pyclass(name = "WeatherInfo", module = "weathernoaa", frozen, get_all)]
+#[derive(Clone)]
+struct PyWeatherInfo {
+    station: Option<PyStation>,
+    weather_time: PyWeatherTime,
+    wind: PyWind,
+    visibility: String,
+    sky_condition: Option<String>,
+    weather: Option<String>,
+    temperature: PyTemperature,
+    dewpoint: PyTemperature,
+    relative_humidity: f64,
+    pressure: i16,
+    /// Present weather groups of the METAR. Eg: light rain, mist
+    weather_groups: Vec<String>,
+    /// Prevailing visibility in meters, when known
+    visibility_meters: Option<f64>,
+    /// Height of the ceiling in feet, when known
+    ceiling_ft: Option<u32>,
+    /// Whether the sun was up at the station, when its location is known
+    is_daytime: Option<bool>,
+    /// Compact one line summary
+    summary: String,
+}
+
+#[pymethods]
+impl PyWeatherInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "WeatherInfo(weather_time={}, temperature={}, wind={}, pressure={})",
+            self.weather_time.__repr__(),
+            self.temperature.__repr__(),
+            self.wind.__repr__(),
+            self.pressure
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.summary.clone()
+    }
+}
+
+impl From<weather::Temperature> for PyTemperature {
+    fn from(temperature: weather::Temperature) -> Self {
+        PyTemperature {
+            celsius: temperature.celsius,
+            fahrenheit: temperature.fahrenheit,
+        }
+    }
+}
+
+impl From<weather::WeatherInfo> for PyWeatherInfo {
+    fn from(info: weather::WeatherInfo) -> Self {
+        let summary = info.to_string();
+        let weather_groups = info
+            .weather_groups()
+            .iter()
+            .map(|group| group.to_string())
+            .collect();
+        let visibility_meters = info.visibility_meters();
+        let ceiling_ft = info.ceiling_ft();
+        let is_daytime = info.is_daytime();
+        let wind = info.wind;
+        PyWeatherInfo {
+            station: info.station.map(|station| PyStation {
+                place: station.place,
+                country: station.country,
+                latitude: station.location.map(|point| point.latitude),
+                longitude: station.location.map(|point| point.longitude),
+            }),
+            weather_time: PyWeatherTime {
+                year: info.weather_time.year,
+                month: info.weather_time.month,
+                day: info.weather_time.day,
+                time: info.weather_time.time,
+            },
+            wind: PyWind {
+                cardinal: wind.cardinal,
+                azimuth: wind.azimuth,
+                mph: wind.mph,
+                knots: wind.knots,
+                gust_mph: wind.gust_mph,
+                gust_knots: wind.gust_knots,
+                direction_variable: wind.direction_variable,
+                variable_range: wind.variable_range,
+            },
+            visibility: info.visibility,
+            sky_condition: info.sky_condition,
+            weather: info.weather,
+            temperature: info.temperature.into(),
+            dewpoint: info.dewpoint.into(),
+            relative_humidity: info.relative_humidity,
+            pressure: info.pressure,
+            weather_groups,
+            visibility_meters,
+            ceiling_ft,
+            is_daytime,
+            summary,
+        }
+    }
+}
+
+/// Fetches the current weather of a station. Eg: get_weather("VOBL")
+#[pyfunction]
+fn get_weather(py: Python<'_>, station: &str) -> PyResult<PyWeatherInfo> {
+    py.allow_threads(|| app().get_blocking_weather(station))
+        .map(PyWeatherInfo::from)
+        .map_err(to_py_err)
+}
+
+/// Fetches the weather of several stations concurrently, at most `jobs`
+/// at a time. Returns a list of (station, result) pairs in the order of
+/// the stations, where a failed result is a `WeatherError` instead of
+/// being raised.
+#[pyfunction]
+#[pyo3(signature = (stations, jobs = DEFAULT_CONCURRENCY))]
+fn get_weather_many(
+    py: Python<'_>,
+    stations: Vec<String>,
+    jobs: usize,
+) -> PyResult<Vec<(String, PyObject)>> {
+    let runtime = runtime()?;
+    let results = py.allow_threads(|| runtime.block_on(app().get_weather_many(&stations, jobs)));
+    results
+        .into_iter()
+        .map(|(station, result)| {
+            let result = match result {
+                Ok(info) => Py::new(py, PyWeatherInfo::from(info))?.into_any(),
+                Err(err) => to_py_err(err).into_value(py).into_any(),
+            };
+            Ok((station, result))
+        })
+        .collect()
+}
+
+/// Parses the content of a station's decoded file, as published by NOAA.
+#[pyfunction]
+fn parse_weather(text: &str) -> PyResult<PyWeatherInfo> {
+    let (_, info) = weather::parse_weather(text)
+        .map_err(|err| to_py_err(ParseError::decoded(text, err).into()))?;
+    Ok(info.into())
+}
+
+/// Parses a METAR report. Its year and month, which the report doesn't
+/// carry, have to be given.
+#[pyfunction]
+fn parse_metar(text: &str, year: u16, month: u8) -> PyResult<PyWeatherInfo> {
+    let (_, metar) =
+        metar::parse_metar(text).map_err(|err| to_py_err(ParseError::new(text, err).into()))?;
+    metar
+        .to_weather_info(year, month)
+        .map(PyWeatherInfo::from)
+        .ok_or_else(|| {
+            WeatherError::new_err("The METAR lacks the temperature, dewpoint or altimeter")
+        })
+}
+
+#[pymodule]
+#[pyo3(name = "weathernoaa")]
+fn weathernoaa_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("WeatherError", m.py().get_type::<WeatherError>())?;
+    m.add_class::<PyWeatherInfo>()?;
+    m.add_class::<PyWeatherTime>()?;
+    m.add_class::<PyTemperature>()?;
+    m.add_class::<PyStation>()?;
+    m.add_class::<PyWind>()?;
+    m.add_function(wrap_pyfunction!(get_weather, m)?)?;
+    m.add_function(wrap_pyfunction!(get_weather_many, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_weather, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_metar, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let report = "BANGALORE/HINDUSTAN, India (VOBL) 12-57N 077-38E 888M
+Dec 30, 2023 - 08:30 AM EST / 2023.12.30 1330 UTC
+Wind: from the E (080 degrees) at 9 MPH (8 KT):0
+Visibility: greater than 7 mile(s):0
+Sky conditions: mostly clear
+Temperature: 73 F (23 C)
+Dew Point: 57 F (14 C)
+Relative Humidity: 56%
+Pressure (altimeter): 30.03 in. Hg (1017 hPa)
+ob: VOBL 301330Z 08008KT 9999 SCT020 23/14 Q1017 NOSIG
+cycle: 13
+";
+        let info = parse_weather(report).unwrap();
+        assert_eq!(info.temperature.celsius, 23.0);
+        assert_eq!(info.pressure, 1017);
+        assert_eq!(info.station.unwrap().country, "India");
+
+        let info = parse_metar("VOBL 301330Z 08008KT 9999 SCT020 23/14 Q1017", 2023, 12).unwrap();
+        assert_eq!(info.weather_time.__str__(), "2023-12-30 1330 UTC");
+        assert_eq!(info.dewpoint.celsius, 14.0);
+        assert_eq!(info.wind.knots, 8.0);
+        assert!(parse_metar("VOBL 301330Z 08008KT 9999", 2023, 12).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+#ifndef WEATHERNOAA_H
+#define WEATHERNOAA_H
+
+/* Generated with cbindgen, don't edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ Main values of an observation. Missing values are NaN.
+ */
+typedef struct WeathernoaaObservation {
+  /*
+   Seconds since the Unix epoch, -1 when the time isn't known
+   */
+  int64_t timestamp;
+  double temperature_celsius;
+  double dewpoint_celsius;
+  /*
+   Relative humidity in percent
+   */
+  double relative_humidity;
+  double pressure_hpa;
+  /*
+   Direction the wind is blowing from, in degrees
+   */
+  double wind_azimuth;
+  double wind_knots;
+  double gust_knots;
+  double visibility_meters;
+} WeathernoaaObservation;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Fetches the current weather of a station, as JSON. Returns null on
+ failure.
+
+ # Safety
+
+ `station` must be a valid nul terminated string.
+ */
+char *weathernoaa_get_blocking(const char *station);
+
+/*
+ Fetches the current weather of a station into `observation`. Returns
+ 0 on success and -1 on failure, leaving `observation` untouched.
+
+ # Safety
+
+ `station` must be a valid nul terminated string and `observation` a
+ valid pointer.
+ */
+int weathernoaa_get_observation(const char *station, struct WeathernoaaObservation *observation);
+
+/*
+ Parses the content of a station's decoded file into JSON. Returns
+ null on failure.
+
+ # Safety
+
+ `text` must be a valid nul terminated string.
+ */
+char *weathernoaa_parse_weather(const char *text);
+
+/*
+ Description of the last failure on the calling thread, or null. The
+ string is owned by the library and valid until the next call on the
+ thread.
+ */
+const char *weathernoaa_last_error(void);
+
+/*
+ Releases a string returned by the library.
+
+ # Safety
+
+ `s` must be null or a string returned by the library, not released
+ before.
+ */
+void weathernoaa_string_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* WEATHERNOAA_H */
@@ -0,0 +1,342 @@
+//! Alert rules evaluated over the successive observations of stations,
+//! eg: "wind above 30 kt for 2 consecutive observations at KOAK", for
+//! services polling stations such as those built on
+//! [NoaaApp::observe_many](crate::weather::NoaaApp::observe_many).
+//!
+//! With the `serde` feature, rules can be read from a configuration
+//! file. Eg, in TOML:
+//!
+//! ```toml
+//! [[alerts]]
+//! name = "gale"
+//! quantity = "wind"
+//! comparator = "above"
+//! threshold = 30.0
+//! consecutive = 2
+//! stations = ["KOAK"]
+//! actions = [{ webhook = { url = "https://example.com/hook" } }, "unhealthy"]
+//! ```
+
+use crate::weather::{WeatherInfo, WeatherTime};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Value of an observation a rule checks.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Quantity {
+    /// Temperature in celsius
+    Temperature,
+    /// Dewpoint in celsius
+    Dewpoint,
+    /// Relative humidity in percent
+    RelativeHumidity,
+    /// Sustained wind speed in knots
+    Wind,
+    /// Gust speed in knots, absent without gusts
+    Gust,
+    /// Pressure in hectopascals
+    Pressure,
+    /// Visibility in meters, absent when only a bound is known
+    Visibility,
+}
+
+impl Quantity {
+    /// The value of the quantity in an observation.
+    pub fn of(self, info: &WeatherInfo) -> Option<f64> {
+        match self {
+            Quantity::Temperature => Some(info.temperature.celsius),
+            Quantity::Dewpoint => Some(info.dewpoint.celsius),
+            Quantity::RelativeHumidity => Some(info.relative_humidity),
+            Quantity::Wind => Some(info.wind.knots),
+            Quantity::Gust => info.wind.gust_knots,
+            Quantity::Pressure => Some(f64::from(info.pressure)),
+            Quantity::Visibility => info.visibility_meters(),
+        }
+    }
+
+    /// Unit of the values of the quantity.
+    pub fn unit(self) -> &'static str {
+        match self {
+            Quantity::Temperature | Quantity::Dewpoint => "°C",
+            Quantity::RelativeHumidity => "%",
+            Quantity::Wind | Quantity::Gust => "kt",
+            Quantity::Pressure => "hPa",
+            Quantity::Visibility => "m",
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Quantity::Temperature => "temperature",
+            Quantity::Dewpoint => "dewpoint",
+            Quantity::RelativeHumidity => "relative humidity",
+            Quantity::Wind => "wind",
+            Quantity::Gust => "gust",
+            Quantity::Pressure => "pressure",
+            Quantity::Visibility => "visibility",
+        })
+    }
+}
+
+/// How a value is compared to the threshold of a rule.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Comparator {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+impl Comparator {
+    pub fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparator::Above => value > threshold,
+            Comparator::AtLeast => value >= threshold,
+            Comparator::Below => value < threshold,
+            Comparator::AtMost => value <= threshold,
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Comparator::Above => ">",
+            Comparator::AtLeast => ">=",
+            Comparator::Below => "<",
+            Comparator::AtMost => "<=",
+        })
+    }
+}
+
+/// What to do when a rule triggers. Carrying out the actions is left to
+/// the service evaluating the rules.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Action {
+    /// Show a desktop notification
+    Notify,
+    /// POST the alert to a URL, eg: with a
+    /// [WebhookSink](crate::sink::WebhookSink)
+    Webhook { url: String },
+    /// Publish the alert to an MQTT topic
+    Mqtt { topic: String },
+    /// Report the service as unhealthy while the rule holds, see
+    /// [AlertEngine::is_healthy]
+    Unhealthy,
+}
+
+/// A condition on the observations of stations, which triggers its
+/// actions once it held for a number of consecutive observations.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    pub name: String,
+    pub quantity: Quantity,
+    pub comparator: Comparator,
+    pub threshold: f64,
+    /// Consecutive observations the condition must hold for, at least 1
+    #[cfg_attr(feature = "serde", serde(default = "default_consecutive"))]
+    pub consecutive: usize,
+    /// Stations the rule applies to, all of them when empty
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stations: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub actions: Vec<Action>,
+}
+
+#[cfg(feature = "serde")]
+fn default_consecutive() -> usize {
+    1
+}
+
+impl Rule {
+    fn applies_to(&self, station: &str) -> bool {
+        self.stations.is_empty()
+            || self
+                .stations
+                .iter()
+                .any(|code| code.eq_ignore_ascii_case(station))
+    }
+}
+
+/// A rule which triggered on the observation of a station.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Alert<'a> {
+    pub rule: &'a Rule,
+    pub station: String,
+    /// Value of the observation which triggered the rule
+    pub value: f64,
+    pub weather_time: WeatherTime,
+}
+
+impl fmt::Display for Alert<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quantity = self.rule.quantity;
+        write!(
+            f,
+            "{} at {}: {} {} {} {} {}",
+            self.rule.name,
+            self.station,
+            quantity,
+            self.value,
+            quantity.unit(),
+            self.rule.comparator,
+            self.rule.threshold
+        )
+    }
+}
+
+/// How long the condition of a rule held at a station.
+struct Streak {
+    /// Consecutive observations the condition held for
+    count: usize,
+    /// Time of the last observation evaluated, for not counting the
+    /// same one twice
+    last: WeatherTime,
+}
+
+/// Evaluates rules over the observations of stations as they come.
+pub struct AlertEngine {
+    rules: Vec<Rule>,
+    /// Streaks by rule index and station
+    streaks: HashMap<(usize, String), Streak>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        AlertEngine {
+            rules,
+            streaks: HashMap::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Evaluates the rules on a new observation of a station, returning
+    /// those which trigger. A rule triggers once its condition held for
+    /// its consecutive observations, and triggers again only after the
+    /// condition stopped holding. An observation given twice counts
+    /// once, and one lacking the value of a rule interrupts its streak.
+    pub fn observe(&mut self, station: &str, info: &WeatherInfo) -> Vec<Alert<'_>> {
+        let mut triggered = vec![];
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !rule.applies_to(station) {
+                continue;
+            }
+            let key = (index, station.to_uppercase());
+            if self
+                .streaks
+                .get(&key)
+                .is_some_and(|streak| streak.last == info.weather_time)
+            {
+                continue;
+            }
+            let value = rule
+                .quantity
+                .of(info)
+                .filter(|value| rule.comparator.holds(*value, rule.threshold));
+            let streak = self.streaks.entry(key).or_insert(Streak {
+                count: 0,
+                last: info.weather_time.clone(),
+            });
+            streak.last = info.weather_time.clone();
+            let Some(value) = value else {
+                streak.count = 0;
+                continue;
+            };
+            streak.count += 1;
+            if streak.count == rule.consecutive.max(1) {
+                triggered.push(Alert {
+                    rule,
+                    station: station.to_owned(),
+                    value,
+                    weather_time: info.weather_time.clone(),
+                });
+            }
+        }
+        triggered
+    }
+
+    /// Whether no rule with the [Action::Unhealthy] action currently
+    /// holds at any station, eg: for the health check of a service.
+    pub fn is_healthy(&self) -> bool {
+        !self.streaks.iter().any(|((index, _), streak)| {
+            let rule = &self.rules[*index];
+            rule.actions.contains(&Action::Unhealthy) && streak.count >= rule.consecutive.max(1)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+
+    #[test]
+    fn test_alert_engine() {
+        let gale = Rule {
+            name: "gale".into(),
+            quantity: Quantity::Wind,
+            comparator: Comparator::Above,
+            threshold: 30.0,
+            consecutive: 2,
+            stations: vec!["VOGO".into()],
+            actions: vec![Action::Unhealthy],
+        };
+        let mut engine = AlertEngine::new(vec![gale]);
+        let observation = |time: &str, knots: f64| {
+            let (_, mut info) = parse_weather(&vogo_report(time)).unwrap();
+            info.wind.knots = knots;
+            info
+        };
+        let names = |alerts: Vec<Alert>| -> Vec<String> {
+            alerts.iter().map(|alert| alert.to_string()).collect()
+        };
+        assert!(engine
+            .observe("VOGO", &observation("1200", 35.0))
+            .is_empty());
+        // The same observation again doesn't count
+        assert!(engine
+            .observe("VOGO", &observation("1200", 35.0))
+            .is_empty());
+        assert!(engine.is_healthy());
+        assert_eq!(
+            names(engine.observe("VOGO", &observation("1230", 32.0))),
+            ["gale at VOGO: wind 32 kt > 30"]
+        );
+        assert!(!engine.is_healthy());
+        // Still holding, not triggered again
+        assert!(engine
+            .observe("VOGO", &observation("1300", 40.0))
+            .is_empty());
+        assert!(engine
+            .observe("VOGO", &observation("1330", 10.0))
+            .is_empty());
+        assert!(engine.is_healthy());
+        assert!(engine
+            .observe("VOBL", &observation("1330", 40.0))
+            .is_empty());
+        assert_eq!(engine.observe("VOGO", &observation("1400", 40.0)).len(), 0);
+        assert_eq!(engine.observe("VOGO", &observation("1430", 40.0)).len(), 1);
+    }
+}
@@ -0,0 +1,160 @@
+//! Snapshot archives of the decoded reports fetched during a run, for
+//! replaying them through the parser offline, eg: for reproducible bug
+//! reports and regression corpora. Requires the `archive` feature.
+//!
+//! An archive is a zstd compressed tar file holding each report as
+//! `reports/<station>.TXT`, along with an `index.tsv` file listing, in
+//! order, the station, path and fetch time of each report, the time
+//! being in seconds since the Unix epoch.
+
+use crate::weather::{parse_decoded, parse_report, WeatherError, WeatherInfo};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const INDEX: &str = "index.tsv";
+
+/// Writes reports into an archive.
+pub struct ArchiveWriter<W: Write> {
+    builder: tar::Builder<zstd::Encoder<'static, W>>,
+    index: String,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    pub fn new(writer: W) -> io::Result<Self> {
+        let encoder = zstd::Encoder::new(writer, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+        Ok(ArchiveWriter {
+            builder: tar::Builder::new(encoder),
+            index: String::new(),
+        })
+    }
+
+    /// Adds the decoded report of a station, as fetched at `fetched_at`.
+    pub fn add(&mut self, station: &str, report: &str, fetched_at: SystemTime) -> io::Result<()> {
+        if station.is_empty() || station.contains(['/', '\t', '\n']) {
+            return Err(invalid(format!("Invalid station code {:?}", station)));
+        }
+        let path = format!("reports/{}.TXT", station);
+        let time = fetched_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        append(&mut self.builder, &path, report.as_bytes(), time)?;
+        self.index
+            .push_str(&format!("{}\t{}\t{}\n", station, path, time));
+        Ok(())
+    }
+
+    /// Writes the index and completes the archive, returning the
+    /// underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let index = std::mem::take(&mut self.index);
+        append(&mut self.builder, INDEX, index.as_bytes(), now)?;
+        self.builder.into_inner()?.finish()
+    }
+}
+
+fn append<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    data: &[u8],
+    mtime: u64,
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// A report read back from an archive.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ArchivedReport {
+    pub station: String,
+    pub fetched_at: SystemTime,
+    /// The decoded report, as fetched
+    pub report: String,
+}
+
+impl ArchivedReport {
+    /// Parses the report, as [crate::weather::NoaaApp::get_weather] does
+    /// once fetched.
+    pub fn parse(&self) -> Result<WeatherInfo, WeatherError> {
+        parse_report(&self.station, &self.report, parse_decoded)
+    }
+}
+
+/// Reads the reports of an archive, in the order they were added.
+pub fn read_archive<R: Read>(reader: R) -> io::Result<Vec<ArchivedReport>> {
+    let mut archive = tar::Archive::new(zstd::Decoder::new(reader)?);
+    let mut files = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        files.insert(path, content);
+    }
+    let index = files
+        .get(INDEX)
+        .ok_or_else(|| invalid("The archive has no index".to_owned()))?;
+    index
+        .lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [station, path, time] = fields[..] else {
+                return Err(invalid(format!("Invalid index line {:?}", line)));
+            };
+            let report = files
+                .get(path)
+                .ok_or_else(|| invalid(format!("The archive has no {}", path)))?;
+            let time = time
+                .parse()
+                .map_err(|_| invalid(format!("Invalid time in index line {:?}", line)))?;
+            Ok(ArchivedReport {
+                station: station.to_owned(),
+                fetched_at: UNIX_EPOCH + Duration::from_secs(time),
+                report: report.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::tests::vogo_report;
+
+    #[test]
+    fn test_archive() {
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(1_703_939_400);
+        let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+        writer
+            .add("VOGO", &vogo_report("1230"), fetched_at)
+            .unwrap();
+        writer.add("KXXX", "", fetched_at).unwrap();
+        assert!(writer.add("../KSFO", "", fetched_at).is_err());
+        let archive = writer.finish().unwrap();
+
+        let reports = read_archive(archive.as_slice()).unwrap();
+        let stations: Vec<&str> = reports.iter().map(|r| r.station.as_str()).collect();
+        assert_eq!(stations, ["VOGO", "KXXX"]);
+        assert_eq!(reports[0].fetched_at, fetched_at);
+        assert_eq!(reports[0].report, vogo_report("1230"));
+        assert_eq!(reports[0].parse().unwrap().pressure, 1010);
+        assert!(matches!(
+            reports[1].parse(),
+            Err(WeatherError::NoReport { .. })
+        ));
+        assert!(read_archive(&b"not an archive"[..]).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+//! Position of the sun, for telling day and night apart at a station.
+
+use crate::geo::Point;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+/// Elevation of the center of the sun at sunrise and sunset, accounting
+/// for atmospheric refraction and the radius of the sun.
+const SUNRISE_ELEVATION: f64 = -0.833;
+
+/// Elevation of the sun above the horizon in degrees, using NOAA's
+/// approximate solar position equations, accurate to a fraction of a
+/// degree.
+pub fn solar_elevation(point: Point, time: DateTime<Utc>) -> f64 {
+    let hour = f64::from(time.hour());
+    let gamma =
+        2.0 * std::f64::consts::PI / 365.0 * (f64::from(time.ordinal0()) + (hour - 12.0) / 24.0);
+    let equation_of_time = 229.18
+        * (0.000_075 + 0.001_868 * gamma.cos()
+            - 0.032_077 * gamma.sin()
+            - 0.014_615 * (2.0 * gamma).cos()
+            - 0.040_849 * (2.0 * gamma).sin());
+    let declination = 0.006_918 - 0.399_912 * gamma.cos() + 0.070_257 * gamma.sin()
+        - 0.006_758 * (2.0 * gamma).cos()
+        + 0.000_907 * (2.0 * gamma).sin()
+        - 0.002_697 * (3.0 * gamma).cos()
+        + 0.001_48 * (3.0 * gamma).sin();
+    let minutes = hour * 60.0 + f64::from(time.minute()) + f64::from(time.second()) / 60.0;
+    let solar_time = minutes + equation_of_time + 4.0 * point.longitude;
+    let hour_angle = (solar_time / 4.0 - 180.0).to_radians();
+    let latitude = point.latitude.to_radians();
+    let cos_zenith =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    90.0 - cos_zenith.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// Whether the sun is up at a point, between sunrise and sunset.
+pub fn is_daytime(point: Point, time: DateTime<Utc>) -> bool {
+    solar_elevation(point, time) > SUNRISE_ELEVATION
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAN_FRANCISCO: Point = Point {
+        latitude: 37.6197,
+        longitude: -122.3656,
+    };
+
+    #[test]
+    fn test_solar_elevation() {
+        // Solar noon around the summer solstice
+        let noon = "2024-06-20T20:10:00Z".parse().unwrap();
+        assert!((solar_elevation(SAN_FRANCISCO, noon) - 75.8).abs() < 0.5);
+        assert!(is_daytime(SAN_FRANCISCO, noon));
+        let midnight = "2024-06-21T08:10:00Z".parse().unwrap();
+        assert!(!is_daytime(SAN_FRANCISCO, midnight));
+        // Sunrise is at 05:48 local time
+        let before = "2024-06-20T12:40:00Z".parse().unwrap();
+        let after = "2024-06-20T12:55:00Z".parse().unwrap();
+        assert!(!is_daytime(SAN_FRANCISCO, before));
+        assert!(is_daytime(SAN_FRANCISCO, after));
+    }
+}
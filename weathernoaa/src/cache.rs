@@ -0,0 +1,332 @@
+//! Compact binary cache of the latest observation of stations, which
+//! is memory-mapped for reading and updated in place, eg: for services
+//! answering frequent requests about hundreds of stations without
+//! parsing a store on every request. Requires the `cache` feature.
+//!
+//! The file is a 16 bytes header, made of the magic `WNOAAC`, the
+//! format version on two bytes and the number of records on four, then
+//! a 64 bytes record per station. Numbers are little endian:
+//!
+//! | Offset | Size | Value                                          |
+//! |--------|------|------------------------------------------------|
+//! | 0      | 8    | Station code, ASCII, padded with zeros         |
+//! | 8      | 8    | Observation time in seconds since the epoch    |
+//! | 16     | 4×8  | Temperature, dewpoint, humidity, wind, azimuth, gust, visibility and pressure as `f32` |
+//! | 48     | 1    | [Severity] of the weather                      |
+//! | 49     | 1    | Whether the wind direction is variable         |
+//! | 50     | 6    | Zeros                                          |
+//! | 56     | 8    | Time the record was written, as above          |
+//!
+//! Unknown times are `i64::MIN` and missing values `NaN`.
+
+use crate::severity::Severity;
+use crate::weather::WeatherInfo;
+use chrono::{DateTime, TimeZone, Utc};
+use memmap2::Mmap;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 6] = b"WNOAAC";
+const VERSION: u16 = 1;
+const HEADER_LEN: usize = 16;
+const RECORD_LEN: usize = 64;
+const UNKNOWN_TIME: i64 = i64::MIN;
+
+/// Severities in the order of their code in the records
+const SEVERITIES: [Severity; 12] = [
+    Severity::None,
+    Severity::Obscuration,
+    Severity::Fog,
+    Severity::Drizzle,
+    Severity::Rain,
+    Severity::Snow,
+    Severity::Hail,
+    Severity::FreezingRain,
+    Severity::Sandstorm,
+    Severity::Thunderstorm,
+    Severity::Squalls,
+    Severity::FunnelCloud,
+];
+
+/// The values of an observation kept in the cache.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CachedObservation {
+    pub station: String,
+    /// `None` when the time of the observation isn't known
+    pub observed: Option<DateTime<Utc>>,
+    pub temperature_celsius: f64,
+    pub dewpoint_celsius: f64,
+    pub relative_humidity: f64,
+    pub wind_knots: f64,
+    pub wind_azimuth: f64,
+    pub wind_direction_variable: bool,
+    pub gust_knots: Option<f64>,
+    /// See [WeatherInfo::visibility_meters]
+    pub visibility_meters: Option<f64>,
+    pub pressure_hpa: f64,
+    pub severity: Severity,
+    /// When the record was written
+    pub updated: Option<DateTime<Utc>>,
+}
+
+impl CachedObservation {
+    pub fn new(station: &str, info: &WeatherInfo) -> Self {
+        CachedObservation {
+            station: station.to_owned(),
+            observed: info.weather_time.to_datetime(),
+            temperature_celsius: info.temperature.celsius,
+            dewpoint_celsius: info.dewpoint.celsius,
+            relative_humidity: info.relative_humidity,
+            wind_knots: info.wind.knots,
+            wind_azimuth: info.wind.azimuth,
+            wind_direction_variable: info.wind.direction_variable,
+            gust_knots: info.wind.gust_knots,
+            visibility_meters: info.visibility_meters(),
+            pressure_hpa: f64::from(info.pressure),
+            severity: info.severity(),
+            updated: Some(Utc::now()),
+        }
+    }
+
+    fn encode(&self) -> [u8; RECORD_LEN] {
+        let mut record = [0; RECORD_LEN];
+        let code = self.station.as_bytes();
+        record[..code.len()].copy_from_slice(code);
+        let time = |time: Option<DateTime<Utc>>| time.map_or(UNKNOWN_TIME, |t| t.timestamp());
+        record[8..16].copy_from_slice(&time(self.observed).to_le_bytes());
+        let values = [
+            self.temperature_celsius,
+            self.dewpoint_celsius,
+            self.relative_humidity,
+            self.wind_knots,
+            self.wind_azimuth,
+            self.gust_knots.unwrap_or(f64::NAN),
+            self.visibility_meters.unwrap_or(f64::NAN),
+            self.pressure_hpa,
+        ];
+        for (index, value) in values.iter().enumerate() {
+            let offset = 16 + 4 * index;
+            record[offset..offset + 4].copy_from_slice(&(*value as f32).to_le_bytes());
+        }
+        record[48] = SEVERITIES
+            .iter()
+            .position(|severity| *severity == self.severity)
+            .unwrap_or_default() as u8;
+        record[49] = u8::from(self.wind_direction_variable);
+        record[56..64].copy_from_slice(&time(self.updated).to_le_bytes());
+        record
+    }
+
+    fn decode(record: &[u8]) -> Self {
+        let time = |offset: usize| {
+            let secs = i64::from_le_bytes(record[offset..offset + 8].try_into().unwrap());
+            if secs == UNKNOWN_TIME {
+                return None;
+            }
+            Utc.timestamp_opt(secs, 0).single()
+        };
+        let value = |index: usize| {
+            let offset = 16 + 4 * index;
+            f64::from(f32::from_le_bytes(
+                record[offset..offset + 4].try_into().unwrap(),
+            ))
+        };
+        let optional = |index: usize| Some(value(index)).filter(|value| !value.is_nan());
+        CachedObservation {
+            station: station_code(record).to_owned(),
+            observed: time(8),
+            temperature_celsius: value(0),
+            dewpoint_celsius: value(1),
+            relative_humidity: value(2),
+            wind_knots: value(3),
+            wind_azimuth: value(4),
+            gust_knots: optional(5),
+            visibility_meters: optional(6),
+            pressure_hpa: value(7),
+            severity: SEVERITIES
+                .get(usize::from(record[48]))
+                .copied()
+                .unwrap_or(Severity::None),
+            wind_direction_variable: record[49] != 0,
+            updated: time(56),
+        }
+    }
+}
+
+fn station_code(record: &[u8]) -> &str {
+    let code = &record[..8];
+    let len = code.iter().position(|&b| b == 0).unwrap_or(code.len());
+    std::str::from_utf8(&code[..len]).unwrap_or_default()
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Checks the header of a cache, returning its number of records.
+fn read_header(bytes: &[u8]) -> io::Result<usize> {
+    if bytes.len() < HEADER_LEN || &bytes[..6] != MAGIC {
+        return Err(invalid("Not an observation cache"));
+    }
+    if u16::from_le_bytes([bytes[6], bytes[7]]) != VERSION {
+        return Err(invalid("Unsupported observation cache version"));
+    }
+    let count = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+    if bytes.len() < HEADER_LEN + count * RECORD_LEN {
+        return Err(invalid("Truncated observation cache"));
+    }
+    Ok(count)
+}
+
+/// Writer of a cache file, updating the record of a station in place
+/// or appending it.
+pub struct CacheWriter {
+    file: File,
+    /// Position of the records by station
+    index: HashMap<String, usize>,
+}
+
+impl CacheWriter {
+    /// Opens the cache at `path`, creating it when it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        if bytes.is_empty() {
+            let mut header = [0; HEADER_LEN];
+            header[..6].copy_from_slice(MAGIC);
+            header[6..8].copy_from_slice(&VERSION.to_le_bytes());
+            file.write_all(&header)?;
+            bytes = header.to_vec();
+        }
+        let count = read_header(&bytes)?;
+        let index = (0..count)
+            .map(|position| {
+                let record = &bytes[HEADER_LEN + position * RECORD_LEN..];
+                (station_code(record).to_ascii_uppercase(), position)
+            })
+            .collect();
+        Ok(CacheWriter { file, index })
+    }
+
+    /// Records the observation of a station, replacing its previous
+    /// one. Station codes are at most 8 ASCII characters, and are
+    /// recorded in uppercase.
+    pub fn update(&mut self, station: &str, info: &WeatherInfo) -> io::Result<()> {
+        if station.is_empty() || station.len() > 8 || !station.is_ascii() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid station code {:?}", station),
+            ));
+        }
+        let station = station.to_ascii_uppercase();
+        let record = CachedObservation::new(&station, info).encode();
+        let count = self.index.len();
+        let position = *self.index.entry(station).or_insert(count);
+        let offset = HEADER_LEN + position * RECORD_LEN;
+        self.file.seek(SeekFrom::Start(offset as u64))?;
+        self.file.write_all(&record)?;
+        if position == count {
+            // The count is written last, so that readers never see a
+            // record which isn't complete
+            self.file.seek(SeekFrom::Start(8))?;
+            self.file
+                .write_all(&(self.index.len() as u32).to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Memory-mapped view of a cache file. Reading a record only decodes
+/// its 64 bytes, whatever the number of stations.
+pub struct CacheReader {
+    mmap: Mmap,
+    count: usize,
+}
+
+impl CacheReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the file is only modified by CacheWriter, which
+        // doesn't shrink it. A record being rewritten while read may be
+        // seen partly updated.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let count = read_header(&mmap)?;
+        Ok(CacheReader { mmap, count })
+    }
+
+    /// Number of stations in the cache, as of when it was opened.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    fn record(&self, position: usize) -> &[u8] {
+        let offset = HEADER_LEN + position * RECORD_LEN;
+        &self.mmap[offset..offset + RECORD_LEN]
+    }
+
+    /// The cached observation of a station.
+    pub fn get(&self, station: &str) -> Option<CachedObservation> {
+        (0..self.count)
+            .map(|position| self.record(position))
+            .find(|record| station_code(record).eq_ignore_ascii_case(station))
+            .map(CachedObservation::decode)
+    }
+
+    /// The cached observations, in the order their stations were added.
+    pub fn iter(&self) -> impl Iterator<Item = CachedObservation> + '_ {
+        (0..self.count).map(move |position| CachedObservation::decode(self.record(position)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+
+    #[test]
+    fn test_observation_cache() {
+        let path =
+            std::env::temp_dir().join(format!("weathernoaa-cache-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let (_, vogo) = parse_weather(&vogo_report("1230")).unwrap();
+        let (_, later) = parse_weather(&vogo_report("1300")).unwrap();
+        let mut writer = CacheWriter::open(&path).unwrap();
+        writer.update("VOGO", &vogo).unwrap();
+        writer.update("VOBL", &vogo).unwrap();
+        assert!(writer.update("NOT A STATION", &vogo).is_err());
+        drop(writer);
+
+        // Reopened, the record of VOGO is replaced, whatever the case
+        CacheWriter::open(&path)
+            .unwrap()
+            .update("vogo", &later)
+            .unwrap();
+        let reader = CacheReader::open(&path).unwrap();
+        assert_eq!(reader.len(), 2);
+        let cached = reader.get("vogo").unwrap();
+        assert_eq!(cached.observed, later.weather_time.to_datetime());
+        assert_eq!(cached.temperature_celsius, 21.0);
+        assert_eq!(cached.pressure_hpa, 1010.0);
+        assert_eq!(cached.gust_knots, None);
+        assert_eq!(cached.visibility_meters, Some(5000.0));
+        assert_eq!(cached.severity, Severity::Obscuration);
+        let stations: Vec<String> = reader.iter().map(|cached| cached.station).collect();
+        assert_eq!(stations, ["VOGO", "VOBL"]);
+        assert!(reader.get("KSFO").is_none());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,213 @@
+//! Telling significant changes between observations of a station apart
+//! from minor ones, eg: for notifying about them.
+
+use crate::icon::Condition;
+use crate::weather::WeatherInfo;
+use std::fmt;
+
+/// Smallest changes between two observations which are significant.
+///
+/// Used by [crate::weather::NoaaApp::observe_changes] for yielding only
+/// the observations which changed significantly, rather than every new
+/// one.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ChangeDetector {
+    /// Change of temperature in celsius
+    pub temperature_celsius: f64,
+    /// Change of the wind speed in knots
+    pub wind_knots: f64,
+    /// Change of pressure in hectopascals
+    pub pressure_hpa: f64,
+    /// Whether a change of the overall [condition](WeatherInfo::condition)
+    /// is significant. Eg: from clear to rain
+    pub condition: bool,
+}
+
+impl Default for ChangeDetector {
+    fn default() -> Self {
+        ChangeDetector {
+            temperature_celsius: 2.0,
+            wind_knots: 10.0,
+            pressure_hpa: 2.0,
+            condition: true,
+        }
+    }
+}
+
+/// A significant change between two observations.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Change {
+    /// Temperature in celsius
+    Temperature {
+        from: f64,
+        to: f64,
+    },
+    /// Wind speed in knots
+    Wind {
+        from: f64,
+        to: f64,
+    },
+    /// Pressure in hectopascals
+    Pressure {
+        from: i16,
+        to: i16,
+    },
+    Condition {
+        from: Condition,
+        to: Condition,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Temperature { from, to } => write!(f, "temperature {}°C → {}°C", from, to),
+            Change::Wind { from, to } => write!(f, "wind {}kt → {}kt", from, to),
+            Change::Pressure { from, to } => write!(f, "pressure {}hPa → {}hPa", from, to),
+            Change::Condition { from, to } => write!(f, "condition {:?} → {:?}", from, to),
+        }
+    }
+}
+
+impl ChangeDetector {
+    /// The significant changes from the `previous` observation to the
+    /// `current` one, empty when they're minor.
+    pub fn changes(&self, previous: &WeatherInfo, current: &WeatherInfo) -> Vec<Change> {
+        let mut changes = vec![];
+        let (from, to) = (previous.temperature.celsius, current.temperature.celsius);
+        if (to - from).abs() >= self.temperature_celsius {
+            changes.push(Change::Temperature { from, to });
+        }
+        let (from, to) = (previous.wind.knots, current.wind.knots);
+        if (to - from).abs() >= self.wind_knots {
+            changes.push(Change::Wind { from, to });
+        }
+        let (from, to) = (previous.pressure, current.pressure);
+        if f64::from(to - from).abs() >= self.pressure_hpa {
+            changes.push(Change::Pressure { from, to });
+        }
+        let (from, to) = (previous.condition(), current.condition());
+        if self.condition && from != to {
+            changes.push(Change::Condition { from, to });
+        }
+        changes
+    }
+
+    /// Whether the `current` observation changed significantly from the
+    /// `previous` one.
+    pub fn is_significant(&self, previous: &WeatherInfo, current: &WeatherInfo) -> bool {
+        !self.changes(previous, current).is_empty()
+    }
+}
+
+/// Smallest change of temperature in celsius shown as a [Trend].
+pub const TREND_TEMPERATURE_CELSIUS: f64 = 0.5;
+
+/// Smallest change of pressure in hectopascals shown as a [Trend].
+pub const TREND_PRESSURE_HPA: f64 = 1.0;
+
+/// Direction in which a value moved between two observations.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    /// Trend from the `previous` value to the `current` one, steady when
+    /// they differ by less than `threshold`.
+    pub fn between(previous: f64, current: f64, threshold: f64) -> Self {
+        if current - previous >= threshold {
+            Trend::Rising
+        } else if previous - current >= threshold {
+            Trend::Falling
+        } else {
+            Trend::Steady
+        }
+    }
+
+    /// Eg: ↑ when rising
+    pub fn arrow(self) -> char {
+        match self {
+            Trend::Rising => '↑',
+            Trend::Falling => '↓',
+            Trend::Steady => '→',
+        }
+    }
+}
+
+/// Trends of the temperature and pressure from an observation to the
+/// next one of the same station.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trends {
+    pub temperature: Trend,
+    pub pressure: Trend,
+}
+
+impl Trends {
+    pub fn between(previous: &WeatherInfo, current: &WeatherInfo) -> Self {
+        Trends {
+            temperature: Trend::between(
+                previous.temperature.celsius,
+                current.temperature.celsius,
+                TREND_TEMPERATURE_CELSIUS,
+            ),
+            pressure: Trend::between(
+                f64::from(previous.pressure),
+                f64::from(current.pressure),
+                TREND_PRESSURE_HPA,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::tests::vogo_report;
+    use crate::weather::{parse_weather, Temperature};
+
+    #[test]
+    fn test_changes() {
+        let (_, previous) = parse_weather(&vogo_report("1230")).unwrap();
+        let (_, mut current) = parse_weather(&vogo_report("1300")).unwrap();
+        let detector = ChangeDetector::default();
+        assert!(!detector.is_significant(&previous, &current));
+
+        current.temperature = Temperature::from_celsius(22.5);
+        current.pressure = 1012;
+        // The haze of the METAR clears
+        current.metar = None;
+        current.sky_condition = Some("overcast".into());
+        assert_eq!(
+            detector.changes(&previous, &current),
+            vec![
+                Change::Pressure {
+                    from: 1010,
+                    to: 1012
+                },
+                Change::Condition {
+                    from: Condition::Haze,
+                    to: Condition::Overcast
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trends() {
+        let (_, previous) = parse_weather(&vogo_report("1230")).unwrap();
+        let mut current = previous.clone();
+        current.temperature = Temperature::from_celsius(previous.temperature.celsius - 1.0);
+        let trends = Trends::between(&previous, &current);
+        assert_eq!(trends.temperature, Trend::Falling);
+        assert_eq!(trends.pressure, Trend::Steady);
+        assert_eq!(
+            Trend::between(1010.0, 1012.0, TREND_PRESSURE_HPA).arrow(),
+            '↑'
+        );
+    }
+}
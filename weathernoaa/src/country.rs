@@ -0,0 +1,281 @@
+//! ISO 3166 country codes, for looking up the stations of a country in
+//! [StationDirectory](crate::directory::StationDirectory), whose country
+//! names are in English.
+
+/// Alpha-2 codes along with the names their country may have in NOAA's
+/// station directory. Some countries are spelled several ways, eg:
+/// "Russia" and "Russian Federation".
+const COUNTRIES: [(&str, &[&str]); 239] = [
+    ("AD", &["Andorra"]),
+    ("AE", &["United Arab Emirates"]),
+    ("AF", &["Afghanistan"]),
+    ("AG", &["Antigua and Barbuda"]),
+    ("AI", &["Anguilla"]),
+    ("AL", &["Albania"]),
+    ("AM", &["Armenia"]),
+    ("AO", &["Angola"]),
+    ("AQ", &["Antarctica"]),
+    ("AR", &["Argentina"]),
+    ("AS", &["American Samoa"]),
+    ("AT", &["Austria"]),
+    ("AU", &["Australia"]),
+    ("AW", &["Aruba"]),
+    ("AZ", &["Azerbaijan"]),
+    ("BA", &["Bosnia and Herzegovina"]),
+    ("BB", &["Barbados"]),
+    ("BD", &["Bangladesh"]),
+    ("BE", &["Belgium"]),
+    ("BF", &["Burkina Faso"]),
+    ("BG", &["Bulgaria"]),
+    ("BH", &["Bahrain"]),
+    ("BI", &["Burundi"]),
+    ("BJ", &["Benin"]),
+    ("BM", &["Bermuda"]),
+    ("BN", &["Brunei", "Brunei Darussalam"]),
+    ("BO", &["Bolivia"]),
+    ("BR", &["Brazil"]),
+    ("BS", &["Bahamas", "Bahamas, The"]),
+    ("BT", &["Bhutan"]),
+    ("BW", &["Botswana"]),
+    ("BY", &["Belarus"]),
+    ("BZ", &["Belize"]),
+    ("CA", &["Canada"]),
+    ("CC", &["Cocos (Keeling) Islands", "Cocos Islands"]),
+    (
+        "CD",
+        &[
+            "Democratic Republic of the Congo",
+            "Congo, Democratic Republic of the",
+            "Congo (Kinshasa)",
+        ],
+    ),
+    ("CF", &["Central African Republic"]),
+    (
+        "CG",
+        &["Congo", "Republic of the Congo", "Congo (Brazzaville)"],
+    ),
+    ("CH", &["Switzerland"]),
+    ("CI", &["Cote d'Ivoire", "Ivory Coast"]),
+    ("CK", &["Cook Islands"]),
+    ("CL", &["Chile"]),
+    ("CM", &["Cameroon"]),
+    ("CN", &["China"]),
+    ("CO", &["Colombia"]),
+    ("CR", &["Costa Rica"]),
+    ("CU", &["Cuba"]),
+    ("CV", &["Cape Verde", "Cabo Verde"]),
+    ("CW", &["Curacao"]),
+    ("CX", &["Christmas Island"]),
+    ("CY", &["Cyprus"]),
+    ("CZ", &["Czech Republic", "Czechia"]),
+    ("DE", &["Germany"]),
+    ("DJ", &["Djibouti"]),
+    ("DK", &["Denmark"]),
+    ("DM", &["Dominica"]),
+    ("DO", &["Dominican Republic"]),
+    ("DZ", &["Algeria"]),
+    ("EC", &["Ecuador"]),
+    ("EE", &["Estonia"]),
+    ("EG", &["Egypt"]),
+    ("EH", &["Western Sahara"]),
+    ("ER", &["Eritrea"]),
+    ("ES", &["Spain"]),
+    ("ET", &["Ethiopia"]),
+    ("FI", &["Finland"]),
+    ("FJ", &["Fiji"]),
+    ("FK", &["Falkland Islands", "Falkland Islands (Malvinas)"]),
+    ("FM", &["Micronesia", "Micronesia, Federated States of"]),
+    ("FO", &["Faroe Islands"]),
+    ("FR", &["France"]),
+    ("GA", &["Gabon"]),
+    ("GB", &["United Kingdom"]),
+    ("GD", &["Grenada"]),
+    ("GE", &["Georgia"]),
+    ("GF", &["French Guiana"]),
+    ("GG", &["Guernsey"]),
+    ("GH", &["Ghana"]),
+    ("GI", &["Gibraltar"]),
+    ("GL", &["Greenland"]),
+    ("GM", &["Gambia", "Gambia, The"]),
+    ("GN", &["Guinea"]),
+    ("GP", &["Guadeloupe"]),
+    ("GQ", &["Equatorial Guinea"]),
+    ("GR", &["Greece"]),
+    ("GT", &["Guatemala"]),
+    ("GU", &["Guam"]),
+    ("GW", &["Guinea-Bissau"]),
+    ("GY", &["Guyana"]),
+    ("HK", &["Hong Kong"]),
+    ("HN", &["Honduras"]),
+    ("HR", &["Croatia"]),
+    ("HT", &["Haiti"]),
+    ("HU", &["Hungary"]),
+    ("ID", &["Indonesia"]),
+    ("IE", &["Ireland"]),
+    ("IL", &["Israel"]),
+    ("IM", &["Isle of Man"]),
+    ("IN", &["India"]),
+    ("IO", &["British Indian Ocean Territory"]),
+    ("IQ", &["Iraq"]),
+    ("IR", &["Iran", "Iran, Islamic Republic of"]),
+    ("IS", &["Iceland"]),
+    ("IT", &["Italy"]),
+    ("JE", &["Jersey"]),
+    ("JM", &["Jamaica"]),
+    ("JO", &["Jordan"]),
+    ("JP", &["Japan"]),
+    ("KE", &["Kenya"]),
+    ("KG", &["Kyrgyzstan"]),
+    ("KH", &["Cambodia"]),
+    ("KI", &["Kiribati"]),
+    ("KM", &["Comoros"]),
+    ("KN", &["Saint Kitts and Nevis"]),
+    (
+        "KP",
+        &[
+            "North Korea",
+            "Korea, North",
+            "Korea, Democratic People's Republic of",
+        ],
+    ),
+    ("KR", &["South Korea", "Korea, South", "Korea, Republic of"]),
+    ("KW", &["Kuwait"]),
+    ("KY", &["Cayman Islands"]),
+    ("KZ", &["Kazakhstan"]),
+    ("LA", &["Laos", "Lao People's Democratic Republic"]),
+    ("LB", &["Lebanon"]),
+    ("LC", &["Saint Lucia"]),
+    ("LI", &["Liechtenstein"]),
+    ("LK", &["Sri Lanka"]),
+    ("LR", &["Liberia"]),
+    ("LS", &["Lesotho"]),
+    ("LT", &["Lithuania"]),
+    ("LU", &["Luxembourg"]),
+    ("LV", &["Latvia"]),
+    ("LY", &["Libya"]),
+    ("MA", &["Morocco"]),
+    ("MC", &["Monaco"]),
+    ("MD", &["Moldova", "Moldova, Republic of"]),
+    ("ME", &["Montenegro"]),
+    ("MG", &["Madagascar"]),
+    ("MH", &["Marshall Islands"]),
+    (
+        "MK",
+        &[
+            "North Macedonia",
+            "Macedonia",
+            "Macedonia, The Former Yugoslav Republic of",
+        ],
+    ),
+    ("ML", &["Mali"]),
+    ("MM", &["Myanmar", "Burma"]),
+    ("MN", &["Mongolia"]),
+    ("MO", &["Macau", "Macao"]),
+    ("MP", &["Northern Mariana Islands"]),
+    ("MQ", &["Martinique"]),
+    ("MR", &["Mauritania"]),
+    ("MS", &["Montserrat"]),
+    ("MT", &["Malta"]),
+    ("MU", &["Mauritius"]),
+    ("MV", &["Maldives"]),
+    ("MW", &["Malawi"]),
+    ("MX", &["Mexico"]),
+    ("MY", &["Malaysia"]),
+    ("MZ", &["Mozambique"]),
+    ("NA", &["Namibia"]),
+    ("NC", &["New Caledonia"]),
+    ("NE", &["Niger"]),
+    ("NF", &["Norfolk Island"]),
+    ("NG", &["Nigeria"]),
+    ("NI", &["Nicaragua"]),
+    ("NL", &["Netherlands"]),
+    ("NO", &["Norway"]),
+    ("NP", &["Nepal"]),
+    ("NR", &["Nauru"]),
+    ("NU", &["Niue"]),
+    ("NZ", &["New Zealand"]),
+    ("OM", &["Oman"]),
+    ("PA", &["Panama"]),
+    ("PE", &["Peru"]),
+    ("PF", &["French Polynesia"]),
+    ("PG", &["Papua New Guinea"]),
+    ("PH", &["Philippines"]),
+    ("PK", &["Pakistan"]),
+    ("PL", &["Poland"]),
+    ("PM", &["Saint Pierre and Miquelon"]),
+    ("PN", &["Pitcairn Islands", "Pitcairn"]),
+    ("PR", &["Puerto Rico"]),
+    ("PS", &["Palestine", "Palestinian Territory"]),
+    ("PT", &["Portugal"]),
+    ("PW", &["Palau"]),
+    ("PY", &["Paraguay"]),
+    ("QA", &["Qatar"]),
+    ("RE", &["Reunion"]),
+    ("RO", &["Romania"]),
+    ("RS", &["Serbia"]),
+    ("RU", &["Russia", "Russian Federation"]),
+    ("RW", &["Rwanda"]),
+    ("SA", &["Saudi Arabia"]),
+    ("SB", &["Solomon Islands"]),
+    ("SC", &["Seychelles"]),
+    ("SD", &["Sudan"]),
+    ("SE", &["Sweden"]),
+    ("SG", &["Singapore"]),
+    ("SH", &["Saint Helena"]),
+    ("SI", &["Slovenia"]),
+    ("SJ", &["Svalbard", "Svalbard and Jan Mayen"]),
+    ("SK", &["Slovakia"]),
+    ("SL", &["Sierra Leone"]),
+    ("SM", &["San Marino"]),
+    ("SN", &["Senegal"]),
+    ("SO", &["Somalia"]),
+    ("SR", &["Suriname"]),
+    ("SS", &["South Sudan"]),
+    ("ST", &["Sao Tome and Principe"]),
+    ("SV", &["El Salvador"]),
+    ("SX", &["Sint Maarten"]),
+    ("SY", &["Syria", "Syrian Arab Republic"]),
+    ("SZ", &["Eswatini", "Swaziland"]),
+    ("TC", &["Turks and Caicos Islands"]),
+    ("TD", &["Chad"]),
+    ("TG", &["Togo"]),
+    ("TH", &["Thailand"]),
+    ("TJ", &["Tajikistan"]),
+    ("TL", &["Timor-Leste", "East Timor"]),
+    ("TM", &["Turkmenistan"]),
+    ("TN", &["Tunisia"]),
+    ("TO", &["Tonga"]),
+    ("TR", &["Turkey", "Turkiye"]),
+    ("TT", &["Trinidad and Tobago"]),
+    ("TV", &["Tuvalu"]),
+    ("TW", &["Taiwan"]),
+    ("TZ", &["Tanzania", "Tanzania, United Republic of"]),
+    ("UA", &["Ukraine"]),
+    ("UG", &["Uganda"]),
+    ("UM", &["United States Minor Outlying Islands"]),
+    ("US", &["United States"]),
+    ("UY", &["Uruguay"]),
+    ("UZ", &["Uzbekistan"]),
+    ("VC", &["Saint Vincent and the Grenadines"]),
+    ("VE", &["Venezuela"]),
+    ("VG", &["British Virgin Islands", "Virgin Islands, British"]),
+    ("VI", &["Virgin Islands", "Virgin Islands, U.S."]),
+    ("VN", &["Vietnam", "Viet Nam"]),
+    ("VU", &["Vanuatu"]),
+    ("WF", &["Wallis and Futuna"]),
+    ("WS", &["Samoa"]),
+    ("YE", &["Yemen"]),
+    ("YT", &["Mayotte"]),
+    ("ZA", &["South Africa"]),
+    ("ZM", &["Zambia"]),
+    ("ZW", &["Zimbabwe"]),
+];
+
+/// Names of the country with the ISO 3166 alpha-2 `code`, ignoring
+/// case. Empty when the code isn't known. Eg: `["India"]` for "IN"
+pub fn country_names(code: &str) -> &'static [&'static str] {
+    COUNTRIES
+        .iter()
+        .find(|(alpha2, _)| alpha2.eq_ignore_ascii_case(code))
+        .map_or(&[], |(_, names)| names)
+}
@@ -0,0 +1,383 @@
+use crate::country::country_names;
+use crate::geo::Point;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+// The directory is NOAA's list of ICAO location indicators, which is
+// semicolon separated with the following fields:
+//
+// ICAO;Block Number;Station Number;Place Name;State;Country Name;WMO Region;
+// Station Latitude;Station Longitude;Upper Air Latitude;Upper Air Longitude;
+// Station Elevation (Ha);Upper Air Elevation (Hp);RBSN indicator
+//
+// Eg: KSFO;72;494;San Francisco, San Francisco International Airport;CA;United States;4;37-37N;122-22W;37-37N;122-22W;5;6;P
+
+/// URL of NOAA's station directory
+pub const DIRECTORY_URL: &str = "https://tgftp.nws.noaa.gov/data/nsd_cccc.txt";
+
+/// Distance in kilometers by which [StationDirectory::nearest_reliable]
+/// pushes back a station which never reports on schedule. Stations
+/// reporting on schedule part of the time are pushed back in proportion.
+pub const UNRELIABLE_PENALTY_KM: f64 = 100.0;
+
+/// Metadata of a station in the directory.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StationInfo {
+    /// ICAO code of the station. Eg: VOBL
+    pub icao: String,
+    /// Name of the station
+    pub name: String,
+    /// State, only present for some countries
+    pub state: Option<String>,
+    /// Name of the country. Eg: India
+    pub country: String,
+    /// WMO index number, made of the block and station number. Eg: 43295
+    pub wmo_id: Option<String>,
+    /// Latitude in decimal degrees, positive north of the equator
+    pub latitude: f64,
+    /// Longitude in decimal degrees, positive east of Greenwich
+    pub longitude: f64,
+    /// Elevation in meters
+    pub elevation: Option<i32>,
+}
+
+/// Directory of the stations known to NOAA.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct StationDirectory {
+    stations: Vec<StationInfo>,
+}
+
+impl StationDirectory {
+    /// Parses the directory. Lines that can't be parsed are skipped.
+    pub fn parse(input: &str) -> Self {
+        StationDirectory {
+            stations: input.lines().filter_map(parse_station_line).collect(),
+        }
+    }
+
+    /// All the stations in the directory
+    pub fn stations(&self) -> &[StationInfo] {
+        &self.stations
+    }
+
+    /// Looks up a station by its ICAO code, ignoring case.
+    pub fn get(&self, icao: &str) -> Option<&StationInfo> {
+        self.stations
+            .iter()
+            .find(|station| station.icao.eq_ignore_ascii_case(icao))
+    }
+
+    /// Stations in the given country, by its name or its ISO 3166
+    /// alpha-2 code, ignoring case. Eg: `by_country("India")` or
+    /// `by_country("IN")`
+    pub fn by_country(&self, country: &str) -> Vec<&StationInfo> {
+        let names = country_names(country);
+        self.stations
+            .iter()
+            .filter(|station| {
+                station.country.eq_ignore_ascii_case(country)
+                    || names
+                        .iter()
+                        .any(|name| station.country.eq_ignore_ascii_case(name))
+            })
+            .collect()
+    }
+
+    /// The `count` stations closest to `point`, along with their
+    /// distance in kilometers, nearest first.
+    pub fn nearest(&self, point: &Point, count: usize) -> Vec<(&StationInfo, f64)> {
+        let mut stations: Vec<(&StationInfo, f64)> = self
+            .stations
+            .iter()
+            .map(|station| (station, point.distance_km(&station.point())))
+            .collect();
+        stations.sort_by(|a, b| a.1.total_cmp(&b.1));
+        stations.truncate(count);
+        stations
+    }
+
+    /// Same as [StationDirectory::nearest], preferring the stations
+    /// which report on schedule: each is ranked by its distance plus a
+    /// penalty of up to [UNRELIABLE_PENALTY_KM], according to its
+    /// [reliability score](crate::reliability::Reliability::score).
+    /// Stations whose score isn't known have no penalty.
+    pub fn nearest_reliable<F>(
+        &self,
+        point: &Point,
+        count: usize,
+        score: F,
+    ) -> Vec<(&StationInfo, f64)>
+    where
+        F: Fn(&StationInfo) -> Option<f64>,
+    {
+        let mut stations: Vec<(&StationInfo, f64, f64)> = self
+            .stations
+            .iter()
+            .map(|station| {
+                let distance = point.distance_km(&station.point());
+                let penalty = score(station).map_or(0.0, |score| {
+                    (1.0 - score.clamp(0.0, 1.0)) * UNRELIABLE_PENALTY_KM
+                });
+                (station, distance, distance + penalty)
+            })
+            .collect();
+        stations.sort_by(|a, b| a.2.total_cmp(&b.2));
+        stations
+            .into_iter()
+            .take(count)
+            .map(|(station, distance, _)| (station, distance))
+            .collect()
+    }
+
+    /// Stations within `width_km` of the great-circle path from `from`
+    /// to `to`, eg: for a briefing of the weather along a flight, along
+    /// with how far along the path they are in kilometers. They're
+    /// ordered from `from` to `to`. Stations beyond the ends of the
+    /// path are those within `width_km` of the ends.
+    pub fn along_route(&self, from: &Point, to: &Point, width_km: f64) -> Vec<(&StationInfo, f64)> {
+        let length = from.distance_km(to);
+        let mut stations: Vec<(&StationInfo, f64)> = self
+            .stations
+            .iter()
+            .filter_map(|station| {
+                let point = station.point();
+                let along = point.along_track_km(from, to);
+                let distance = if along < 0.0 {
+                    point.distance_km(from)
+                } else if along > length {
+                    point.distance_km(to)
+                } else {
+                    point.cross_track_km(from, to).abs()
+                };
+                (distance <= width_km).then_some((station, along.clamp(0.0, length)))
+            })
+            .collect();
+        stations.sort_by(|a, b| a.1.total_cmp(&b.1));
+        stations
+    }
+
+    /// Stations inside the bounding box, edges included. A box crossing
+    /// the antimeridian can be given with `min_lon` greater than
+    /// `max_lon`.
+    pub fn within_bbox(
+        &self,
+        min_lat: f64,
+        min_lon: f64,
+        max_lat: f64,
+        max_lon: f64,
+    ) -> Vec<&StationInfo> {
+        let within_lon = |lon: f64| {
+            if min_lon <= max_lon {
+                (min_lon..=max_lon).contains(&lon)
+            } else {
+                lon >= min_lon || lon <= max_lon
+            }
+        };
+        self.stations
+            .iter()
+            .filter(|station| {
+                (min_lat..=max_lat).contains(&station.latitude) && within_lon(station.longitude)
+            })
+            .collect()
+    }
+}
+
+/// Copy of the station directory kept on disk, so that it's available
+/// instantly and offline. See [crate::weather::NoaaApp::with_directory_cache].
+#[derive(Debug, Clone)]
+pub struct DirectoryCache {
+    /// File holding the directory, as downloaded
+    pub path: PathBuf,
+    /// Age after which the directory is refreshed
+    pub max_age: Duration,
+}
+
+impl DirectoryCache {
+    pub fn new(path: PathBuf, max_age: Duration) -> Self {
+        DirectoryCache { path, max_age }
+    }
+
+    /// The cached directory along with its age, `None` when it hasn't
+    /// been cached yet or can't be read.
+    pub fn read(&self) -> Option<(StationDirectory, Duration)> {
+        let modified = fs::metadata(&self.path).ok()?.modified().ok()?;
+        let text = fs::read_to_string(&self.path).ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        Some((StationDirectory::parse(&text), age))
+    }
+
+    /// Replaces the cached directory. The file is replaced atomically, so
+    /// that concurrent readers never see a partial directory.
+    pub fn write(&self, text: &str) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = self.path.with_extension("partial");
+        fs::write(&partial, text)?;
+        fs::rename(&partial, &self.path)
+    }
+}
+
+fn parse_station_line(line: &str) -> Option<StationInfo> {
+    let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+    if fields.len() < 12 || fields[0].is_empty() {
+        return None;
+    }
+    let (block, number) = (fields[1], fields[2]);
+    let wmo_id = if block.chars().all(|c| c.is_ascii_digit())
+        && number.chars().all(|c| c.is_ascii_digit())
+        && !block.is_empty()
+        && !number.is_empty()
+    {
+        Some(format!("{}{}", block, number))
+    } else {
+        None
+    };
+    Some(StationInfo {
+        icao: fields[0].to_owned(),
+        name: fields[3].to_owned(),
+        state: Some(fields[4])
+            .filter(|s| !s.is_empty() && *s != "--")
+            .map(|s| s.to_owned()),
+        country: fields[5].to_owned(),
+        wmo_id,
+        latitude: parse_coordinate(fields[7])?,
+        longitude: parse_coordinate(fields[8])?,
+        elevation: fields[11].parse().ok(),
+    })
+}
+
+/// Parses coordinates in degrees, minutes and optionally seconds
+/// followed by the hemisphere. Eg: 37-37N, 122-22-30W
+pub(crate) fn parse_coordinate(input: &str) -> Option<f64> {
+    let hemisphere = input.chars().last()?;
+    let sign = match hemisphere {
+        'N' | 'E' => 1.0,
+        'S' | 'W' => -1.0,
+        _ => return None,
+    };
+    let mut degrees = 0.0;
+    for (part, divisor) in input[..input.len() - 1]
+        .split('-')
+        .zip([1.0, 60.0, 3600.0].iter())
+    {
+        degrees += part.parse::<f64>().ok()? / divisor;
+    }
+    Some(sign * degrees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIRECTORY: &str = "KSFO;72;494;San Francisco, San Francisco International Airport;CA;United States;4;37-37N;122-22W;37-37N;122-22W;5;6;P
+VOBL;43;295;Bangalore / Bengaluru International Airport;;India;2;13-12N;077-42E;;;915;;
+VOGO;--;---;Goa / Dabolim Navy Airport;;India;2;15-23-01N;073-50-02E;;;46;;
+broken line
+";
+
+    #[test]
+    fn test_parse_directory() {
+        let directory = StationDirectory::parse(DIRECTORY);
+        assert_eq!(directory.stations().len(), 3);
+        let vobl = directory.get("vobl").unwrap();
+        assert_eq!(vobl.state, None);
+        assert_eq!(vobl.wmo_id, Some("43295".into()));
+        assert_eq!(vobl.elevation, Some(915));
+        assert_eq!(vobl.latitude, 13.2);
+        assert_eq!(vobl.longitude, 77.7);
+
+        let ksfo = directory.get("KSFO").unwrap();
+        assert_eq!(ksfo.state, Some("CA".into()));
+        assert!((ksfo.longitude + 122.366_666).abs() < 1e-5);
+
+        let vogo = directory.get("VOGO").unwrap();
+        assert_eq!(vogo.wmo_id, None);
+        assert!((vogo.latitude - 15.383_611).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_by_country() {
+        let directory = StationDirectory::parse(DIRECTORY);
+        let india: Vec<&str> = directory
+            .by_country("india")
+            .iter()
+            .map(|s| s.icao.as_str())
+            .collect();
+        assert_eq!(india, vec!["VOBL", "VOGO"]);
+        assert_eq!(directory.by_country("IN"), directory.by_country("India"));
+        assert_eq!(directory.by_country("us")[0].icao, "KSFO");
+        assert!(directory.by_country("China").is_empty());
+        assert!(directory.by_country("XX").is_empty());
+    }
+
+    #[test]
+    fn test_nearest() {
+        let directory = StationDirectory::parse(DIRECTORY);
+        let nearest = directory.nearest(&Point::new(12.97, 77.59), 2);
+        assert_eq!(nearest[0].0.icao, "VOBL");
+        assert!((nearest[0].1 - 27.7).abs() < 1.0);
+        assert_eq!(nearest[1].0.icao, "VOGO");
+    }
+
+    #[test]
+    fn test_nearest_reliable() {
+        let directory = StationDirectory::parse(DIRECTORY);
+        // Slightly closer to Goa than to Bangalore
+        let point = Point::new(14.3, 75.7);
+        let icao = |stations: Vec<(&StationInfo, f64)>| -> Vec<String> {
+            stations.into_iter().map(|(s, _)| s.icao.clone()).collect()
+        };
+        assert_eq!(icao(directory.nearest(&point, 2)), ["VOGO", "VOBL"]);
+        let reliable = directory
+            .nearest_reliable(&point, 2, |station| (station.icao == "VOGO").then_some(0.5));
+        assert_eq!(icao(reliable.clone()), ["VOBL", "VOGO"]);
+        assert_eq!(reliable[1].1, directory.nearest(&point, 1)[0].1);
+    }
+
+    #[test]
+    fn test_along_route() {
+        let directory = StationDirectory::parse(DIRECTORY);
+        let route = |from: &str, to: &str, width_km: f64| -> Vec<(String, f64)> {
+            let point = |icao: &str| directory.get(icao).unwrap().point();
+            directory
+                .along_route(&point(from), &point(to), width_km)
+                .into_iter()
+                .map(|(s, along)| (s.icao.clone(), along.round()))
+                .collect()
+        };
+        assert_eq!(
+            route("VOGO", "VOBL", 10.0),
+            [("VOGO".into(), 0.0), ("VOBL".into(), 482.0)]
+        );
+        assert_eq!(route("VOBL", "VOGO", 10.0)[0].0, "VOBL");
+        assert_eq!(route("VOBL", "KSFO", 10.0).len(), 2);
+    }
+
+    #[test]
+    fn test_within_bbox() {
+        let directory = StationDirectory::parse(DIRECTORY);
+        let icao = |stations: Vec<&StationInfo>| -> Vec<String> {
+            stations.into_iter().map(|s| s.icao.clone()).collect()
+        };
+        assert_eq!(
+            icao(directory.within_bbox(10.0, 70.0, 14.0, 80.0)),
+            vec!["VOBL"]
+        );
+        assert_eq!(
+            icao(directory.within_bbox(10.0, 70.0, 40.0, 80.0)),
+            vec!["VOBL", "VOGO"]
+        );
+        // Crossing the antimeridian
+        assert_eq!(
+            icao(directory.within_bbox(30.0, 170.0, 40.0, -120.0)),
+            vec!["KSFO"]
+        );
+        assert!(directory.within_bbox(-10.0, 0.0, 0.0, 10.0).is_empty());
+    }
+}
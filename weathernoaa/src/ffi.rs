@@ -0,0 +1,228 @@
+//! C-compatible interface, enabled by the `ffi` feature. Build the
+//! library for linking from C with:
+//!
+//! ```text
+//! cargo rustc -p weathernoaa --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! The declarations are in `include/weathernoaa.h`, generated with
+//! cbindgen. Strings returned by the functions are owned by the caller
+//! and released with [weathernoaa_string_free]. When a function fails,
+//! [weathernoaa_last_error] describes the failure.
+
+use crate::weather::{self, NoaaApp, ParseError, WeatherError, WeatherInfo};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::OnceLock;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Main values of an observation. Missing values are NaN.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct WeathernoaaObservation {
+    /// Seconds since the Unix epoch, -1 when the time isn't known
+    pub timestamp: i64,
+    pub temperature_celsius: f64,
+    pub dewpoint_celsius: f64,
+    /// Relative humidity in percent
+    pub relative_humidity: f64,
+    pub pressure_hpa: f64,
+    /// Direction the wind is blowing from, in degrees
+    pub wind_azimuth: f64,
+    pub wind_knots: f64,
+    pub gust_knots: f64,
+    pub visibility_meters: f64,
+}
+
+impl From<&WeatherInfo> for WeathernoaaObservation {
+    fn from(info: &WeatherInfo) -> Self {
+        WeathernoaaObservation {
+            timestamp: info
+                .weather_time
+                .to_datetime()
+                .map_or(-1, |time| time.timestamp()),
+            temperature_celsius: info.temperature.celsius,
+            dewpoint_celsius: info.dewpoint.celsius,
+            relative_humidity: info.relative_humidity,
+            pressure_hpa: f64::from(info.pressure),
+            wind_azimuth: info.wind.azimuth,
+            wind_knots: info.wind.knots,
+            gust_knots: info.wind.gust_knots.unwrap_or(f64::NAN),
+            visibility_meters: info.visibility_meters().unwrap_or(f64::NAN),
+        }
+    }
+}
+
+/// App shared by the calls, so that connections are reused.
+fn app() -> &'static NoaaApp {
+    static APP: OnceLock<NoaaApp> = OnceLock::new();
+    APP.get_or_init(|| NoaaApp::new().with_metar_fallback(true))
+}
+
+fn set_last_error(message: String) {
+    // Messages can't contain nul bytes, which end C strings
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, recording its failure, or its panic which mustn't unwind
+/// into C, as the last error.
+fn guard<T>(f: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(message)) => {
+            set_last_error(message);
+            None
+        }
+        Err(_) => {
+            set_last_error("weathernoaa panicked".to_owned());
+            None
+        }
+    }
+}
+
+/// Reads a nul terminated UTF-8 string argument.
+unsafe fn read_str<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("Unexpected null string".to_owned());
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|err| format!("Invalid UTF-8 string: {}", err))
+}
+
+fn to_json(info: &WeatherInfo) -> Result<*mut c_char, String> {
+    let json = serde_json::to_string(info).map_err(|err| err.to_string())?;
+    // JSON escapes control characters, so it has no nul bytes
+    Ok(CString::new(json)
+        .map_err(|err| err.to_string())?
+        .into_raw())
+}
+
+/// Fetches the current weather of a station, as JSON. Returns null on
+/// failure.
+///
+/// # Safety
+///
+/// `station` must be a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn weathernoaa_get_blocking(station: *const c_char) -> *mut c_char {
+    guard(|| {
+        let station = read_str(station)?;
+        let info = app()
+            .get_blocking_weather(station)
+            .map_err(|err| err.to_string())?;
+        to_json(&info)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Fetches the current weather of a station into `observation`. Returns
+/// 0 on success and -1 on failure, leaving `observation` untouched.
+///
+/// # Safety
+///
+/// `station` must be a valid nul terminated string and `observation` a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn weathernoaa_get_observation(
+    station: *const c_char,
+    observation: *mut WeathernoaaObservation,
+) -> c_int {
+    let result = guard(|| {
+        if observation.is_null() {
+            return Err("Unexpected null observation".to_owned());
+        }
+        let station = read_str(station)?;
+        let info = app()
+            .get_blocking_weather(station)
+            .map_err(|err| err.to_string())?;
+        *observation = WeathernoaaObservation::from(&info);
+        Ok(())
+    });
+    result.map_or(-1, |()| 0)
+}
+
+/// Parses the content of a station's decoded file into JSON. Returns
+/// null on failure.
+///
+/// # Safety
+///
+/// `text` must be a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn weathernoaa_parse_weather(text: *const c_char) -> *mut c_char {
+    guard(|| {
+        let text = read_str(text)?;
+        let (_, info) = weather::parse_weather(text)
+            .map_err(|err| WeatherError::from(ParseError::decoded(text, err)).to_string())?;
+        to_json(&info)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Description of the last failure on the calling thread, or null. The
+/// string is owned by the library and valid until the next call on the
+/// thread.
+#[no_mangle]
+pub extern "C" fn weathernoaa_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Releases a string returned by the library.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by the library, not released
+/// before.
+#[no_mangle]
+pub unsafe extern "C" fn weathernoaa_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::tests::vogo_report;
+
+    #[test]
+    fn test_parse_weather() {
+        let report = CString::new(vogo_report("1230")).unwrap();
+        let json = unsafe { weathernoaa_parse_weather(report.as_ptr()) };
+        assert!(!json.is_null());
+        let parsed = unsafe { CStr::from_ptr(json) }.to_str().unwrap();
+        let info: WeatherInfo = serde_json::from_str(parsed).unwrap();
+        assert_eq!(info.weather_time.time, "1230 UTC");
+        unsafe { weathernoaa_string_free(json) };
+
+        let broken = CString::new("broken").unwrap();
+        assert!(unsafe { weathernoaa_parse_weather(broken.as_ptr()) }.is_null());
+        let error = unsafe { CStr::from_ptr(weathernoaa_last_error()) };
+        assert_eq!(
+            error.to_str().unwrap(),
+            "Failed to parse the observation: expected the time, like \
+             'Mar 28, 2021 - 04:00 AM EDT / 2021.03.28 0800 UTC' at line 2, column 1: \"\""
+        );
+        assert!(unsafe { weathernoaa_parse_weather(ptr::null()) }.is_null());
+    }
+
+    #[test]
+    fn test_observation() {
+        let (_, info) = weather::parse_weather(&vogo_report("1230")).unwrap();
+        let observation = WeathernoaaObservation::from(&info);
+        assert_eq!(observation.timestamp, 1_703_939_400);
+        assert_eq!(observation.temperature_celsius, info.temperature.celsius);
+        assert!(observation.gust_knots.is_nan());
+    }
+}
@@ -0,0 +1,630 @@
+use crate::change::{Trend, Trends};
+use crate::geo::wind_arrow;
+use crate::icon::{Condition, IconSet};
+use crate::metar::KMH_PER_KNOT;
+use crate::weather::{Temperature, WeatherInfo, WindInfo};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Options controlling how numeric values are formatted for display.
+///
+/// A precision of `None` keeps the value as reported, which is usually
+/// an integer for decoded observations.
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormatOptions {
+    /// Decimal places of temperatures
+    pub temperature_precision: Option<usize>,
+    /// Decimal places of wind speeds
+    pub wind_precision: Option<usize>,
+    /// Decimal places of pressures
+    pub pressure_precision: Option<usize>,
+    /// Round every value to an integer, eg: for status bars. Takes
+    /// precedence over the precisions.
+    pub round: bool,
+}
+
+impl FormatOptions {
+    /// Options rounding every value to an integer.
+    pub fn compact() -> Self {
+        FormatOptions {
+            round: true,
+            ..FormatOptions::default()
+        }
+    }
+
+    /// Formats a temperature value, in either unit.
+    pub fn temperature(&self, value: f64) -> String {
+        self.number(value, self.temperature_precision)
+    }
+
+    /// Formats a temperature in celsius, with the Fahrenheit value in
+    /// parenthesis. Eg: 23 °C (73.4 °F)
+    pub fn celsius_fahrenheit(&self, temperature: &Temperature) -> String {
+        format!(
+            "{} °C ({} °F)",
+            self.temperature(temperature.celsius),
+            self.temperature(temperature.fahrenheit)
+        )
+    }
+
+    /// Formats a wind speed, in either unit.
+    pub fn wind(&self, value: f64) -> String {
+        self.number(value, self.wind_precision)
+    }
+
+    /// Formats a wind speed in knots, with the speed in miles per hour
+    /// in parenthesis. Eg: 8 kt (9 mph)
+    pub fn wind_speed(&self, wind: &WindInfo) -> String {
+        format!("{} kt ({} mph)", self.wind(wind.knots), self.wind(wind.mph))
+    }
+
+    /// Formats the gust speed in knots, with the speed in miles per hour
+    /// in parenthesis. `None` when the wind isn't gusting.
+    pub fn gust_speed(&self, wind: &WindInfo) -> Option<String> {
+        let (knots, mph) = (wind.gust_knots?, wind.gust_mph?);
+        Some(format!("{} kt ({} mph)", self.wind(knots), self.wind(mph)))
+    }
+
+    /// Formats the speed in knots followed by the gusts, if any, as in a
+    /// METAR. Eg: 14G23
+    pub fn compact_wind_speed(&self, wind: &WindInfo) -> String {
+        match wind.gust_knots {
+            Some(gust) => format!("{}G{}", self.wind(wind.knots), self.wind(gust)),
+            None => self.wind(wind.knots),
+        }
+    }
+
+    /// Formats a pressure in hectopascal.
+    pub fn pressure(&self, value: f64) -> String {
+        self.number(value, self.pressure_precision)
+    }
+
+    /// Formats an observation on a single line, for prompts, status bars
+    /// and window titles. The age of the observation is appended when
+    /// `now` is given. Eg: 12°C ↗14kt 10mi OVC 1017hPa (25m ago)
+    pub fn oneline(
+        &self,
+        info: &WeatherInfo,
+        icons: IconSet,
+        now: Option<DateTime<Utc>>,
+    ) -> String {
+        self.oneline_with_trends(info, icons, now, None)
+    }
+
+    /// Formats an observation on a single line like
+    /// [FormatOptions::oneline], with the arrows of the `trends` since
+    /// the previous observation after the temperature and pressure. Eg:
+    /// 12°C↑ ↗14kt 10mi OVC 1017hPa↓
+    pub fn oneline_with_trends(
+        &self,
+        info: &WeatherInfo,
+        icons: IconSet,
+        now: Option<DateTime<Utc>>,
+        trends: Option<Trends>,
+    ) -> String {
+        oneline_formatted(self, info, icons, now, trends)
+    }
+
+    /// Formats the current conditions of an observation in the layout of
+    /// wttr.in, with a drawing of the condition next to its description,
+    /// the temperature, wind, visibility and pressure in metric units:
+    ///
+    /// ```text
+    ///    \  /        Partly cloudy
+    ///  _ /"".-.      +12 °C
+    ///    \_(   ).    ↗ 26 km/h
+    ///    /(___(__)   10 km
+    ///                1017 hPa
+    /// ```
+    pub fn wttr(&self, info: &WeatherInfo) -> String {
+        let wind = &info.wind;
+        let direction = if wind.knots == 0.0 {
+            String::new()
+        } else if wind.cardinal == WindInfo::default().cardinal {
+            "~ ".to_owned()
+        } else {
+            format!("{} ", wind_arrow(wind.azimuth))
+        };
+        let mut temperature = self.temperature(info.temperature.celsius);
+        if info.temperature.celsius > 0.0 && temperature != "0" {
+            temperature.insert(0, '+');
+        }
+        let visibility = match info.visibility_meters() {
+            Some(meters) if meters < 5000.0 => format!("{:.1} km", meters / 1000.0),
+            Some(meters) => format!("{:.0} km", meters / 1000.0),
+            None => compact_visibility(&info.visibility),
+        };
+        let details = [
+            describe_condition(info),
+            format!("{} °C", temperature),
+            format!(
+                "{}{} km/h",
+                direction,
+                self.wind((wind.knots * KMH_PER_KNOT).round())
+            ),
+            visibility,
+            format!("{} hPa", self.pressure(f64::from(info.pressure))),
+        ];
+        let mut out = String::new();
+        for (art, detail) in wttr_art(info.condition()).iter().zip(details) {
+            let padding = 15usize.saturating_sub(art.chars().count());
+            out.push_str(&format!("{}{}{}\n", art, " ".repeat(padding), detail));
+        }
+        out
+    }
+
+    /// Formats an observation as a banner for terminal dashboards and
+    /// MOTD scripts: the drawing of its condition next to its
+    /// temperature in large characters, followed by a summary. Eg:
+    ///
+    /// ```text
+    ///      .-.         #    ###   #   ####
+    ///     (   ).      ##   #   # # # #
+    ///    (___(__)      #     ##   #  #
+    ///   ‚‘‚‘‚‘‚‘       #    #        #
+    ///   ‚’‚’‚’‚’      ###  #####      ####
+    ///
+    /// Light rain, ↗ 14 kt, 1017 hPa
+    /// ```
+    pub fn banner(&self, info: &WeatherInfo) -> String {
+        let temperature = format!("{}°C", self.temperature(info.temperature.celsius));
+        let glyphs: Vec<[&str; 5]> = temperature.chars().filter_map(big_glyph).collect();
+        let mut out = String::new();
+        for (row, art) in wttr_art(info.condition()).iter().enumerate() {
+            let padding = 15usize.saturating_sub(art.chars().count());
+            let big: Vec<&str> = glyphs.iter().map(|glyph| glyph[row]).collect();
+            let line = format!("{}{}{}", art, " ".repeat(padding), big.join(" "));
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        let wind = &info.wind;
+        let wind = if wind.knots == 0.0 {
+            "calm".to_owned()
+        } else {
+            format!(
+                "{} {} kt",
+                wind_arrow(wind.azimuth),
+                self.compact_wind_speed(wind)
+            )
+        };
+        out.push_str(&format!(
+            "\n{}, {}, {} hPa\n",
+            describe_condition(info),
+            wind,
+            self.pressure(f64::from(info.pressure))
+        ));
+        out
+    }
+
+    fn number(&self, value: f64, precision: Option<usize>) -> String {
+        let formatted = match (self.round, precision) {
+            (true, _) => format!("{:.0}", value),
+            (false, Some(precision)) => format!("{:.*}", precision, value),
+            (false, None) => value.to_string(),
+        };
+        // Rounding small negative values shouldn't show as -0
+        match formatted.strip_prefix('-') {
+            Some(unsigned) if unsigned.chars().all(|c| c == '0' || c == '.') => unsigned.to_owned(),
+            _ => formatted,
+        }
+    }
+}
+
+/// How the values of observations are formatted, along with their unit,
+/// by [oneline_with] and the [Display](std::fmt::Display) of
+/// [WeatherInfo]. Implemented by [FormatOptions], and by embedders
+/// needing other units or precisions, eg: a GUI showing Fahrenheit.
+pub trait ValueFormatter {
+    /// Eg: 12°C
+    fn format_temperature(&self, celsius: f64) -> String;
+
+    /// Eg: 14kt
+    fn format_speed(&self, knots: f64) -> String;
+
+    /// Eg: 1017hPa
+    fn format_pressure(&self, hectopascals: f64) -> String;
+
+    /// Eg: 6km
+    fn format_distance(&self, meters: f64) -> String;
+
+    /// The speed of the wind along with its gusts, if any. Eg: 14kt G23kt
+    fn format_wind(&self, wind: &WindInfo) -> String {
+        match wind.gust_knots {
+            Some(gust) => format!(
+                "{} G{}",
+                self.format_speed(wind.knots),
+                self.format_speed(gust)
+            ),
+            None => self.format_speed(wind.knots),
+        }
+    }
+
+    /// The visibility of an observation, as a distance when known and
+    /// otherwise as reported. Eg: 6km, >7mi
+    fn format_visibility(&self, info: &WeatherInfo) -> String {
+        match info.visibility_meters() {
+            Some(meters) => self.format_distance(meters),
+            None => compact_visibility(&info.visibility),
+        }
+    }
+}
+
+/// The values are formatted with the precisions of the options, in the
+/// units of METARs, the visibility being the one of the decoded report.
+impl ValueFormatter for FormatOptions {
+    fn format_temperature(&self, celsius: f64) -> String {
+        format!("{}°C", self.temperature(celsius))
+    }
+
+    fn format_speed(&self, knots: f64) -> String {
+        format!("{}kt", self.wind(knots))
+    }
+
+    fn format_pressure(&self, hectopascals: f64) -> String {
+        format!("{}hPa", self.pressure(hectopascals))
+    }
+
+    fn format_distance(&self, meters: f64) -> String {
+        if meters < 5000.0 {
+            format!("{:.1}km", meters / 1000.0)
+        } else {
+            format!("{:.0}km", meters / 1000.0)
+        }
+    }
+
+    /// Eg: 14G23kt
+    fn format_wind(&self, wind: &WindInfo) -> String {
+        format!("{}kt", self.compact_wind_speed(wind))
+    }
+
+    fn format_visibility(&self, info: &WeatherInfo) -> String {
+        compact_visibility(&info.visibility)
+    }
+}
+
+/// Formats an observation on a single line like [FormatOptions::oneline],
+/// with the values formatted by `formatter`.
+pub fn oneline_with(formatter: &dyn ValueFormatter, info: &WeatherInfo, icons: IconSet) -> String {
+    oneline_formatted(formatter, info, icons, None, None)
+}
+
+fn oneline_formatted(
+    formatter: &dyn ValueFormatter,
+    info: &WeatherInfo,
+    icons: IconSet,
+    now: Option<DateTime<Utc>>,
+    trends: Option<Trends>,
+) -> String {
+    let arrow = |trend: fn(&Trends) -> Trend| {
+        trends
+            .as_ref()
+            .map_or(String::new(), |t| trend(t).arrow().to_string())
+    };
+    let wind = &info.wind;
+    let direction = if wind.knots == 0.0 {
+        String::new()
+    } else if wind.cardinal == WindInfo::default().cardinal {
+        "~".to_owned()
+    } else {
+        wind_arrow(wind.azimuth).to_string()
+    };
+    let mut line = format!(
+        "{}{} {}{} {} {} {}{}",
+        formatter.format_temperature(info.temperature.celsius),
+        arrow(|t| t.temperature),
+        direction,
+        formatter.format_wind(wind),
+        formatter.format_visibility(info),
+        info.glyph(icons),
+        formatter.format_pressure(f64::from(info.pressure)),
+        arrow(|t| t.pressure),
+    );
+    if let Some(age) = now.and_then(|now| info.weather_time.age(now)) {
+        line.push_str(&format!(" ({} ago)", compact_duration(age)));
+    }
+    line
+}
+
+/// Describes a duration as a time in the past, in the largest whole
+/// unit. Eg: just now, 23 minutes ago, 1 hour ago
+pub fn humanize_duration(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    let (count, unit) = match minutes {
+        0 => return "just now".to_owned(),
+        1..=59 => (minutes, "minute"),
+        60..=2879 => (minutes / 60, "hour"),
+        _ => (minutes / 1440, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+/// Describes the weather of an observation, or its sky condition when
+/// there's no weather, capitalized. Eg: Light rain
+fn describe_condition(info: &WeatherInfo) -> String {
+    let groups: Vec<String> = info
+        .weather_groups()
+        .iter()
+        .map(|g| g.to_string())
+        .collect();
+    let description = if !groups.is_empty() {
+        groups.join(", ")
+    } else {
+        info.weather
+            .clone()
+            .or_else(|| info.sky_condition.clone())
+            .unwrap_or_else(|| "Unknown".to_owned())
+    };
+    let mut chars = description.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => description,
+    }
+}
+
+/// Character in large letters, five lines high, as used by
+/// [FormatOptions::banner]. `None` for characters without one.
+fn big_glyph(c: char) -> Option<[&'static str; 5]> {
+    let glyph = match c {
+        '0' => [" ### ", "#   #", "#   #", "#   #", " ### "],
+        '1' => ["  #  ", " ##  ", "  #  ", "  #  ", " ### "],
+        '2' => [" ### ", "#   #", "  ## ", " #   ", "#####"],
+        '3' => ["#### ", "    #", " ### ", "    #", "#### "],
+        '4' => ["#   #", "#   #", "#####", "    #", "    #"],
+        '5' => ["#####", "#    ", "#### ", "    #", "#### "],
+        '6' => [" ### ", "#    ", "#### ", "#   #", " ### "],
+        '7' => ["#####", "    #", "   # ", "  #  ", "  #  "],
+        '8' => [" ### ", "#   #", " ### ", "#   #", " ### "],
+        '9' => [" ### ", "#   #", " ####", "    #", " ### "],
+        '-' => ["    ", "    ", "####", "    ", "    "],
+        '.' => [" ", " ", " ", " ", "#"],
+        '°' => [" # ", "# #", " # ", "   ", "   "],
+        'C' => [" ####", "#    ", "#    ", "#    ", " ####"],
+        _ => return None,
+    };
+    Some(glyph)
+}
+
+/// Drawing of a condition in wttr.in's style, five lines high.
+fn wttr_art(condition: Condition) -> [&'static str; 5] {
+    match condition {
+        Condition::Unknown => ["    .-.", "     __)", "    (", "     `-’", "      •"],
+        Condition::Clear => [
+            "    \\   /",
+            "     .-.",
+            "  ― (   ) ―",
+            "     `-’",
+            "    /   \\",
+        ],
+        Condition::PartlyCloudy => [
+            "   \\  /",
+            " _ /\"\".-.",
+            "   \\_(   ).",
+            "   /(___(__)",
+            "",
+        ],
+        Condition::MostlyCloudy | Condition::Overcast | Condition::Windy => {
+            ["", "     .--.", "  .-(    ).", " (___.__)__)", ""]
+        }
+        Condition::Haze
+        | Condition::Smoke
+        | Condition::Fog
+        | Condition::Dust
+        | Condition::VolcanicAsh
+        | Condition::Sandstorm => ["", " _ - _ - _ -", "  _ - _ - _", " _ - _ - _ -", ""],
+        Condition::Drizzle => [
+            "     .-.",
+            "    (   ).",
+            "   (___(__)",
+            "    ‘ ‘ ‘ ‘",
+            "   ‘ ‘ ‘ ‘",
+        ],
+        Condition::Rain => [
+            "     .-.",
+            "    (   ).",
+            "   (___(__)",
+            "  ‚‘‚‘‚‘‚‘",
+            "  ‚’‚’‚’‚’",
+        ],
+        Condition::Showers => [
+            " _`/\"\".-.",
+            "  ,\\_(   ).",
+            "   /(___(__)",
+            "     ‘ ‘ ‘ ‘",
+            "    ‘ ‘ ‘ ‘",
+        ],
+        Condition::Sleet | Condition::Hail => [
+            "     .-.",
+            "    (   ).",
+            "   (___(__)",
+            "    ‘ * ‘ *",
+            "   * ‘ * ‘",
+        ],
+        Condition::Snow => [
+            "     .-.",
+            "    (   ).",
+            "   (___(__)",
+            "    *  *  *",
+            "   *  *  *",
+        ],
+        Condition::Thunderstorm | Condition::Tornado => [
+            " _`/\"\".-.",
+            "  ,\\_(   ).",
+            "   /(___(__)",
+            "    ⚡‘‘⚡‘‘",
+            "    ‘ ‘ ‘ ‘",
+        ],
+    }
+}
+
+/// Shortens a visibility in the wording of NOAA's decoded files. Eg:
+/// greater than 7 mile(s):0 gives >7mi
+fn compact_visibility(visibility: &str) -> String {
+    let visibility = visibility
+        .trim_end_matches(":0")
+        .trim_end_matches(" mile(s)")
+        .replace("greater than ", ">")
+        .replace("less than ", "<");
+    format!("{}mi", visibility)
+}
+
+/// Describes a duration in its largest whole unit, abbreviated. Eg: 25m,
+/// 3h, 2d
+pub fn compact_duration(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    match minutes {
+        0..=59 => format!("{}m", minutes),
+        60..=2879 => format!("{}h", minutes / 60),
+        _ => format!("{}d", minutes / 1440),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precision() {
+        let options = FormatOptions {
+            temperature_precision: Some(2),
+            wind_precision: Some(1),
+            ..FormatOptions::default()
+        };
+        assert_eq!(options.temperature(23.0), "23.00");
+        assert_eq!(options.wind(8.0), "8.0");
+        assert_eq!(options.pressure(1017.0), "1017");
+        assert_eq!(
+            options.celsius_fahrenheit(&Temperature::from_celsius(-3.5)),
+            "-3.50 °C (25.70 °F)"
+        );
+    }
+
+    #[test]
+    fn test_compact() {
+        let options = FormatOptions {
+            temperature_precision: Some(2),
+            ..FormatOptions::compact()
+        };
+        assert_eq!(options.temperature(73.4), "73");
+        assert_eq!(options.temperature(-0.4), "0");
+        assert_eq!(options.temperature(-0.6), "-1");
+        let wind = WindInfo {
+            knots: 8.0,
+            mph: 9.2,
+            ..WindInfo::default()
+        };
+        assert_eq!(options.wind_speed(&wind), "8 kt (9 mph)");
+        assert_eq!(FormatOptions::default().wind_speed(&wind), "8 kt (9.2 mph)");
+        assert_eq!(options.gust_speed(&wind), None);
+        let gusty = WindInfo {
+            gust_knots: Some(18.0),
+            gust_mph: Some(20.7),
+            ..wind
+        };
+        assert_eq!(options.gust_speed(&gusty), Some("18 kt (21 mph)".into()));
+        assert_eq!(options.compact_wind_speed(&gusty), "8G18");
+    }
+
+    #[test]
+    fn test_humanize_duration() {
+        let humanize = |secs| humanize_duration(Duration::from_secs(secs));
+        assert_eq!(humanize(59), "just now");
+        assert_eq!(humanize(60), "1 minute ago");
+        assert_eq!(humanize(23 * 60 + 10), "23 minutes ago");
+        assert_eq!(humanize(3600), "1 hour ago");
+        assert_eq!(humanize(47 * 3600), "47 hours ago");
+        assert_eq!(humanize(3 * 86400), "3 days ago");
+    }
+
+    #[test]
+    fn test_oneline() {
+        let (_, metar) =
+            crate::metar::parse_metar("KSFO 301356Z 22014KT 10SM OVC008 12/11 A3002").unwrap();
+        let info = metar.to_weather_info(2023, 12).unwrap();
+        let options = FormatOptions::compact();
+        assert_eq!(
+            options.oneline(&info, IconSet::Ascii, None),
+            "12°C ↗14kt 10mi OVC 1017hPa"
+        );
+        let now = "2023-12-30T14:21:00Z".parse().unwrap();
+        assert_eq!(
+            options.oneline(&info, IconSet::Ascii, Some(now)),
+            "12°C ↗14kt 10mi OVC 1017hPa (25m ago)"
+        );
+        assert_eq!(info.to_string(), "12°C ↗14kt 10mi OVC 1017hPa");
+        let trends = Trends {
+            temperature: Trend::Rising,
+            pressure: Trend::Steady,
+        };
+        assert_eq!(
+            options.oneline_with_trends(&info, IconSet::Ascii, None, Some(trends)),
+            "12°C↑ ↗14kt 10mi OVC 1017hPa→"
+        );
+    }
+
+    #[test]
+    fn test_wttr() {
+        let (_, metar) =
+            crate::metar::parse_metar("EGLL 301350Z 22014KT 6000 -RA SCT008 12/11 Q1017").unwrap();
+        let info = metar.to_weather_info(2023, 12).unwrap();
+        let lines: Vec<String> = FormatOptions::default()
+            .wttr(&info)
+            .lines()
+            .map(|line| line.trim_end().to_owned())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "     .-.       Light rain",
+                "    (   ).     +12 °C",
+                "   (___(__)    ↗ 26 km/h",
+                "  ‚‘‚‘‚‘‚‘     6 km",
+                "  ‚’‚’‚’‚’     1017 hPa",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_value_formatter() {
+        struct Imperial;
+        impl ValueFormatter for Imperial {
+            fn format_temperature(&self, celsius: f64) -> String {
+                format!("{:.0}°F", celsius * 9.0 / 5.0 + 32.0)
+            }
+            fn format_speed(&self, knots: f64) -> String {
+                format!("{:.0}mph", knots * 1.150779)
+            }
+            fn format_pressure(&self, hectopascals: f64) -> String {
+                format!("{:.2}inHg", hectopascals * 0.02953)
+            }
+            fn format_distance(&self, meters: f64) -> String {
+                format!("{:.1}mi", meters / 1609.344)
+            }
+        }
+        let (_, metar) =
+            crate::metar::parse_metar("EGLL 301350Z 22014G23KT 6000 -RA SCT008 12/11 Q1017")
+                .unwrap();
+        let info = metar.to_weather_info(2023, 12).unwrap();
+        assert_eq!(
+            oneline_with(&Imperial, &info, IconSet::Ascii),
+            "54°F ↗16mph G26mph 3.7mi RA 30.03inHg"
+        );
+        assert_eq!(
+            oneline_with(&FormatOptions::default(), &info, IconSet::Ascii),
+            FormatOptions::default().oneline(&info, IconSet::Ascii, None)
+        );
+    }
+
+    #[test]
+    fn test_banner() {
+        let (_, metar) =
+            crate::metar::parse_metar("EGLL 301350Z 22014KT 6000 -RA SCT008 12/11 Q1017").unwrap();
+        let info = metar.to_weather_info(2023, 12).unwrap();
+        let banner = FormatOptions::default().banner(&info);
+        let lines: Vec<&str> = banner.lines().collect();
+        assert_eq!(lines[0], "     .-.         #    ###   #   ####");
+        assert_eq!(lines[4], "  ‚’‚’‚’‚’      ###  #####      ####");
+        assert_eq!(lines[6], "Light rain, ↗ 14 kt, 1017 hPa");
+    }
+}
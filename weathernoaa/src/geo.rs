@@ -0,0 +1,160 @@
+//! Geodesy helpers for working with station coordinates.
+
+use crate::directory::StationInfo;
+
+/// Mean radius of the Earth in kilometers
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Kilometers in a nautical mile
+pub const KM_PER_NAUTICAL_MILE: f64 = 1.852;
+
+/// A point on Earth in decimal degrees.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    /// Latitude, positive north of the equator
+    pub latitude: f64,
+    /// Longitude, positive east of Greenwich
+    pub longitude: f64,
+}
+
+impl Point {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Point {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// Great-circle distance to another point in kilometers, using the
+    /// [haversine formula](https://en.wikipedia.org/wiki/Haversine_formula).
+    pub fn distance_km(&self, to: &Point) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), to.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (to.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+
+    /// Initial bearing of the great-circle path to another point, in
+    /// degrees clockwise from north in the range `0..360`.
+    pub fn bearing(&self, to: &Point) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), to.latitude.to_radians());
+        let dlon = (to.longitude - self.longitude).to_radians();
+        let y = dlon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+        (y.atan2(x).to_degrees() + 360.0) % 360.0
+    }
+
+    /// Distance in kilometers from the great circle going through
+    /// `start` and `end`, positive when right of it going towards `end`.
+    pub fn cross_track_km(&self, start: &Point, end: &Point) -> f64 {
+        let angular = start.distance_km(self) / EARTH_RADIUS_KM;
+        let bearings = (start.bearing(self) - start.bearing(end)).to_radians();
+        (angular.sin() * bearings.sin()).asin() * EARTH_RADIUS_KM
+    }
+
+    /// Distance in kilometers from `start` to the point of the great
+    /// circle going through `start` and `end` which is the closest to
+    /// this point. Negative when that point is behind `start`.
+    pub fn along_track_km(&self, start: &Point, end: &Point) -> f64 {
+        let angular = start.distance_km(self) / EARTH_RADIUS_KM;
+        let cross = self.cross_track_km(start, end) / EARTH_RADIUS_KM;
+        let along = (angular.cos() / cross.cos()).clamp(-1.0, 1.0).acos() * EARTH_RADIUS_KM;
+        let bearings = (start.bearing(self) - start.bearing(end)).to_radians();
+        if bearings.cos() < 0.0 {
+            -along
+        } else {
+            along
+        }
+    }
+}
+
+impl StationInfo {
+    /// Location of the station
+    pub fn point(&self) -> Point {
+        Point::new(self.latitude, self.longitude)
+    }
+}
+
+/// Cardinal direction of a bearing in degrees, using the 16 points of
+/// the compass rose. Eg: 315.0 gives "NW"
+pub fn cardinal_direction(bearing: f64) -> &'static str {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+    let index = (bearing.rem_euclid(360.0) / 22.5).round() as usize;
+    POINTS[index % 16]
+}
+
+/// Arrow pointing where a wind blowing from `azimuth` degrees goes, using
+/// the 8 principal directions. Eg: 225.0 (a south westerly) gives '↗'
+pub fn wind_arrow(azimuth: f64) -> char {
+    const ARROWS: [char; 8] = ['↓', '↙', '←', '↖', '↑', '↗', '→', '↘'];
+    let index = (azimuth.rem_euclid(360.0) / 45.0).round() as usize;
+    ARROWS[index % 8]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONDON: Point = Point {
+        latitude: 51.5074,
+        longitude: -0.1278,
+    };
+    const PARIS: Point = Point {
+        latitude: 48.8566,
+        longitude: 2.3522,
+    };
+
+    #[test]
+    fn test_distance() {
+        assert!((LONDON.distance_km(&PARIS) - 343.5).abs() < 1.0);
+        assert_eq!(LONDON.distance_km(&LONDON), 0.0);
+        let antipode = Point::new(-51.5074, 179.8722);
+        assert!(
+            (LONDON.distance_km(&antipode) - EARTH_RADIUS_KM * std::f64::consts::PI).abs() < 1.0
+        );
+    }
+
+    #[test]
+    fn test_bearing() {
+        assert!((LONDON.bearing(&PARIS) - 148.1).abs() < 0.5);
+        assert!((PARIS.bearing(&LONDON) - 330.2).abs() < 0.5);
+        assert_eq!(Point::new(0.0, 0.0).bearing(&Point::new(0.0, -10.0)), 270.0);
+    }
+
+    #[test]
+    fn test_track_distances() {
+        let (start, end) = (Point::new(0.0, 0.0), Point::new(0.0, 10.0));
+        let north = Point::new(1.0, 5.0);
+        let degree_km = EARTH_RADIUS_KM * 1f64.to_radians();
+        assert!((north.cross_track_km(&start, &end) + degree_km).abs() < 0.1);
+        assert!((north.along_track_km(&start, &end) - 5.0 * degree_km).abs() < 1.0);
+        let behind = Point::new(-1.0, -2.0);
+        assert!(behind.cross_track_km(&start, &end) > 0.0);
+        assert!((behind.along_track_km(&start, &end) + 2.0 * degree_km).abs() < 1.0);
+        // Going from Le Havre to Paris, London is on the left
+        let le_havre = Point::new(49.4944, 0.1079);
+        assert!(LONDON.cross_track_km(&le_havre, &PARIS) < 0.0);
+    }
+
+    #[test]
+    fn test_cardinal_direction() {
+        assert_eq!(cardinal_direction(0.0), "N");
+        assert_eq!(cardinal_direction(148.1), "SSE");
+        assert_eq!(cardinal_direction(315.0), "NW");
+        assert_eq!(cardinal_direction(355.0), "N");
+        assert_eq!(cardinal_direction(-90.0), "W");
+    }
+
+    #[test]
+    fn test_wind_arrow() {
+        assert_eq!(wind_arrow(0.0), '↓');
+        assert_eq!(wind_arrow(80.0), '←');
+        assert_eq!(wind_arrow(225.0), '↗');
+        assert_eq!(wind_arrow(350.0), '↓');
+    }
+}
@@ -0,0 +1,106 @@
+//! Observations as [GeoJSON](https://datatracker.ietf.org/doc/html/rfc7946),
+//! for dropping them onto maps, eg: with Leaflet or MapLibre, or into GIS
+//! tools.
+//!
+//! Each observation is a `Feature` whose geometry is the `Point` of its
+//! station and whose properties are its values:
+//!
+//! ```json
+//! {
+//!   "type": "Feature",
+//!   "geometry": { "type": "Point", "coordinates": [120.33, 36.07] },
+//!   "properties": { "station": "ZSQD", "temperature_c": 12.0, ... }
+//! }
+//! ```
+
+use crate::weather::WeatherInfo;
+use serde_json::{json, Value};
+
+/// Feature of the observation of a station. The geometry is `null` when
+/// the location of the station isn't known.
+pub fn to_feature(station: &str, info: &WeatherInfo) -> Value {
+    let place = info.station.as_ref();
+    let geometry = match place.and_then(|place| place.location) {
+        // GeoJSON positions are longitude first
+        Some(point) => json!({
+            "type": "Point",
+            "coordinates": [point.longitude, point.latitude],
+        }),
+        None => Value::Null,
+    };
+    json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": {
+            "station": station,
+            "place": place.map(|place| &place.place),
+            "country": place.map(|place| &place.country),
+            "time": info.weather_time.to_datetime().map(|time| time.to_rfc3339()),
+            "temperature_c": info.temperature.celsius,
+            "dewpoint_c": info.dewpoint.celsius,
+            "relative_humidity": info.relative_humidity,
+            "wind_cardinal": info.wind.cardinal,
+            "wind_azimuth": info.wind.azimuth,
+            "wind_knots": info.wind.knots,
+            "wind_gust_knots": info.wind.gust_knots,
+            "visibility": info.visibility,
+            "pressure_hpa": info.pressure,
+            "sky_condition": info.sky_condition,
+            "weather": info.weather,
+            "condition": info.condition(),
+        },
+    })
+}
+
+/// `FeatureCollection` of the observations of stations, in the order
+/// given.
+pub fn to_feature_collection(observations: &[(String, WeatherInfo)]) -> Value {
+    let features: Vec<Value> = observations
+        .iter()
+        .map(|(station, info)| to_feature(station, info))
+        .collect();
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::Point;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+    use crate::weather::Station;
+
+    #[test]
+    fn test_feature_collection() {
+        let (_, vogo) = parse_weather(&vogo_report("1230")).unwrap();
+        let mut located = vogo.clone();
+        located.station = Some(Station {
+            place: "Goa".into(),
+            country: "India".into(),
+            location: Some(Point::new(15.38, 73.83)),
+        });
+        let collection =
+            to_feature_collection(&[("VOGO".into(), vogo.clone()), ("VOGO".into(), located)]);
+        assert_eq!(collection["type"], "FeatureCollection");
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features[0]["geometry"], Value::Null);
+        assert_eq!(features[0]["properties"]["station"], "VOGO");
+        assert_eq!(
+            features[0]["properties"]["temperature_c"],
+            vogo.temperature.celsius
+        );
+        assert_eq!(features[0]["properties"]["condition"], "Haze");
+        assert_eq!(
+            features[0]["properties"]["time"],
+            "2023-12-30T12:30:00+00:00"
+        );
+        assert_eq!(
+            features[1]["geometry"]["coordinates"],
+            json!([73.83, 15.38])
+        );
+        assert_eq!(features[1]["properties"]["place"], "Goa");
+    }
+}
@@ -0,0 +1,181 @@
+use crate::sink::Sink;
+use crate::weather::{NoaaApp, WeatherError, WeatherInfo};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+/// Number of observations kept for subscribers lagging behind. Slower
+/// subscribers miss the oldest ones, see [broadcast::error::RecvError].
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Result of polling a station, shared with every subscriber of a
+/// [WeatherHub].
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub station: String,
+    pub result: Arc<Result<WeatherInfo, WeatherError>>,
+}
+
+/// Polls a set of stations once and shares the observations with any
+/// number of subscribers, so that several components don't each poll
+/// NOAA for the same stations.
+///
+/// Stations are polled as by [NoaaApp::observe_many], from a task
+/// spawned on the current tokio runtime. The task stops when the hub is
+/// dropped, or when the app is
+/// [cancelled](crate::weather::NoaaApp::with_cancellation).
+pub struct WeatherHub {
+    sender: broadcast::Sender<Observation>,
+    latest: HashMap<String, watch::Receiver<Option<Observation>>>,
+    task: JoinHandle<()>,
+}
+
+impl WeatherHub {
+    /// Starts polling `station_codes` every `interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside of a tokio runtime.
+    pub fn spawn(app: NoaaApp, station_codes: Vec<String>, interval: Duration) -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let mut watchers = HashMap::new();
+        let mut latest = HashMap::new();
+        for code in &station_codes {
+            let (watcher, receiver) = watch::channel(None);
+            watchers.insert(code.clone(), watcher);
+            latest.insert(code.clone(), receiver);
+        }
+        let broadcaster = sender.clone();
+        let task = tokio::spawn(async move {
+            let mut feed = app.observe_many(&station_codes, interval);
+            while let Some((station, result)) = feed.next().await {
+                let observation = Observation {
+                    station,
+                    result: Arc::new(result),
+                };
+                if let Some(watcher) = watchers.get(&observation.station) {
+                    watcher.send_replace(Some(observation.clone()));
+                }
+                // Nobody listening isn't an error, subscribers may come later
+                let _ = broadcaster.send(observation);
+            }
+        });
+        WeatherHub {
+            sender,
+            latest,
+            task,
+        }
+    }
+
+    /// Receives every observation polled from now on, including errors.
+    pub fn subscribe(&self) -> broadcast::Receiver<Observation> {
+        self.sender.subscribe()
+    }
+
+    /// Watches the latest observation of a station, holding `None` until
+    /// the station is first polled. `None` when the station isn't polled
+    /// by the hub.
+    pub fn watch(&self, station_code: &str) -> Option<watch::Receiver<Option<Observation>>> {
+        self.latest.get(station_code).cloned()
+    }
+
+    /// Writes every successful observation polled from now on to `sink`,
+    /// from a blocking task, until the hub is dropped or writing fails.
+    /// Observations missed by lagging behind are skipped. Combine sinks
+    /// by passing a `Vec<Box<dyn Sink>>`.
+    pub fn spawn_sink<S: Sink + 'static>(&self, mut sink: S) -> JoinHandle<io::Result<()>> {
+        let mut receiver = self.subscribe();
+        tokio::task::spawn_blocking(move || loop {
+            match receiver.blocking_recv() {
+                Ok(observation) => {
+                    if let Ok(info) = observation.result.as_ref() {
+                        sink.write(&observation.station, info)?;
+                        sink.flush()?;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        })
+    }
+}
+
+impl Drop for WeatherHub {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::tests::{serve, vogo_report, Mirror};
+
+    #[test]
+    fn test_hub() {
+        let (port, server) = serve(vec![vogo_report("1230")]);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let hub = WeatherHub::spawn(app, vec!["VOGO".into()], Duration::from_secs(60));
+            let mut first = hub.subscribe();
+            let mut second = hub.subscribe();
+            let mut latest = hub.watch("VOGO").unwrap();
+            assert!(hub.watch("KSFO").is_none());
+
+            let observation = first.recv().await.unwrap();
+            assert_eq!(observation.station, "VOGO");
+            assert_eq!(observation.result.as_ref().as_ref().unwrap().pressure, 1010);
+            assert!(Arc::ptr_eq(
+                &observation.result,
+                &second.recv().await.unwrap().result
+            ));
+            latest.changed().await.unwrap();
+            let watched = latest.borrow().clone().unwrap();
+            assert!(Arc::ptr_eq(&observation.result, &watched.result));
+        });
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_spawn_sink() {
+        struct Stations(Arc<std::sync::Mutex<Vec<String>>>);
+
+        impl Sink for Stations {
+            fn write(&mut self, station: &str, _: &WeatherInfo) -> io::Result<()> {
+                self.0.lock().unwrap().push(station.to_owned());
+                Ok(())
+            }
+        }
+
+        let (port, server) = serve(vec![vogo_report("1230")]);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let written = Arc::default();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let hub = WeatherHub::spawn(app, vec!["VOGO".into()], Duration::from_secs(60));
+            let sinks: Vec<Box<dyn Sink>> = vec![Box::new(Stations(Arc::clone(&written)))];
+            let sink = hub.spawn_sink(sinks);
+            let mut latest = hub.watch("VOGO").unwrap();
+            latest.changed().await.unwrap();
+            drop(hub);
+            sink.await.unwrap().unwrap();
+        });
+        assert_eq!(*written.lock().unwrap(), vec!["VOGO".to_owned()]);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,366 @@
+use crate::astro::is_daytime;
+use crate::geo::Point;
+use crate::metar::{Descriptor, Intensity, Phenomenon, WeatherGroup};
+use crate::weather::WeatherInfo;
+
+/// Wind speed in knots from which an otherwise calm observation is
+/// considered windy.
+const WINDY_KNOTS: f64 = 25.0;
+
+/// Set of glyphs used to render a [Condition].
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IconSet {
+    /// Weather icons of [Nerd Fonts](https://www.nerdfonts.com/cheat-sheet)
+    NerdFont,
+    /// METAR like abbreviations, for terminals without a patched font.
+    /// Eg: OVC, RA
+    Ascii,
+}
+
+/// Overall condition of an observation, used for picking an icon. The
+/// variants are ordered by significance, so when several conditions
+/// are present the greatest one describes the observation.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Condition {
+    Unknown,
+    Clear,
+    PartlyCloudy,
+    MostlyCloudy,
+    Overcast,
+    Windy,
+    Haze,
+    Smoke,
+    Fog,
+    Dust,
+    VolcanicAsh,
+    Sandstorm,
+    Drizzle,
+    Rain,
+    Showers,
+    Sleet,
+    Snow,
+    Hail,
+    Thunderstorm,
+    Tornado,
+}
+
+impl Condition {
+    /// The glyph of the condition in the icon set, by day.
+    pub fn glyph(self, set: IconSet) -> &'static str {
+        self.glyph_for(set, true)
+    }
+
+    /// The glyph of the condition in the icon set, by day or by night.
+    /// Only clear and partly cloudy skies have night glyphs.
+    pub fn glyph_for(self, set: IconSet, daytime: bool) -> &'static str {
+        match (set, self) {
+            (IconSet::NerdFont, Condition::Clear) if !daytime => "\u{e32b}", // nf-weather-night_clear
+            (IconSet::NerdFont, Condition::PartlyCloudy) if !daytime => "\u{e37e}", // nf-weather-night_alt_cloudy
+            (IconSet::NerdFont, _) => self.nerd_font(),
+            (IconSet::Ascii, _) => self.ascii(),
+        }
+    }
+
+    fn nerd_font(self) -> &'static str {
+        match self {
+            Condition::Unknown => "\u{e374}",      // nf-weather-na
+            Condition::Clear => "\u{e30d}",        // nf-weather-day_sunny
+            Condition::PartlyCloudy => "\u{e302}", // nf-weather-day_cloudy
+            Condition::MostlyCloudy => "\u{e33d}", // nf-weather-cloud
+            Condition::Overcast => "\u{e312}",     // nf-weather-cloudy
+            Condition::Windy => "\u{e34b}",        // nf-weather-strong_wind
+            Condition::Haze => "\u{e3ae}",         // nf-weather-day_haze
+            Condition::Smoke => "\u{e35c}",        // nf-weather-smoke
+            Condition::Fog => "\u{e313}",          // nf-weather-fog
+            Condition::Dust => "\u{e35d}",         // nf-weather-dust
+            Condition::VolcanicAsh => "\u{e3c0}",  // nf-weather-volcano
+            Condition::Sandstorm => "\u{e37a}",    // nf-weather-sandstorm
+            Condition::Drizzle => "\u{e31b}",      // nf-weather-sprinkle
+            Condition::Rain => "\u{e318}",         // nf-weather-rain
+            Condition::Showers => "\u{e319}",      // nf-weather-showers
+            Condition::Sleet => "\u{e3ad}",        // nf-weather-sleet
+            Condition::Snow => "\u{e31a}",         // nf-weather-snow
+            Condition::Hail => "\u{e314}",         // nf-weather-hail
+            Condition::Thunderstorm => "\u{e31d}", // nf-weather-thunderstorm
+            Condition::Tornado => "\u{e351}",      // nf-weather-tornado
+        }
+    }
+
+    fn ascii(self) -> &'static str {
+        match self {
+            Condition::Unknown => "?",
+            Condition::Clear => "SKC",
+            Condition::PartlyCloudy => "SCT",
+            Condition::MostlyCloudy => "BKN",
+            Condition::Overcast => "OVC",
+            Condition::Windy => "WND",
+            Condition::Haze => "HZ",
+            Condition::Smoke => "FU",
+            Condition::Fog => "FG",
+            Condition::Dust => "DU",
+            Condition::VolcanicAsh => "VA",
+            Condition::Sandstorm => "SS",
+            Condition::Drizzle => "DZ",
+            Condition::Rain => "RA",
+            Condition::Showers => "SHRA",
+            Condition::Sleet => "PL",
+            Condition::Snow => "SN",
+            Condition::Hail => "GR",
+            Condition::Thunderstorm => "TS",
+            Condition::Tornado => "FC",
+        }
+    }
+
+    /// Name of the condition in the taxonomy shared by many weather icon
+    /// sets, such as Skycons and Meteocons. Eg: clear-night, rain, wind.
+    /// Clear and partly cloudy skies have a day and a night variant.
+    /// `None` for an unknown condition.
+    pub fn icon_name(self, daytime: bool) -> Option<&'static str> {
+        let name = match self {
+            Condition::Unknown => return None,
+            Condition::Clear if daytime => "clear-day",
+            Condition::Clear => "clear-night",
+            Condition::PartlyCloudy if daytime => "partly-cloudy-day",
+            Condition::PartlyCloudy => "partly-cloudy-night",
+            Condition::MostlyCloudy | Condition::Overcast => "cloudy",
+            Condition::Windy => "wind",
+            Condition::Haze
+            | Condition::Smoke
+            | Condition::Fog
+            | Condition::Dust
+            | Condition::VolcanicAsh
+            | Condition::Sandstorm => "fog",
+            Condition::Drizzle | Condition::Rain | Condition::Showers => "rain",
+            Condition::Sleet => "sleet",
+            Condition::Snow => "snow",
+            Condition::Hail => "hail",
+            Condition::Thunderstorm => "thunderstorm",
+            Condition::Tornado => "tornado",
+        };
+        Some(name)
+    }
+
+    /// The condition described by a weather group. Phenomena in the
+    /// vicinity of the station are ignored.
+    pub fn of_group(group: &WeatherGroup) -> Condition {
+        if group.intensity == Intensity::Vicinity {
+            return Condition::Unknown;
+        }
+        let condition = group
+            .phenomena
+            .iter()
+            .map(|phenomenon| Condition::of_phenomenon(*phenomenon))
+            .max()
+            .unwrap_or(Condition::Unknown);
+        match group.descriptor {
+            Some(Descriptor::Thunderstorm) => condition.max(Condition::Thunderstorm),
+            Some(Descriptor::Showers)
+                if matches!(
+                    condition,
+                    Condition::Unknown | Condition::Drizzle | Condition::Rain
+                ) =>
+            {
+                Condition::Showers
+            }
+            Some(Descriptor::Freezing)
+                if matches!(condition, Condition::Drizzle | Condition::Rain) =>
+            {
+                Condition::Sleet
+            }
+            _ => condition,
+        }
+    }
+
+    fn of_phenomenon(phenomenon: Phenomenon) -> Condition {
+        match phenomenon {
+            Phenomenon::Drizzle => Condition::Drizzle,
+            Phenomenon::Rain | Phenomenon::UnknownPrecipitation => Condition::Rain,
+            Phenomenon::Snow | Phenomenon::SnowGrains | Phenomenon::IceCrystals => Condition::Snow,
+            Phenomenon::IcePellets => Condition::Sleet,
+            Phenomenon::Hail | Phenomenon::SmallHail => Condition::Hail,
+            Phenomenon::Mist | Phenomenon::Fog => Condition::Fog,
+            Phenomenon::Smoke => Condition::Smoke,
+            Phenomenon::VolcanicAsh => Condition::VolcanicAsh,
+            Phenomenon::WidespreadDust | Phenomenon::Sand | Phenomenon::DustWhirls => {
+                Condition::Dust
+            }
+            Phenomenon::Haze | Phenomenon::Spray => Condition::Haze,
+            Phenomenon::Squalls => Condition::Windy,
+            Phenomenon::FunnelCloud => Condition::Tornado,
+            Phenomenon::Sandstorm | Phenomenon::Duststorm => Condition::Sandstorm,
+        }
+    }
+
+    /// The condition described by a sky condition in the wording of
+    /// NOAA's decoded files. Eg: partly cloudy
+    pub fn of_sky_condition(sky: &str) -> Condition {
+        match sky {
+            "clear" => Condition::Clear,
+            "mostly clear" | "partly cloudy" => Condition::PartlyCloudy,
+            "mostly cloudy" => Condition::MostlyCloudy,
+            "overcast" => Condition::Overcast,
+            "obscured" => Condition::Fog,
+            _ => Condition::Unknown,
+        }
+    }
+
+    /// The condition described by the weather of NOAA's decoded files,
+    /// which is only used when the METAR isn't available. Eg: light
+    /// rain, mist
+    fn of_weather_str(weather: &str) -> Condition {
+        const KEYWORDS: [(&str, Condition); 13] = [
+            ("funnel", Condition::Tornado),
+            ("thunder", Condition::Thunderstorm),
+            ("hail", Condition::Hail),
+            ("snow", Condition::Snow),
+            ("freezing", Condition::Sleet),
+            ("shower", Condition::Showers),
+            ("rain", Condition::Rain),
+            ("drizzle", Condition::Drizzle),
+            ("dust", Condition::Dust),
+            ("smoke", Condition::Smoke),
+            ("fog", Condition::Fog),
+            ("mist", Condition::Fog),
+            ("haze", Condition::Haze),
+        ];
+        let weather = weather.to_lowercase();
+        KEYWORDS
+            .iter()
+            .find(|(keyword, _)| weather.contains(keyword))
+            .map_or(Condition::Unknown, |(_, condition)| *condition)
+    }
+}
+
+impl WeatherInfo {
+    /// Overall condition of the observation, from the most significant
+    /// of its weather, sky condition and wind.
+    pub fn condition(&self) -> Condition {
+        let weather = match self.weather_groups() {
+            [] => self
+                .weather
+                .as_deref()
+                .map_or(Condition::Unknown, Condition::of_weather_str),
+            groups => groups
+                .iter()
+                .map(Condition::of_group)
+                .max()
+                .unwrap_or(Condition::Unknown),
+        };
+        let sky = self
+            .sky_condition
+            .as_deref()
+            .map_or(Condition::Unknown, Condition::of_sky_condition);
+        let wind = if self.wind.knots >= WINDY_KNOTS {
+            Condition::Windy
+        } else {
+            Condition::Unknown
+        };
+        weather.max(sky).max(wind)
+    }
+
+    /// Whether the sun was up at the station when the observation was
+    /// made. `None` when the station's location or the time isn't known.
+    pub fn is_daytime(&self) -> Option<bool> {
+        let location = self.station.as_ref()?.location?;
+        let time = self.weather_time.to_datetime()?;
+        Some(is_daytime(location, time))
+    }
+
+    /// The glyph of the observation's [condition](WeatherInfo::condition)
+    /// in the icon set, by night when [WeatherInfo::is_daytime] tells so.
+    pub fn glyph(&self, set: IconSet) -> &'static str {
+        let daytime = self.is_daytime().unwrap_or(true);
+        self.condition().glyph_for(set, daytime)
+    }
+
+    /// The [icon name](Condition::icon_name) of the observation's
+    /// condition, with day and night told apart from the position of the
+    /// sun at the station's `location` when the observation was made.
+    pub fn icon_name(&self, location: Point) -> Option<&'static str> {
+        let daytime = self
+            .weather_time
+            .to_datetime()
+            .map(|time| is_daytime(location, time))
+            .unwrap_or(true);
+        self.condition().icon_name(daytime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metar::parse_metar;
+    use crate::weather::Station;
+
+    fn groups(metar: &str) -> Vec<Condition> {
+        let (_, metar) = parse_metar(metar).unwrap();
+        metar.weather.iter().map(Condition::of_group).collect()
+    }
+
+    #[test]
+    fn test_of_group() {
+        assert_eq!(
+            groups("KSFO 301356Z 28012KT 2SM -SHRA TSRA FZDZ FZFG VCSH BKN008 12/11 A3002"),
+            vec![
+                Condition::Showers,
+                Condition::Thunderstorm,
+                Condition::Sleet,
+                Condition::Fog,
+                Condition::Unknown,
+            ]
+        );
+        assert_eq!(Condition::Thunderstorm.glyph(IconSet::Ascii), "TS");
+        assert_eq!(Condition::Rain.glyph(IconSet::NerdFont), "\u{e318}");
+    }
+
+    #[test]
+    fn test_condition() {
+        let (_, metar) = parse_metar("VOBL 301330Z 08008KT 9999 -RA SCT020 23/14 Q1017").unwrap();
+        let mut info = metar.to_weather_info(2023, 12).unwrap();
+        assert_eq!(info.condition(), Condition::Rain);
+        info.metar = None;
+        info.weather = None;
+        assert_eq!(info.condition(), Condition::PartlyCloudy);
+        info.weather = Some("light rain; mist".into());
+        assert_eq!(info.glyph(IconSet::Ascii), "RA");
+        info.weather = None;
+        info.wind.knots = 30.0;
+        assert_eq!(info.condition(), Condition::Windy);
+    }
+
+    #[test]
+    fn test_icon_name() {
+        let (_, metar) = parse_metar("VOBL 301330Z 08008KT 9999 SCT020 23/14 Q1017").unwrap();
+        let info = metar.to_weather_info(2023, 12).unwrap();
+        let bengaluru = Point::new(13.2, 77.7);
+        // 19:00 local time
+        assert_eq!(info.icon_name(bengaluru), Some("partly-cloudy-night"));
+        let london = Point::new(51.5, -0.1);
+        assert_eq!(info.icon_name(london), Some("partly-cloudy-day"));
+        assert_eq!(Condition::Sleet.icon_name(false), Some("sleet"));
+        assert_eq!(Condition::Unknown.icon_name(true), None);
+    }
+
+    #[test]
+    fn test_is_daytime() {
+        let (_, metar) = parse_metar("VOBL 301330Z 08008KT 9999 SCT020 23/14 Q1017").unwrap();
+        let mut info = metar.to_weather_info(2023, 12).unwrap();
+        assert_eq!(info.is_daytime(), None);
+        assert_eq!(info.glyph(IconSet::NerdFont), "\u{e302}");
+        info.station = Some(Station {
+            place: "Bangalore".into(),
+            country: "India".into(),
+            location: Some(Point::new(13.2, 77.7)),
+        });
+        // 19:00 local time
+        assert_eq!(info.is_daytime(), Some(false));
+        assert_eq!(info.glyph(IconSet::NerdFont), "\u{e37e}");
+        assert_eq!(info.glyph(IconSet::Ascii), "SCT");
+        assert_eq!(
+            Condition::Clear.glyph_for(IconSet::NerdFont, false),
+            "\u{e32b}"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Reading the CSV archives of METARs of the [Iowa Environmental
+//! Mesonet](https://mesonet.agron.iastate.edu/request/download.phtml),
+//! which go back decades, for long-range statistics.
+//!
+//! The archives start with a header row naming the columns, of which
+//! `valid`, the time of the observation in UTC, and `metar` are read:
+//!
+//! ```text
+//! station,valid,tmpf,dwpf,metar
+//! SFO,2023-12-30 13:56,53.60,51.80,KSFO 301356Z 22014KT 10SM OVC008 12/11 A3002
+//! ```
+
+use crate::metar::parse_metar;
+use crate::weather::{Origin, WeatherInfo};
+use std::io::{self, BufRead};
+
+/// Reads the observations of an archive, in order. Rows whose METAR is
+/// missing or can't be parsed are skipped, as are comment lines starting
+/// with `#`. The station of an observation is the one of its METAR.
+pub fn read_iem_csv<R: BufRead>(reader: R) -> io::Result<Vec<WeatherInfo>> {
+    let mut lines = reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.starts_with('#') || line.is_empty()));
+    let header = lines
+        .next()
+        .ok_or_else(|| invalid("The archive is empty"))??;
+    let columns = split_row(&header);
+    let column = |name: &str| {
+        columns
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| invalid(&format!("The archive has no {} column", name)))
+    };
+    let (valid, metar) = (column("valid")?, column("metar")?);
+    let mut observations = vec![];
+    for line in lines {
+        let row = split_row(&line?);
+        let (Some(valid), Some(metar)) = (row.get(valid), row.get(metar)) else {
+            continue;
+        };
+        if let Some(info) = parse_row(valid, metar) {
+            observations.push(info);
+        }
+    }
+    Ok(observations)
+}
+
+/// Observation of a row, the year and month of its METAR being those of
+/// its time. Eg: 2023-12-30 13:56
+fn parse_row(valid: &str, metar: &str) -> Option<WeatherInfo> {
+    let year = valid.get(..4)?.parse().ok()?;
+    let month = valid.get(5..7)?.parse().ok()?;
+    let (_, metar) = parse_metar(metar).ok()?;
+    let mut info = metar.to_weather_info(year, month)?;
+    info.provenance.origin = Origin::Import;
+    Some(info)
+}
+
+/// Fields of a CSV row, which may be quoted.
+fn split_row(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_iem_csv() {
+        let archive = "#DEBUG: Format Typ    -> comma
+station,valid,tmpf,metar
+SFO,2023-12-30 13:56,53.60,KSFO 301356Z 22014KT 10SM OVC008 12/11 A3002
+SFO,2023-12-30 14:56,M,M
+SFO,2024-01-01 00:56,50.00,\"KSFO 010056Z 22014KT 10SM OVC008 10/09 A3002 RMK AO2 T01000089\"
+";
+        let observations = read_iem_csv(archive.as_bytes()).unwrap();
+        assert_eq!(observations.len(), 2);
+        assert_eq!(observations[0].weather_time.year, 2023);
+        assert_eq!(observations[1].weather_time.month, 1);
+        assert_eq!(observations[1].dewpoint.celsius, 8.9);
+        assert_eq!(observations[1].provenance.origin, Origin::Import);
+        assert_eq!(observations[1].metar.as_ref().unwrap().station, "KSFO");
+        assert!(read_iem_csv("station,valid\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Observations as [KML](https://developers.google.com/kml/documentation),
+//! for overlaying them in Google Earth, eg: for flight planning or storm
+//! chasing.
+//!
+//! Each observation is a placemark at its station, with an icon of its
+//! condition and a balloon summarizing it.
+
+use crate::format::FormatOptions;
+use crate::icon::Condition;
+use crate::weather::WeatherInfo;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Base URL of the icons bundled with Google Earth
+const ICON_BASE_URL: &str = "https://maps.google.com/mapfiles/kml/shapes";
+
+/// Name of the Google Earth icon of a condition, which is also the id of
+/// its style in the document.
+fn icon(condition: Condition) -> &'static str {
+    match condition {
+        Condition::Clear => "sunny",
+        Condition::PartlyCloudy | Condition::MostlyCloudy | Condition::Overcast => "partly_cloudy",
+        Condition::Drizzle | Condition::Rain | Condition::Showers | Condition::Sleet => "rainy",
+        Condition::Snow | Condition::Hail => "snowflake_simple",
+        Condition::Thunderstorm | Condition::Tornado => "thunderstorm",
+        Condition::VolcanicAsh => "volcano",
+        Condition::Unknown
+        | Condition::Windy
+        | Condition::Haze
+        | Condition::Smoke
+        | Condition::Fog
+        | Condition::Dust
+        | Condition::Sandstorm => "shaded_dot",
+    }
+}
+
+/// KML document with a placemark per observation, formatting the values
+/// of the balloons with `options`. Stations whose location isn't known
+/// have a placemark without a point, listed but not shown on the map.
+pub fn to_kml(observations: &[(String, WeatherInfo)], options: &FormatOptions) -> String {
+    let icons: BTreeSet<&str> = observations
+        .iter()
+        .map(|(_, info)| icon(info.condition()))
+        .collect();
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n");
+    for icon in icons {
+        let _ = writeln!(
+            out,
+            "<Style id=\"{icon}\"><IconStyle><Icon><href>{}/{icon}.png</href></Icon></IconStyle></Style>",
+            ICON_BASE_URL,
+            icon = icon
+        );
+    }
+    for (station, info) in observations {
+        out.push_str("<Placemark>\n");
+        let name = match &info.station {
+            Some(place) => format!("{} ({})", station, place.place),
+            None => station.clone(),
+        };
+        let _ = writeln!(out, "<name>{}</name>", escape(&name));
+        let _ = writeln!(out, "<styleUrl>#{}</styleUrl>", icon(info.condition()));
+        let _ = writeln!(
+            out,
+            "<description><![CDATA[{}]]></description>",
+            balloon(info, options).replace("]]>", "]]]]><![CDATA[>")
+        );
+        if let Some(time) = info.weather_time.to_datetime() {
+            let _ = writeln!(
+                out,
+                "<TimeStamp><when>{}</when></TimeStamp>",
+                time.to_rfc3339()
+            );
+        }
+        if let Some(point) = info.station.as_ref().and_then(|place| place.location) {
+            let _ = writeln!(
+                out,
+                "<Point><coordinates>{},{}</coordinates></Point>",
+                point.longitude, point.latitude
+            );
+        }
+        out.push_str("</Placemark>\n");
+    }
+    out.push_str("</Document>\n</kml>\n");
+    out
+}
+
+/// HTML table of the values of an observation.
+fn balloon(info: &WeatherInfo, options: &FormatOptions) -> String {
+    let mut rows = vec![
+        ("Time", info.weather_time.time.clone()),
+        ("Temperature", options.celsius_fahrenheit(&info.temperature)),
+        ("Dew point", options.celsius_fahrenheit(&info.dewpoint)),
+        ("Humidity", format!("{}%", info.relative_humidity)),
+        (
+            "Wind",
+            format!("{} {}", info.wind.cardinal, options.wind_speed(&info.wind)),
+        ),
+        (
+            "Visibility",
+            info.visibility.trim_end_matches(":0").to_owned(),
+        ),
+        (
+            "Pressure",
+            format!("{} hPa", options.pressure(f64::from(info.pressure))),
+        ),
+    ];
+    if let Some(gust) = options.gust_speed(&info.wind) {
+        rows.insert(5, ("Gusts", gust));
+    }
+    rows.extend(info.sky_condition.clone().map(|sky| ("Sky", sky)));
+    rows.extend(info.weather.clone().map(|weather| ("Weather", weather)));
+    let mut out = String::from("<table>");
+    for (label, value) in rows {
+        let _ = write!(
+            out,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            label,
+            escape(&value)
+        );
+    }
+    out.push_str("</table>");
+    out
+}
+
+/// Escapes the characters with a meaning in XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::Point;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+    use crate::weather::Station;
+
+    #[test]
+    fn test_to_kml() {
+        let (_, vogo) = parse_weather(&vogo_report("1230")).unwrap();
+        let mut located = vogo.clone();
+        located.station = Some(Station {
+            place: "Goa <Dabolim>".into(),
+            country: "India".into(),
+            location: Some(Point::new(15.38, 73.83)),
+        });
+        let kml = to_kml(
+            &[("VOGO".into(), vogo), ("VOGO".into(), located)],
+            &FormatOptions::default(),
+        );
+        assert_eq!(kml.matches("<Placemark>").count(), 2);
+        assert_eq!(kml.matches("<Style ").count(), 1);
+        assert!(kml.contains("<styleUrl>#shaded_dot</styleUrl>"));
+        assert!(kml.contains("<name>VOGO (Goa &lt;Dabolim&gt;)</name>"));
+        assert!(kml.contains("<Point><coordinates>73.83,15.38</coordinates></Point>"));
+        assert!(kml.contains("<tr><th>Temperature</th><td>21 °C (69 °F)</td></tr>"));
+        assert!(kml.contains("<when>2023-12-30T12:30:00+00:00</when>"));
+    }
+}
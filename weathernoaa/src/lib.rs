@@ -1 +1,35 @@
+pub mod alert;
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod astro;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod change;
+pub mod country;
+pub mod directory;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod geo;
+#[cfg(feature = "serde")]
+pub mod geojson;
+pub mod hub;
+pub mod icon;
+pub mod iem;
+pub mod kml;
+pub mod metar;
+pub mod middleware;
+pub mod normals;
+pub mod region;
+pub mod reliability;
+#[cfg(feature = "replay")]
+pub mod replay;
+pub mod retry;
+pub mod schedule;
+pub mod severity;
+pub mod sink;
+pub mod statistics;
+pub mod validation;
 pub mod weather;
+pub mod windrose;
+pub mod wmo;
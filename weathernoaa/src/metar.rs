@@ -0,0 +1,1249 @@
+use crate::geo::cardinal_direction;
+use crate::weather::{Origin, Provenance, Source, Temperature, WeatherInfo, WeatherTime, WindInfo};
+use chrono::{DateTime, Datelike, Utc};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_till, take_while_m_n};
+use nom::character::complete::{char, digit1, newline};
+use nom::combinator::{all_consuming, map, map_res, opt, value, verify};
+use nom::error::{Error, ErrorKind};
+use nom::sequence::tuple;
+use nom::IResult;
+use std::fmt;
+
+// The format is described in the Federal Meteorological Handbook No. 1,
+// chapter 12: https://www.icams-portal.gov/resources/ofcm/fmh/FMH1/fmh1_2019.pdf
+
+/// Miles per hour in a knot
+pub const MPH_PER_KNOT: f64 = 1.150_779;
+
+/// Kilometers per hour in a knot
+pub const KMH_PER_KNOT: f64 = 1.852;
+
+/// Unit of the wind speed reported in a METAR.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpeedUnit {
+    Knots,
+    MetersPerSecond,
+    KilometersPerHour,
+}
+
+/// Wind group of a METAR. Eg: 34006KT, VRB03KT, 28012G20KT
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetarWind {
+    /// Direction the wind is blowing from in degrees. `None` when the
+    /// direction is variable.
+    pub direction: Option<u16>,
+    /// Sustained wind speed
+    pub speed: u16,
+    /// Gust speed
+    pub gust: Option<u16>,
+    /// Unit of `speed` and `gust`
+    pub unit: SpeedUnit,
+    /// Range of directions in degrees the wind is varying in, from a
+    /// separate group. Eg: 180V240
+    pub variable_range: Option<(u16, u16)>,
+}
+
+impl MetarWind {
+    fn to_knots(&self, speed: u16) -> f64 {
+        let speed = f64::from(speed);
+        match self.unit {
+            SpeedUnit::Knots => speed,
+            SpeedUnit::MetersPerSecond => speed * 1.943_844,
+            SpeedUnit::KilometersPerHour => speed / 1.852,
+        }
+    }
+
+    /// Sustained wind speed in knots
+    pub fn speed_knots(&self) -> f64 {
+        self.to_knots(self.speed)
+    }
+
+    /// Gust speed in knots
+    pub fn gust_knots(&self) -> Option<f64> {
+        self.gust.map(|gust| self.to_knots(gust))
+    }
+}
+
+/// Prevailing visibility of a METAR.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetarVisibility {
+    /// Visibility in statute miles, as reported by US stations. Eg: 10SM, 1 1/2SM
+    StatuteMiles(f64),
+    /// Visibility in meters, as reported by international stations.
+    /// 9999 means 10 km or more. Eg: 6000
+    Meters(u32),
+}
+
+impl MetarVisibility {
+    /// Visibility converted to statute miles
+    pub fn miles(&self) -> f64 {
+        match *self {
+            MetarVisibility::StatuteMiles(miles) => miles,
+            MetarVisibility::Meters(meters) => f64::from(meters) / 1609.344,
+        }
+    }
+
+    /// Visibility in meters, exact for visibilities reported in meters
+    /// and converted from statute miles otherwise.
+    pub fn meters(&self) -> f64 {
+        match *self {
+            MetarVisibility::StatuteMiles(miles) => miles * 1609.344,
+            MetarVisibility::Meters(meters) => f64::from(meters),
+        }
+    }
+}
+
+/// Amount of sky covered by a cloud layer.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CloudCover {
+    /// 1 to 2 oktas
+    Few,
+    /// 3 to 4 oktas
+    Scattered,
+    /// 5 to 7 oktas
+    Broken,
+    /// 8 oktas
+    Overcast,
+    /// Sky obscured, the height is the vertical visibility
+    VerticalVisibility,
+}
+
+impl CloudCover {
+    /// Whether a layer of this cover forms a ceiling: broken, overcast
+    /// or an obscured sky.
+    pub fn is_ceiling(self) -> bool {
+        self >= CloudCover::Broken
+    }
+}
+
+/// Convective cloud type reported with a cloud layer.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CloudType {
+    Cumulonimbus,
+    ToweringCumulus,
+}
+
+/// A single cloud layer. Eg: BKN025, FEW030CB
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CloudLayer {
+    pub cover: CloudCover,
+    /// Height of the base above ground level in feet. `None` when the
+    /// station couldn't measure it.
+    pub base_ft: Option<u32>,
+    pub cloud_type: Option<CloudType>,
+}
+
+/// Intensity or proximity qualifier of a weather group.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Intensity {
+    Light,
+    Moderate,
+    Heavy,
+    /// Phenomenon observed in the vicinity of the station
+    Vicinity,
+}
+
+/// Descriptor of a weather group.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Descriptor {
+    Shallow,
+    Partial,
+    Patches,
+    LowDrifting,
+    Blowing,
+    Showers,
+    Thunderstorm,
+    Freezing,
+}
+
+/// Weather phenomenon of a weather group.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phenomenon {
+    Drizzle,
+    Rain,
+    Snow,
+    SnowGrains,
+    IceCrystals,
+    IcePellets,
+    Hail,
+    SmallHail,
+    UnknownPrecipitation,
+    Mist,
+    Fog,
+    Smoke,
+    VolcanicAsh,
+    WidespreadDust,
+    Sand,
+    Haze,
+    Spray,
+    DustWhirls,
+    Squalls,
+    FunnelCloud,
+    Sandstorm,
+    Duststorm,
+}
+
+/// Present weather group. Eg: -RA, +TSRA, VCSH, BR
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeatherGroup {
+    pub intensity: Intensity,
+    pub descriptor: Option<Descriptor>,
+    pub phenomena: Vec<Phenomenon>,
+}
+
+/// Type of a report.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReportType {
+    /// Routine report, usually issued hourly
+    #[default]
+    Metar,
+    /// Special report, issued when the weather changes significantly
+    /// between routine reports
+    Speci,
+}
+
+/// Modifier of a report.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReportModifier {
+    /// Fully automated report, without human intervention
+    Auto,
+    /// Correction of a previously issued report
+    Corrected,
+}
+
+/// Kind of a trend group.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrendKind {
+    /// `BECMG`: the conditions are expected to change lastingly
+    Becoming,
+    /// `TEMPO`: the conditions are expected to change temporarily,
+    /// for less than an hour at a time
+    Temporary,
+    /// `NOSIG`: no significant change is expected
+    NoSignificantChange,
+}
+
+/// Trend forecast at the end of a METAR, for the two hours following
+/// the observation. International stations report those, unlike US
+/// ones. Eg: BECMG TL0930 3000 BR, TEMPO FM1000 TL1100 TSRA, NOSIG
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetarTrend {
+    pub kind: TrendKind,
+    /// Time the change starts at as hour and minute in UTC, from a `FM`
+    /// group
+    pub from: Option<(u8, u8)>,
+    /// Time the change ends by as hour and minute in UTC, from a `TL`
+    /// group
+    pub until: Option<(u8, u8)>,
+    /// Time the change is expected at as hour and minute in UTC, from
+    /// an `AT` group
+    pub at: Option<(u8, u8)>,
+    pub wind: Option<MetarWind>,
+    pub visibility: Option<MetarVisibility>,
+    pub cavok: bool,
+    pub weather: Vec<WeatherGroup>,
+    /// `NSW`: the significant weather is expected to end
+    pub no_significant_weather: bool,
+    pub clouds: Vec<CloudLayer>,
+}
+
+impl MetarTrend {
+    fn new(kind: TrendKind) -> Self {
+        MetarTrend {
+            kind,
+            from: None,
+            until: None,
+            at: None,
+            wind: None,
+            visibility: None,
+            cavok: false,
+            weather: vec![],
+            no_significant_weather: false,
+            clouds: vec![],
+        }
+    }
+
+    /// Decodes a group of the trend, ignoring those it doesn't
+    /// understand.
+    fn push_group(&mut self, token: &str) {
+        if let Some((prefix, hour, minute)) = is_group(trend_time, token) {
+            match prefix {
+                "FM" => self.from = Some((hour, minute)),
+                "TL" => self.until = Some((hour, minute)),
+                _ => self.at = Some((hour, minute)),
+            }
+        } else if let Some(wind) = is_group(wind_group, token) {
+            self.wind = Some(wind);
+        } else if token == "CAVOK" {
+            self.cavok = true;
+        } else if token == "NSW" {
+            self.no_significant_weather = true;
+        } else if let Some(meters) = is_group(metric_visibility, token) {
+            self.visibility = Some(MetarVisibility::Meters(meters));
+        } else if let Some(miles) = is_group(statute_miles, token) {
+            self.visibility = Some(MetarVisibility::StatuteMiles(miles));
+        } else if let Some(group) = is_group(weather_group, token) {
+            self.weather.push(group);
+        } else if let Some(layer) = is_group(cloud_layer, token) {
+            self.clouds.push(layer);
+        }
+    }
+}
+
+fn trend_kind(token: &str) -> Option<TrendKind> {
+    match token {
+        "BECMG" => Some(TrendKind::Becoming),
+        "TEMPO" => Some(TrendKind::Temporary),
+        "NOSIG" => Some(TrendKind::NoSignificantChange),
+        _ => None,
+    }
+}
+
+fn trend_time(i: &str) -> IResult<&str, (&str, u8, u8)> {
+    tuple((
+        alt((tag("FM"), tag("TL"), tag("AT"))),
+        verify(number(2), |hour: &u8| *hour <= 24),
+        verify(number(2), |minute: &u8| *minute < 60),
+    ))(i)
+}
+
+/// A METAR report, as found in the raw station files and on the `ob:`
+/// line of the decoded files.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawMetar {
+    /// Whether the report is routine or special. Reports without a
+    /// type, like the ones of NOAA's files, are routine.
+    pub report_type: ReportType,
+    /// ICAO code of the station
+    pub station: String,
+    /// Day of the month of the observation
+    pub day: u8,
+    /// Hour of the observation in UTC
+    pub hour: u8,
+    /// Minute of the observation
+    pub minute: u8,
+    pub modifier: Option<ReportModifier>,
+    pub wind: Option<MetarWind>,
+    pub visibility: Option<MetarVisibility>,
+    /// Ceiling And Visibility OK: visibility of 10 km or more, no
+    /// significant clouds and no significant weather.
+    pub cavok: bool,
+    pub weather: Vec<WeatherGroup>,
+    pub clouds: Vec<CloudLayer>,
+    /// Temperature in celsius
+    pub temperature: Option<f64>,
+    /// Dewpoint in celsius
+    pub dewpoint: Option<f64>,
+    /// Altimeter setting in Hectopascal, converted from inches of
+    /// mercury for US stations.
+    pub altimeter: Option<f64>,
+    /// Everything following `RMK`
+    pub remarks: Option<String>,
+    /// Trend forecasts, in the order reported
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub trends: Vec<MetarTrend>,
+}
+
+fn number<T: std::str::FromStr>(len: usize) -> impl Fn(&str) -> IResult<&str, T> {
+    move |i| {
+        map_res(
+            take_while_m_n(len, len, |c: char| c.is_ascii_digit()),
+            |s: &str| s.parse::<T>(),
+        )(i)
+    }
+}
+
+fn speed_unit(i: &str) -> IResult<&str, SpeedUnit> {
+    alt((
+        value(SpeedUnit::Knots, tag("KT")),
+        value(SpeedUnit::MetersPerSecond, tag("MPS")),
+        value(SpeedUnit::KilometersPerHour, tag("KMH")),
+    ))(i)
+}
+
+fn wind_group(i: &str) -> IResult<&str, MetarWind> {
+    let (i, direction) = alt((value(None, tag("VRB")), map(number(3), Some)))(i)?;
+    let (i, speed) = alt((number(3), number(2)))(i)?;
+    let (i, gust) = opt(|i| {
+        let (i, _) = char('G')(i)?;
+        alt((number(3), number(2)))(i)
+    })(i)?;
+    let (i, unit) = speed_unit(i)?;
+    Ok((
+        i,
+        MetarWind {
+            direction,
+            speed,
+            gust,
+            unit,
+            variable_range: None,
+        },
+    ))
+}
+
+fn variable_wind_group(i: &str) -> IResult<&str, (u16, u16)> {
+    let (i, (from, _, to)) = tuple((number(3), char('V'), number(3)))(i)?;
+    Ok((i, (from, to)))
+}
+
+fn fraction(i: &str) -> IResult<&str, f64> {
+    let (i, (numerator, _, denominator)) =
+        tuple((digit1, char('/'), verify(digit1, |d: &str| d != "0")))(i)?;
+    let numerator: f64 = numerator.parse().unwrap_or_default();
+    let denominator: f64 = denominator.parse().unwrap_or(1.0);
+    Ok((i, numerator / denominator))
+}
+
+fn statute_miles(i: &str) -> IResult<&str, f64> {
+    let (i, _) = opt(alt((char('M'), char('P'))))(i)?;
+    let (i, miles) = alt((fraction, map_res(digit1, |s: &str| s.parse::<f64>())))(i)?;
+    let (i, _) = tag("SM")(i)?;
+    Ok((i, miles))
+}
+
+fn metric_visibility(i: &str) -> IResult<&str, u32> {
+    let (i, meters) = number(4)(i)?;
+    // Visibility might be followed by a direction, eg: 4000NE
+    let (i, _) = take_while_m_n(0, 2, |c: char| "NSEW".contains(c))(i)?;
+    let (i, _) = opt(tag("NDV"))(i)?;
+    Ok((i, meters))
+}
+
+fn intensity(i: &str) -> IResult<&str, Intensity> {
+    let (i, intensity) = opt(alt((
+        value(Intensity::Light, char('-')),
+        value(Intensity::Heavy, char('+')),
+        value(Intensity::Vicinity, tag("VC")),
+    )))(i)?;
+    Ok((i, intensity.unwrap_or(Intensity::Moderate)))
+}
+
+fn descriptor(i: &str) -> IResult<&str, Descriptor> {
+    alt((
+        value(Descriptor::Shallow, tag("MI")),
+        value(Descriptor::Partial, tag("PR")),
+        value(Descriptor::Patches, tag("BC")),
+        value(Descriptor::LowDrifting, tag("DR")),
+        value(Descriptor::Blowing, tag("BL")),
+        value(Descriptor::Showers, tag("SH")),
+        value(Descriptor::Thunderstorm, tag("TS")),
+        value(Descriptor::Freezing, tag("FZ")),
+    ))(i)
+}
+
+fn phenomenon(i: &str) -> IResult<&str, Phenomenon> {
+    alt((
+        alt((
+            value(Phenomenon::Drizzle, tag("DZ")),
+            value(Phenomenon::Rain, tag("RA")),
+            value(Phenomenon::Snow, tag("SN")),
+            value(Phenomenon::SnowGrains, tag("SG")),
+            value(Phenomenon::IceCrystals, tag("IC")),
+            value(Phenomenon::IcePellets, tag("PL")),
+            value(Phenomenon::Hail, tag("GR")),
+            value(Phenomenon::SmallHail, tag("GS")),
+            value(Phenomenon::UnknownPrecipitation, tag("UP")),
+            value(Phenomenon::Mist, tag("BR")),
+            value(Phenomenon::Fog, tag("FG")),
+        )),
+        alt((
+            value(Phenomenon::Smoke, tag("FU")),
+            value(Phenomenon::VolcanicAsh, tag("VA")),
+            value(Phenomenon::WidespreadDust, tag("DU")),
+            value(Phenomenon::Sand, tag("SA")),
+            value(Phenomenon::Haze, tag("HZ")),
+            value(Phenomenon::Spray, tag("PY")),
+            value(Phenomenon::DustWhirls, tag("PO")),
+            value(Phenomenon::Squalls, tag("SQ")),
+            value(Phenomenon::FunnelCloud, tag("FC")),
+            value(Phenomenon::Sandstorm, tag("SS")),
+            value(Phenomenon::Duststorm, tag("DS")),
+        )),
+    ))(i)
+}
+
+fn weather_group(i: &str) -> IResult<&str, WeatherGroup> {
+    let (i, intensity) = intensity(i)?;
+    let (i, descriptor) = opt(descriptor)(i)?;
+    let (i, phenomena) = nom::multi::many0(phenomenon)(i)?;
+    // A descriptor can stand on its own (eg: TS, VCSH), a bare
+    // intensity can't.
+    if descriptor.is_none() && phenomena.is_empty() {
+        return Err(nom::Err::Error(Error::new(i, ErrorKind::Verify)));
+    }
+    Ok((
+        i,
+        WeatherGroup {
+            intensity,
+            descriptor,
+            phenomena,
+        },
+    ))
+}
+
+fn cloud_layer(i: &str) -> IResult<&str, CloudLayer> {
+    let (i, cover) = alt((
+        value(CloudCover::Few, tag("FEW")),
+        value(CloudCover::Scattered, tag("SCT")),
+        value(CloudCover::Broken, tag("BKN")),
+        value(CloudCover::Overcast, tag("OVC")),
+        value(CloudCover::VerticalVisibility, tag("VV")),
+    ))(i)?;
+    let (i, base) = alt((map(number::<u32>(3), Some), value(None, tag("///"))))(i)?;
+    let (i, cloud_type) = alt((
+        value(Some(CloudType::Cumulonimbus), tag("CB")),
+        value(Some(CloudType::ToweringCumulus), tag("TCU")),
+        value(None, tag("///")),
+        value(None, tag("")),
+    ))(i)?;
+    Ok((
+        i,
+        CloudLayer {
+            cover,
+            base_ft: base.map(|base| base * 100),
+            cloud_type,
+        },
+    ))
+}
+
+fn clear_sky(i: &str) -> IResult<&str, &str> {
+    alt((tag("SKC"), tag("CLR"), tag("NSC"), tag("NCD")))(i)
+}
+
+fn metar_temperature(i: &str) -> IResult<&str, Option<f64>> {
+    let (i, minus) = opt(char('M'))(i)?;
+    let (i, temperature) = opt(number::<u8>(2))(i)?;
+    Ok((
+        i,
+        temperature.map(|t| {
+            let t = f64::from(t);
+            if minus.is_some() {
+                -t
+            } else {
+                t
+            }
+        }),
+    ))
+}
+
+fn temperature_group(i: &str) -> IResult<&str, (Option<f64>, Option<f64>)> {
+    let (i, temperature) = metar_temperature(i)?;
+    let (i, _) = char('/')(i)?;
+    let (i, dewpoint) = metar_temperature(i)?;
+    Ok((i, (temperature, dewpoint)))
+}
+
+fn altimeter_group(i: &str) -> IResult<&str, f64> {
+    alt((
+        map(tuple((char('Q'), number::<u16>(4))), |(_, hpa)| {
+            f64::from(hpa)
+        }),
+        map(tuple((char('A'), number::<u16>(4))), |(_, inches)| {
+            f64::from(inches) / 100.0 * INHG_TO_HPA
+        }),
+    ))(i)
+}
+
+const INHG_TO_HPA: f64 = 33.863_886;
+
+fn is_group<'a, O, F>(parser: F, token: &'a str) -> Option<O>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    all_consuming(parser)(token).ok().map(|(_, o)| o)
+}
+
+/// Nom parser for a single METAR line. The parser is forgiving about
+/// groups it doesn't understand, but the station and time groups are
+/// required.
+pub fn parse_metar(i: &str) -> IResult<&str, RawMetar> {
+    let (rest, line) = take_till(|c| c == '\n')(i)?;
+    let line = line.trim_end_matches('=');
+    let mut tokens = line.split_whitespace().peekable();
+    let fail = || nom::Err::Error(Error::new(i, ErrorKind::Verify));
+
+    let mut report_type = ReportType::Metar;
+    let mut modifier = None;
+    while let Some(token) = tokens.peek() {
+        match *token {
+            "METAR" => report_type = ReportType::Metar,
+            "SPECI" => report_type = ReportType::Speci,
+            // Some stations put the correction ahead of the station
+            "COR" => modifier = Some(ReportModifier::Corrected),
+            _ => break,
+        }
+        tokens.next();
+    }
+    let station = tokens
+        .next()
+        .filter(|s| s.len() == 4 && s.chars().all(|c| c.is_ascii_alphanumeric()))
+        .ok_or_else(fail)?;
+    let (day, hour, minute) = tokens
+        .next()
+        .and_then(|t| is_group(tuple((number(2), number(2), number(2), char('Z'))), t))
+        .map(|(d, h, m, _)| (d, h, m))
+        .ok_or_else(fail)?;
+
+    while let Some(token) = tokens.peek() {
+        match *token {
+            "AUTO" => modifier = Some(ReportModifier::Auto),
+            "COR" => modifier = Some(ReportModifier::Corrected),
+            _ => break,
+        }
+        tokens.next();
+    }
+
+    let mut metar = RawMetar {
+        report_type,
+        station: station.to_owned(),
+        day,
+        hour,
+        minute,
+        modifier,
+        wind: None,
+        visibility: None,
+        cavok: false,
+        weather: vec![],
+        clouds: vec![],
+        temperature: None,
+        dewpoint: None,
+        altimeter: None,
+        remarks: None,
+        trends: vec![],
+    };
+
+    // Whole number part of visibilities like 1 1/2SM
+    let mut whole_miles: Option<f64> = None;
+    while let Some(token) = tokens.next() {
+        if token == "RMK" {
+            let remarks: Vec<&str> = tokens.by_ref().collect();
+            metar.remarks = Some(remarks.join(" "));
+            break;
+        }
+        // The groups following a trend belong to it
+        if let Some(kind) = trend_kind(token) {
+            metar.trends.push(MetarTrend::new(kind));
+            continue;
+        }
+        if let Some(trend) = metar.trends.last_mut() {
+            trend.push_group(token);
+            continue;
+        }
+        if let Some(whole) = whole_miles.take() {
+            if let Some(miles) = is_group(statute_miles, token) {
+                metar.visibility = Some(MetarVisibility::StatuteMiles(whole + miles));
+                continue;
+            }
+        }
+        if metar.wind.is_none() && metar.visibility.is_none() {
+            if let Some(wind) = is_group(wind_group, token) {
+                metar.wind = Some(wind);
+                continue;
+            }
+        }
+        if let Some(range) = is_group(variable_wind_group, token) {
+            if let Some(wind) = &mut metar.wind {
+                wind.variable_range = Some(range);
+            }
+            continue;
+        }
+        if metar.visibility.is_none() && !metar.cavok {
+            if token == "CAVOK" {
+                metar.cavok = true;
+                continue;
+            }
+            if let Some(miles) = is_group(statute_miles, token) {
+                metar.visibility = Some(MetarVisibility::StatuteMiles(miles));
+                continue;
+            }
+            if let Some(meters) = is_group(metric_visibility, token) {
+                metar.visibility = Some(MetarVisibility::Meters(meters));
+                continue;
+            }
+            if token.len() == 1 && token.chars().all(|c| c.is_ascii_digit()) {
+                whole_miles = token.parse().ok();
+                continue;
+            }
+        }
+        if let Some(group) = is_group(weather_group, token) {
+            metar.weather.push(group);
+            continue;
+        }
+        if let Some(layer) = is_group(cloud_layer, token) {
+            metar.clouds.push(layer);
+            continue;
+        }
+        if is_group(clear_sky, token).is_some() {
+            continue;
+        }
+        if let Some((temperature, dewpoint)) = is_group(temperature_group, token) {
+            metar.temperature = temperature;
+            metar.dewpoint = dewpoint;
+            continue;
+        }
+        if let Some(altimeter) = is_group(altimeter_group, token) {
+            metar.altimeter = Some(altimeter);
+            continue;
+        }
+        // Remaining groups (runway visual range, recent weather, wind
+        // shear etc.) aren't decoded.
+    }
+    Ok((rest, metar))
+}
+
+impl RawMetar {
+    /// Sky condition in the same wording as NOAA's decoded files,
+    /// derived from the most significant cloud layer.
+    pub fn sky_condition(&self) -> Option<&'static str> {
+        match self.clouds.iter().map(|layer| layer.cover).max() {
+            Some(CloudCover::VerticalVisibility) => Some("obscured"),
+            Some(CloudCover::Overcast) => Some("overcast"),
+            Some(CloudCover::Broken) => Some("mostly cloudy"),
+            Some(CloudCover::Scattered) => Some("partly cloudy"),
+            Some(CloudCover::Few) => Some("mostly clear"),
+            None if self.cavok => Some("clear"),
+            None => None,
+        }
+    }
+
+    /// Temperature and dewpoint in tenths of a degree celsius, from the
+    /// `T` group of the remarks which US stations report. Eg: T00561039
+    /// is 5.6 °C and -3.9 °C. The dewpoint may be missing.
+    pub fn precise_temperature(&self) -> Option<(f64, Option<f64>)> {
+        fn tenths(group: &str) -> Option<f64> {
+            let (sign, digits) = group.split_at(1);
+            if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let value = f64::from(digits.parse::<u16>().ok()?) / 10.0;
+            match sign {
+                "0" => Some(value),
+                "1" => Some(-value),
+                _ => None,
+            }
+        }
+        self.remarks
+            .as_deref()?
+            .split_whitespace()
+            .find_map(|group| {
+                // Slicing by bytes below needs ASCII, remarks may not be
+                let group = group.strip_prefix('T').filter(|group| group.is_ascii())?;
+                match group.len() {
+                    4 => Some((tenths(group)?, None)),
+                    8 => Some((tenths(&group[..4])?, Some(tenths(&group[4..])?))),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Height of the ceiling above ground level in feet: the base of the
+    /// lowest broken or overcast layer, or the vertical visibility of an
+    /// obscured sky. `None` without such a layer, or when its height
+    /// wasn't measured.
+    pub fn ceiling_ft(&self) -> Option<u32> {
+        self.clouds
+            .iter()
+            .filter(|layer| layer.cover.is_ceiling())
+            .filter_map(|layer| layer.base_ft)
+            .min()
+    }
+
+    /// Converts the report to [WeatherInfo]. The METAR only carries the
+    /// day of the month, so the year and month have to be supplied.
+    /// Returns `None` when the report lacks the temperature, dewpoint or
+    /// pressure.
+    pub fn to_weather_info(&self, year: u16, month: u8) -> Option<WeatherInfo> {
+        let mut provenance = Provenance {
+            temperature: Source::Metar,
+            dewpoint: Source::Metar,
+            wind: Source::Metar,
+            pressure: Source::Metar,
+            origin: Origin::Station,
+            fields: None,
+        };
+        let (mut celsius, mut dewpoint) = (self.temperature?, self.dewpoint?);
+        if let Some((precise, precise_dewpoint)) = self.precise_temperature() {
+            celsius = precise;
+            provenance.temperature = Source::MetarRemarks;
+            if let Some(precise_dewpoint) = precise_dewpoint {
+                dewpoint = precise_dewpoint;
+                provenance.dewpoint = Source::MetarRemarks;
+            }
+        }
+        let pressure = self.altimeter?;
+        let wind = match &self.wind {
+            Some(wind) if wind.speed > 0 => {
+                let gust = wind.gust_knots();
+                let wind_info = WindInfo {
+                    mph: (wind.speed_knots() * MPH_PER_KNOT).round(),
+                    knots: wind.speed_knots().round(),
+                    gust_mph: gust.map(|gust| (gust * MPH_PER_KNOT).round()),
+                    gust_knots: gust.map(f64::round),
+                    direction_variable: wind.direction.is_none() || wind.variable_range.is_some(),
+                    variable_range: wind.variable_range,
+                    ..WindInfo::default()
+                };
+                match wind.direction {
+                    Some(azimuth) => WindInfo {
+                        cardinal: cardinal_direction(f64::from(azimuth)).into(),
+                        azimuth: f64::from(azimuth),
+                        ..wind_info
+                    },
+                    None => wind_info,
+                }
+            }
+            _ => WindInfo::default(),
+        };
+        let weather: Vec<String> = self.weather.iter().map(|w| w.to_string()).collect();
+        Some(WeatherInfo {
+            station: None,
+            weather_time: WeatherTime {
+                year,
+                month,
+                day: self.day,
+                time: format!("{:02}{:02} UTC", self.hour, self.minute),
+            },
+            wind,
+            visibility: self.visibility_description(),
+            sky_condition: self.sky_condition().map(|s| s.to_owned()),
+            weather: if weather.is_empty() {
+                None
+            } else {
+                Some(weather.join("; "))
+            },
+            temperature: Temperature::from_celsius(celsius),
+            dewpoint: Temperature::from_celsius(dewpoint),
+            relative_humidity: relative_humidity(celsius, dewpoint).round(),
+            pressure: pressure.round() as i16,
+            metar: Some(self.clone()),
+            provenance,
+            warnings: Vec::new(),
+        })
+    }
+
+    fn visibility_description(&self) -> String {
+        match self.visibility {
+            Some(MetarVisibility::Meters(meters)) if meters < 9999 => {
+                // NOAA's decoded files truncate rather than round
+                format!("{} mile(s):0", (f64::from(meters) / 1609.344).floor())
+            }
+            Some(MetarVisibility::StatuteMiles(miles)) => format!("{} mile(s):0", miles),
+            Some(MetarVisibility::Meters(_)) | None => "greater than 7 mile(s):0".into(),
+        }
+    }
+}
+
+/// Nom parser for the raw station files, which contain the time of the
+/// observation followed by the METAR. Eg:
+///
+/// ```text
+/// 2023/12/30 12:30
+/// VOGO 301230Z 34006KT 6000 NSC 29/22 Q1010 NOSIG
+/// ```
+pub fn parse_station_file(i: &str) -> IResult<&str, WeatherInfo> {
+    let (i, (year, _, month, _, _day, _)) = tuple((
+        number::<u16>(4),
+        char('/'),
+        number::<u8>(2),
+        char('/'),
+        number::<u8>(2),
+        take_till(|c| c == '\n'),
+    ))(i)?;
+    let (i, _) = newline(i)?;
+    let (rest, metar) = parse_metar(i)?;
+    match metar.to_weather_info(year, month) {
+        Some(info) => Ok((rest, info)),
+        None => Err(nom::Err::Error(Error::new(i, ErrorKind::Verify))),
+    }
+}
+
+/// Observations of a station in a cycle file, which holds the station
+/// files of every station that reported during an hour, separated by
+/// blank lines. Reports that can't be parsed are skipped.
+pub fn parse_cycle_file(input: &str, station_code: &str) -> Vec<WeatherInfo> {
+    input
+        .split("\n\n")
+        .map(str::trim_start)
+        .filter(|report| {
+            let station = report
+                .lines()
+                .nth(1)
+                .and_then(|metar| metar.split(' ').next());
+            station.is_some_and(|station| station.eq_ignore_ascii_case(station_code))
+        })
+        .filter_map(|report| parse_station_file(report).ok().map(|(_, info)| info))
+        .map(|info| with_origin(info, Origin::Cycle))
+        .collect()
+}
+
+/// Observations in a list of METARs, one per line, as served by the
+/// archive of the Aviation Weather Center. The METARs only carry the day
+/// of the month, which is taken as the latest such day until `now`.
+/// Reports that can't be parsed are skipped.
+pub fn parse_archive_file(input: &str, now: DateTime<Utc>) -> Vec<WeatherInfo> {
+    input
+        .lines()
+        .filter_map(|line| {
+            let (_, metar) = parse_metar(line.trim()).ok()?;
+            let (mut year, mut month) = (now.year() as u16, now.month() as u8);
+            if u32::from(metar.day) > now.day() {
+                (year, month) = if month == 1 {
+                    (year - 1, 12)
+                } else {
+                    (year, month - 1)
+                };
+            }
+            metar.to_weather_info(year, month)
+        })
+        .map(|info| with_origin(info, Origin::Archive))
+        .collect()
+}
+
+fn with_origin(mut info: WeatherInfo, origin: Origin) -> WeatherInfo {
+    info.provenance.origin = origin;
+    info
+}
+
+/// Relative humidity in percent from the temperature and dewpoint in
+/// celsius, using the Magnus formula.
+fn relative_humidity(temperature: f64, dewpoint: f64) -> f64 {
+    let saturation = |t: f64| (17.625 * t / (243.04 + t)).exp();
+    100.0 * saturation(dewpoint) / saturation(temperature)
+}
+
+impl fmt::Display for WeatherGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut words: Vec<&str> = vec![];
+        match self.intensity {
+            Intensity::Light => words.push("light"),
+            Intensity::Heavy => words.push("heavy"),
+            Intensity::Moderate | Intensity::Vicinity => {}
+        }
+        let showers = self.descriptor == Some(Descriptor::Showers);
+        let thunderstorm = self.descriptor == Some(Descriptor::Thunderstorm);
+        match self.descriptor {
+            Some(Descriptor::Shallow) => words.push("shallow"),
+            Some(Descriptor::Partial) => words.push("partial"),
+            Some(Descriptor::Patches) => words.push("patches of"),
+            Some(Descriptor::LowDrifting) => words.push("low drifting"),
+            Some(Descriptor::Blowing) => words.push("blowing"),
+            Some(Descriptor::Freezing) => words.push("freezing"),
+            Some(Descriptor::Showers) | Some(Descriptor::Thunderstorm) | None => {}
+        }
+        let phenomena: Vec<&str> = self.phenomena.iter().map(|p| p.name()).collect();
+        let phenomena = phenomena.join(" and ");
+        if thunderstorm {
+            words.push("thunderstorm");
+            if !phenomena.is_empty() {
+                words.push("with");
+            }
+        }
+        if !phenomena.is_empty() {
+            words.push(&phenomena);
+        }
+        if showers {
+            words.push("showers");
+        }
+        if self.intensity == Intensity::Vicinity {
+            words.push("in the vicinity");
+        }
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+impl Phenomenon {
+    /// Name of the phenomenon in the wording of NOAA's decoded files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phenomenon::Drizzle => "drizzle",
+            Phenomenon::Rain => "rain",
+            Phenomenon::Snow => "snow",
+            Phenomenon::SnowGrains => "snow grains",
+            Phenomenon::IceCrystals => "ice crystals",
+            Phenomenon::IcePellets => "ice pellets",
+            Phenomenon::Hail => "hail",
+            Phenomenon::SmallHail => "small hail",
+            Phenomenon::UnknownPrecipitation => "unknown precipitation",
+            Phenomenon::Mist => "mist",
+            Phenomenon::Fog => "fog",
+            Phenomenon::Smoke => "smoke",
+            Phenomenon::VolcanicAsh => "volcanic ash",
+            Phenomenon::WidespreadDust => "widespread dust",
+            Phenomenon::Sand => "sand",
+            Phenomenon::Haze => "haze",
+            Phenomenon::Spray => "spray",
+            Phenomenon::DustWhirls => "dust whirls",
+            Phenomenon::Squalls => "squalls",
+            Phenomenon::FunnelCloud => "funnel cloud",
+            Phenomenon::Sandstorm => "sandstorm",
+            Phenomenon::Duststorm => "duststorm",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_wind_group() {
+        assert_eq!(
+            wind_group("28012G20KT"),
+            Ok((
+                "",
+                MetarWind {
+                    direction: Some(280),
+                    speed: 12,
+                    gust: Some(20),
+                    unit: SpeedUnit::Knots,
+                    variable_range: None,
+                }
+            ))
+        );
+        let wind = wind_group("VRB03MPS").unwrap().1;
+        assert_eq!(wind.direction, None);
+        assert_eq!(wind.unit, SpeedUnit::MetersPerSecond);
+        assert!((wind.speed_knots() - 5.83).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_weather_group() {
+        let group = is_group(weather_group, "-TSRA").unwrap();
+        assert_eq!(group.intensity, Intensity::Light);
+        assert_eq!(group.to_string(), "light thunderstorm with rain");
+        assert_eq!(
+            is_group(weather_group, "VCSH").unwrap().to_string(),
+            "showers in the vicinity"
+        );
+        assert_eq!(
+            is_group(weather_group, "FZFG").unwrap().to_string(),
+            "freezing fog"
+        );
+        assert!(is_group(weather_group, "NOSIG").is_none());
+        assert!(is_group(weather_group, "-").is_none());
+    }
+
+    #[test]
+    fn test_vogo_metar() {
+        let (_, metar) = parse_metar("VOGO 301230Z 34006KT 6000 NSC 29/22 Q1010 NOSIG").unwrap();
+        assert_eq!(metar.station, "VOGO");
+        assert_eq!((metar.day, metar.hour, metar.minute), (30, 12, 30));
+        assert_eq!(metar.visibility, Some(MetarVisibility::Meters(6000)));
+        assert_eq!(metar.visibility.unwrap().meters(), 6000.0);
+        assert!(metar.clouds.is_empty());
+        assert_eq!(metar.temperature, Some(29.0));
+        assert_eq!(metar.dewpoint, Some(22.0));
+        assert_eq!(metar.altimeter, Some(1010.0));
+        assert_eq!(metar.sky_condition(), None);
+    }
+
+    #[test]
+    fn test_trends() {
+        let (_, metar) = parse_metar(
+            "EGLL 301220Z 24015KT 9999 FEW030 12/08 Q1002 BECMG TL1330 3000 -RA BKN012 TEMPO FM1300 TL1400 25025G35KT TSRA RMK TEMPO",
+        )
+        .unwrap();
+        assert_eq!(metar.weather, []);
+        assert_eq!(metar.clouds.len(), 1);
+        assert_eq!(metar.remarks, Some("TEMPO".into()));
+        let [becoming, temporary] = &metar.trends[..] else {
+            panic!("{:?}", metar.trends);
+        };
+        assert_eq!(becoming.kind, TrendKind::Becoming);
+        assert_eq!((becoming.from, becoming.until), (None, Some((13, 30))));
+        assert_eq!(becoming.visibility, Some(MetarVisibility::Meters(3000)));
+        assert_eq!(becoming.weather[0].to_string(), "light rain");
+        assert_eq!(becoming.clouds[0].base_ft, Some(1200));
+        assert_eq!(temporary.kind, TrendKind::Temporary);
+        assert_eq!(temporary.from, Some((13, 0)));
+        assert_eq!(temporary.wind.as_ref().unwrap().gust, Some(35));
+        assert_eq!(
+            temporary.weather[0].descriptor,
+            Some(Descriptor::Thunderstorm)
+        );
+
+        let (_, metar) = parse_metar("VOGO 301230Z 34006KT 6000 NSC 29/22 Q1010 NOSIG").unwrap();
+        assert_eq!(
+            metar.trends,
+            [MetarTrend::new(TrendKind::NoSignificantChange)]
+        );
+    }
+
+    #[test]
+    fn test_ceiling() {
+        let ceiling = |metar: &str| parse_metar(metar).unwrap().1.ceiling_ft();
+        assert_eq!(ceiling("KSFO 301356Z 28012KT 10SM CLR 12/11 A3002"), None);
+        assert_eq!(
+            ceiling("KSFO 301356Z 28012KT 10SM FEW008 SCT012 BKN025 OVC040 12/11 A3002"),
+            Some(2500)
+        );
+        assert_eq!(
+            ceiling("KSFO 301356Z 28012KT 1/4SM FG VV002 12/11 A3002"),
+            Some(200)
+        );
+        assert_eq!(
+            ceiling("KSFO 301356Z 28012KT 10SM BKN/// OVC040 12/11 A3002"),
+            Some(4000)
+        );
+    }
+
+    #[test]
+    fn test_report_type() {
+        let (_, metar) = parse_metar("VOGO 301230Z 34006KT 6000 NSC 29/22 Q1010").unwrap();
+        assert_eq!(metar.report_type, ReportType::Metar);
+        assert_eq!(metar.modifier, None);
+        let (_, metar) =
+            parse_metar("SPECI KSFO 301356Z AUTO 28012KT 10SM CLR 12/11 A3002").unwrap();
+        assert_eq!(metar.report_type, ReportType::Speci);
+        assert_eq!(metar.modifier, Some(ReportModifier::Auto));
+        assert_eq!(metar.wind.map(|w| w.speed), Some(12));
+        let (_, metar) =
+            parse_metar("METAR COR EGLL 301350Z 24010KT 9999 FEW030 09/05 Q1002").unwrap();
+        assert_eq!(metar.station, "EGLL");
+        assert_eq!(metar.modifier, Some(ReportModifier::Corrected));
+    }
+
+    #[test]
+    fn test_kykm_metar() {
+        let (_, metar) = parse_metar(
+            "KYKM 310353Z AUTO 00000KT 1 1/2SM -RA BR OVC025 M06/M08 A3005 RMK AO2 SLP185",
+        )
+        .unwrap();
+        assert_eq!(metar.visibility, Some(MetarVisibility::StatuteMiles(1.5)));
+        assert_eq!(metar.weather.len(), 2);
+        assert_eq!(
+            metar.clouds,
+            vec![CloudLayer {
+                cover: CloudCover::Overcast,
+                base_ft: Some(2500),
+                cloud_type: None,
+            }]
+        );
+        assert_eq!(metar.temperature, Some(-6.0));
+        assert_eq!(metar.dewpoint, Some(-8.0));
+        assert_eq!(metar.altimeter.map(f64::round), Some(1018.0));
+        assert_eq!(metar.remarks, Some("AO2 SLP185".into()));
+    }
+
+    #[test]
+    fn test_archive_file() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 6, 0, 0).unwrap();
+        let archive = "METAR KSFO 020556Z 28012KT 10SM FEW008 12/11 A3002 RMK AO2 T01220106
+SPECI KSFO 311802Z 28012KT 10SM FEW008 13/11 A3002
+KSFO broken";
+        let history = parse_archive_file(archive, now);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].temperature.celsius, 12.2);
+        assert_eq!(history[0].provenance.origin, Origin::Archive);
+        let time = |info: &WeatherInfo| (info.weather_time.year, info.weather_time.month);
+        assert_eq!(time(&history[0]), (2024, 1));
+        assert_eq!(time(&history[1]), (2023, 12));
+    }
+
+    #[test]
+    fn test_precise_temperature() {
+        let precise = |remarks: &str| {
+            let metar = format!(
+                "KYKM 310353Z 00000KT 5SM OVC025 06/04 A3005 RMK {}",
+                remarks
+            );
+            parse_metar(&metar).unwrap().1.precise_temperature()
+        };
+        assert_eq!(precise("AO2 SLP185 T00560039"), Some((5.6, Some(3.9))));
+        assert_eq!(precise("AO2 T10561039"), Some((-5.6, Some(-3.9))));
+        assert_eq!(precise("T0056"), Some((5.6, None)));
+        assert_eq!(precise("AO2 TSB05"), None);
+        assert_eq!(precise("T20560039"), None);
+        assert_eq!(precise("AO2 Té12"), None);
+        let (_, metar) =
+            parse_metar("KSFO 121756Z 28012KT 10SM FEW020 18/12 A3001 RMK AO2 Té12").unwrap();
+        assert_eq!(metar.precise_temperature(), None);
+    }
+
+    #[test]
+    fn test_missing_time() {
+        assert!(parse_metar("VOGO 34006KT 6000").is_err());
+        assert!(parse_metar("").is_err());
+    }
+
+    #[test]
+    fn test_station_file() {
+        let file = "2023/12/30 12:30
+VOGO 301230Z 34006KT 6000 -RA FEW020CB 29/22 Q1010 NOSIG
+";
+        let (_, info) = parse_station_file(file).unwrap();
+        assert_eq!(
+            info.weather_time,
+            WeatherTime {
+                year: 2023,
+                month: 12,
+                day: 30,
+                time: "1230 UTC".into(),
+            }
+        );
+        assert_eq!(
+            info.wind,
+            WindInfo {
+                cardinal: "NNW".into(),
+                azimuth: 340.0,
+                mph: 7.0,
+                knots: 6.0,
+                gust_mph: None,
+                gust_knots: None,
+                direction_variable: false,
+                variable_range: None,
+            }
+        );
+        assert_eq!(info.visibility, "3 mile(s):0");
+        assert_eq!(info.sky_condition, Some("mostly clear".into()));
+        assert_eq!(info.weather, Some("light rain".into()));
+        assert_eq!(info.temperature.celsius, 29.0);
+        assert_eq!(info.relative_humidity, 66.0);
+        assert_eq!(info.pressure, 1010);
+    }
+
+    #[test]
+    fn test_cycle_file() {
+        let file = "2023/12/30 12:30
+VOGO 301230Z 34006KT 6000 NSC 29/22 Q1010 NOSIG
+
+2023/12/30 12:30
+VOBL 301230Z 08008KT 9999 SCT020 23/14 Q1017
+
+2023/12/30 12:45
+VOGO 301245Z 34007KT 6000 NSC 30/22 Q1010
+
+2023/12/30 12:50
+VOGO 301250Z 34007KT 6000 NSC Q1010
+";
+        let infos = parse_cycle_file(file, "vogo");
+        let times: Vec<&str> = infos.iter().map(|i| i.weather_time.time.as_str()).collect();
+        assert_eq!(times, vec!["1230 UTC", "1245 UTC"]);
+        assert!(parse_cycle_file(file, "KSFO").is_empty());
+    }
+
+    #[test]
+    fn test_station_file_without_temperature() {
+        let file = "2023/12/30 12:30
+VOGO 301230Z 34006KT 6000 NSC Q1010
+";
+        assert!(parse_station_file(file).is_err());
+    }
+}
@@ -0,0 +1,25 @@
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+
+/// Hooks run around every request sent by [NoaaApp](crate::weather::NoaaApp),
+/// including retries and the requests of the blocking API.
+///
+/// Both hooks do nothing by default, so an implementation only needs to
+/// override the one it uses. Middlewares run in the order they were
+/// registered.
+pub trait Middleware: Send + Sync {
+    /// Called before a request is sent. The url and headers can be
+    /// changed, eg: to add an authorization header or to point the
+    /// request to a private mirror of NOAA's files.
+    fn before_send(&self, _method: &Method, _url: &mut Url, _headers: &mut HeaderMap) {}
+
+    /// Called with each response received, before its status is checked.
+    fn after_response(
+        &self,
+        _method: &Method,
+        _url: &Url,
+        _status: StatusCode,
+        _headers: &HeaderMap,
+    ) {
+    }
+}
@@ -0,0 +1,182 @@
+use crate::weather::WeatherInfo;
+use std::collections::HashMap;
+use std::fmt;
+
+// The daily normals are published by NCEI as one CSV file per station,
+// named after its GHCN-Daily identifier, which differs from the ICAO
+// code. Eg: USW00023234 for KSFO. Along with the station metadata, each
+// row holds the normals of a day of the year, in Fahrenheit:
+//
+// "STATION","DATE","LATITUDE","LONGITUDE","ELEVATION","NAME","DLY-TMAX-NORMAL",...,"DLY-TMIN-NORMAL",...
+// "USW00023234","01-01","37.6197","-122.3656","2.4","SAN FRANCISCO INTL AP, CA US","  57.5",...,"  44.3",...
+
+/// URL of the 1991–2020 daily normals of a station, by its GHCN-Daily
+/// identifier.
+pub fn normals_url(ghcn_id: &str) -> String {
+    format!(
+        "https://www.ncei.noaa.gov/data/normals-daily/1991-2020/access/{}.csv",
+        ghcn_id
+    )
+}
+
+/// Normal temperatures of a day, in celsius.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DayNormals {
+    /// Normal daily high
+    pub high: f64,
+    /// Normal daily low
+    pub low: f64,
+}
+
+/// 1991–2020 daily climate normals of a station.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct ClimateNormals {
+    days: HashMap<(u8, u8), DayNormals>,
+}
+
+impl ClimateNormals {
+    /// Parses the CSV file of a station's daily normals. Days without
+    /// both temperature normals are skipped.
+    pub fn parse(input: &str) -> Self {
+        let mut lines = input.lines();
+        let header = lines.next().map(split_csv_line).unwrap_or_default();
+        let column = |name: &str| header.iter().position(|field| field == name);
+        let days = match (
+            column("DATE"),
+            column("DLY-TMAX-NORMAL"),
+            column("DLY-TMIN-NORMAL"),
+        ) {
+            (Some(date), Some(high), Some(low)) => lines
+                .filter_map(|line| {
+                    let fields = split_csv_line(line);
+                    let (month, day) = fields.get(date)?.split_once('-')?;
+                    let key = (month.parse().ok()?, day.parse().ok()?);
+                    let normals = DayNormals {
+                        high: fahrenheit_field(fields.get(high)?)?,
+                        low: fahrenheit_field(fields.get(low)?)?,
+                    };
+                    Some((key, normals))
+                })
+                .collect(),
+            _ => HashMap::new(),
+        };
+        ClimateNormals { days }
+    }
+
+    /// Normals of a day of the year.
+    pub fn day(&self, month: u8, day: u8) -> Option<DayNormals> {
+        self.days.get(&(month, day)).copied()
+    }
+
+    /// How the temperature of an observation departs from the normals of
+    /// its day. `None` when the normals of the day are missing.
+    pub fn compare_to_normals(&self, info: &WeatherInfo) -> Option<Anomaly> {
+        let time = &info.weather_time;
+        let normals = self.day(time.month, time.day)?;
+        let temperature = info.temperature.celsius;
+        Some(if temperature > normals.high {
+            Anomaly::AboveHigh(temperature - normals.high)
+        } else if temperature < normals.low {
+            Anomaly::BelowLow(normals.low - temperature)
+        } else {
+            Anomaly::Normal
+        })
+    }
+}
+
+/// Departure of a temperature from the normals of its day, in celsius.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Anomaly {
+    /// Warmer than the normal high, by the given degrees
+    AboveHigh(f64),
+    /// Colder than the normal low, by the given degrees
+    BelowLow(f64),
+    /// Between the normal low and high
+    Normal,
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anomaly::AboveHigh(degrees) => write!(f, "+{:.1} °C above normal high", degrees),
+            Anomaly::BelowLow(degrees) => write!(f, "-{:.1} °C below normal low", degrees),
+            Anomaly::Normal => write!(f, "within normal range"),
+        }
+    }
+}
+
+/// A temperature field in Fahrenheit converted to celsius. NCEI marks
+/// missing values with negative sentinels such as -9999.
+fn fahrenheit_field(field: &str) -> Option<f64> {
+    let fahrenheit: f64 = field.trim().parse().ok()?;
+    if fahrenheit <= -7777.0 {
+        return None;
+    }
+    Some(((fahrenheit - 32.0) * 5.0 / 9.0 * 10.0).round() / 10.0)
+}
+
+/// Splits a CSV line into its fields, removing the quotes around them.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NORMALS: &str = r#""STATION","DATE","LATITUDE","LONGITUDE","ELEVATION","NAME","DLY-TMAX-NORMAL","DLY-TMAX-ATTRIBUTES","DLY-TMIN-NORMAL","DLY-TMIN-ATTRIBUTES"
+"USW00023234","12-30","37.6197","-122.3656","2.4","SAN FRANCISCO INTL AP, CA US","  57.2","S","  43.7","S"
+"USW00023234","12-31","37.6197","-122.3656","2.4","SAN FRANCISCO INTL AP, CA US","  57.3","S","-9999",""
+"#;
+
+    #[test]
+    fn test_parse_normals() {
+        let normals = ClimateNormals::parse(NORMALS);
+        assert_eq!(
+            normals.day(12, 30),
+            Some(DayNormals {
+                high: 14.0,
+                low: 6.5
+            })
+        );
+        assert_eq!(normals.day(12, 31), None);
+        assert_eq!(ClimateNormals::parse("broken"), ClimateNormals::default());
+    }
+
+    #[test]
+    fn test_compare_to_normals() {
+        let normals = ClimateNormals::parse(NORMALS);
+        let (_, metar) =
+            crate::metar::parse_metar("KSFO 301356Z 22014KT 10SM OVC008 12/11 A3002").unwrap();
+        let mut info = metar.to_weather_info(2023, 12).unwrap();
+        assert_eq!(normals.compare_to_normals(&info), Some(Anomaly::Normal));
+        info.temperature.celsius = 18.2;
+        let anomaly = normals.compare_to_normals(&info).unwrap();
+        assert_eq!(anomaly.to_string(), "+4.2 °C above normal high");
+        info.temperature.celsius = 3.0;
+        assert_eq!(
+            normals.compare_to_normals(&info).unwrap().to_string(),
+            "-3.5 °C below normal low"
+        );
+        info.weather_time.day = 31;
+        assert_eq!(normals.compare_to_normals(&info), None);
+    }
+}
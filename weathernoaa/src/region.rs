@@ -0,0 +1,85 @@
+//! Observations of a group of stations taken together, eg: the
+//! stations around a bay, along with their extremes.
+
+use crate::weather::WeatherInfo;
+
+/// Station with the most extreme value of a [RegionReport].
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extreme {
+    pub station: String,
+    pub value: f64,
+}
+
+/// Observations of a group of stations, with the extremes computed
+/// across them. The extremes are `None` when there are no observations.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegionReport {
+    /// Observations along with their station, in the order given
+    pub observations: Vec<(String, WeatherInfo)>,
+    /// Highest temperature in celsius
+    pub warmest: Option<Extreme>,
+    /// Lowest temperature in celsius
+    pub coldest: Option<Extreme>,
+    /// Strongest wind speed in knots, gusts aside
+    pub windiest: Option<Extreme>,
+}
+
+impl RegionReport {
+    pub fn new(observations: Vec<(String, WeatherInfo)>) -> Self {
+        let extreme = |value: fn(&WeatherInfo) -> f64, highest: bool| {
+            observations
+                .iter()
+                .map(|(station, info)| Extreme {
+                    station: station.clone(),
+                    value: value(info),
+                })
+                // The first station wins ties
+                .reduce(|best, extreme| {
+                    let better = if highest {
+                        extreme.value > best.value
+                    } else {
+                        extreme.value < best.value
+                    };
+                    if better {
+                        extreme
+                    } else {
+                        best
+                    }
+                })
+        };
+        RegionReport {
+            warmest: extreme(|info| info.temperature.celsius, true),
+            coldest: extreme(|info| info.temperature.celsius, false),
+            windiest: extreme(|info| info.wind.knots, true),
+            observations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+    use crate::weather::Temperature;
+
+    #[test]
+    fn test_region_report() {
+        let (_, vogo) = parse_weather(&vogo_report("1230")).unwrap();
+        let mut cooler = vogo.clone();
+        cooler.temperature = Temperature::from_celsius(15.0);
+        cooler.wind.knots = 12.0;
+        let report = RegionReport::new(vec![
+            ("VOGO".into(), vogo.clone()),
+            ("VOBL".into(), cooler),
+            ("VOMM".into(), vogo),
+        ]);
+        let station = |extreme: &Option<Extreme>| extreme.as_ref().unwrap().station.clone();
+        assert_eq!(station(&report.warmest), "VOGO");
+        assert_eq!(station(&report.coldest), "VOBL");
+        assert_eq!(report.windiest.unwrap().value, 12.0);
+        assert_eq!(RegionReport::new(vec![]).warmest, None);
+    }
+}
@@ -0,0 +1,98 @@
+//! How reliably stations publish their observations, from what was seen
+//! at successive checks of them, eg: for preferring the stations which
+//! report on schedule, see [StationDirectory::nearest_reliable].
+//!
+//! [StationDirectory::nearest_reliable]: crate::directory::StationDirectory::nearest_reliable
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Time between observations assumed when too few were seen to tell,
+/// most stations publishing a METAR every hour.
+pub const DEFAULT_CADENCE: Duration = Duration::from_secs(3600);
+
+/// A check of a station: when its latest observation was fetched, and
+/// the time of that observation.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Check {
+    pub checked: DateTime<Utc>,
+    pub observed: DateTime<Utc>,
+}
+
+/// Reliability of a station over checks of it.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reliability {
+    /// Typical time between the observations: the median interval
+    /// between the distinct observations seen. `None` when fewer than
+    /// two were seen. Checking rarely makes it look longer than it is.
+    pub cadence: Option<Duration>,
+    /// Share of the checks at which the observation was on schedule,
+    /// from 0 to 1. An observation is on schedule until it's half a
+    /// cadence overdue.
+    pub score: f64,
+    /// Number of checks
+    pub checks: usize,
+}
+
+impl Reliability {
+    /// Reliability over the checks, in any order. `None` without
+    /// checks.
+    pub fn new(checks: &[Check]) -> Option<Self> {
+        if checks.is_empty() {
+            return None;
+        }
+        let mut observed: Vec<DateTime<Utc>> = checks.iter().map(|check| check.observed).collect();
+        observed.sort();
+        observed.dedup();
+        let mut intervals: Vec<Duration> = observed
+            .windows(2)
+            .filter_map(|pair| (pair[1] - pair[0]).to_std().ok())
+            .collect();
+        intervals.sort();
+        let cadence = intervals.get(intervals.len() / 2).copied();
+        let overdue = cadence.unwrap_or(DEFAULT_CADENCE).mul_f64(1.5);
+        let on_schedule = checks
+            .iter()
+            .filter(|check| {
+                (check.checked - check.observed)
+                    .to_std()
+                    .map_or(true, |age| age <= overdue)
+            })
+            .count();
+        Some(Reliability {
+            cadence,
+            score: on_schedule as f64 / checks.len() as f64,
+            checks: checks.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_reliability() {
+        let time =
+            |hour: u32, minute: u32| Utc.with_ymd_and_hms(2023, 12, 30, hour, minute, 0).unwrap();
+        let check = |checked: DateTime<Utc>, observed: DateTime<Utc>| Check { checked, observed };
+        let reliability = Reliability::new(&[
+            check(time(10, 5), time(9, 30)),
+            check(time(10, 20), time(10, 0)),
+            check(time(10, 40), time(10, 30)),
+            // Two hours without a new observation
+            check(time(12, 35), time(10, 30)),
+        ])
+        .unwrap();
+        assert_eq!(reliability.cadence, Some(Duration::from_secs(1800)));
+        assert_eq!(reliability.score, 0.75);
+        assert_eq!(reliability.checks, 4);
+
+        let once = Reliability::new(&[check(time(10, 5), time(9, 30))]).unwrap();
+        assert_eq!((once.cadence, once.score), (None, 1.0));
+        assert_eq!(Reliability::new(&[]), None);
+    }
+}
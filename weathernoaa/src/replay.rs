@@ -0,0 +1,247 @@
+//! Recording the responses of NOAA's servers into cassette files and
+//! replaying them later, so that integration tests don't depend on live
+//! data which changes every hour. Requires the `replay` feature.
+//!
+//! A cassette is a JSON file holding the responses in the order they
+//! were received:
+//!
+//! ```json
+//! {
+//!   "interactions": [
+//!     {
+//!       "method": "GET",
+//!       "url": "https://tgftp.nws.noaa.gov/data/observations/metar/decoded/VOBL.TXT",
+//!       "status": 200,
+//!       "headers": [["last-modified", "Sat, 30 Dec 2023 13:34:12 GMT"]],
+//!       "body": "Bangalore / Hindustan, India (VOBL) 12-57N 077-38E 0 ..."
+//!     }
+//!   ]
+//! }
+//! ```
+
+use crate::weather::WeatherError;
+use reqwest::header::{HeaderMap, CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING};
+use reqwest::{Method, Request, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A response as recorded in a cassette. Bodies are kept as text, which
+/// NOAA's files are.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    /// Url of the request, before the [middlewares](crate::middleware)
+    /// change it
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Recording {
+    interactions: Vec<Interaction>,
+}
+
+enum Mode {
+    Record(PathBuf),
+    /// Position of the next response of each request
+    Replay(HashMap<(String, String), usize>),
+}
+
+/// Recorded responses, used by [NoaaApp](crate::weather::NoaaApp) in
+/// place of the network once given to
+/// [with_cassette](crate::weather::NoaaApp::with_cassette).
+pub struct Cassette {
+    mode: Mutex<Mode>,
+    recording: Mutex<Recording>,
+}
+
+impl Cassette {
+    /// Records the responses received into the file at `path`, which is
+    /// replaced. The file is written after each response.
+    pub fn record<P: AsRef<Path>>(path: P) -> Self {
+        Cassette {
+            mode: Mutex::new(Mode::Record(path.as_ref().to_owned())),
+            recording: Mutex::default(),
+        }
+    }
+
+    /// Replays the responses of the file at `path` instead of sending
+    /// requests. The responses to the same request are replayed in the
+    /// order they were recorded, the last one being repeated. Requests
+    /// which weren't recorded fail with [WeatherError::Cassette].
+    pub fn replay<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let recording = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(Cassette {
+            mode: Mutex::new(Mode::Replay(HashMap::new())),
+            recording: Mutex::new(recording),
+        })
+    }
+
+    /// The recorded responses, in order.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        lock(&self.recording).interactions.clone()
+    }
+
+    /// The recorded response to a request, when replaying. `None` when
+    /// recording.
+    fn replayed(&self, method: &Method, url: &str) -> Option<Result<Interaction, WeatherError>> {
+        let mut mode = lock(&self.mode);
+        let Mode::Replay(positions) = &mut *mode else {
+            return None;
+        };
+        let key = (method.to_string(), url.to_owned());
+        let recording = lock(&self.recording);
+        let matching: Vec<&Interaction> = recording
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.method == key.0 && interaction.url == key.1)
+            .collect();
+        let position = positions.entry(key.clone()).or_default();
+        let interaction = matching.get(*position).or(matching.last()).copied();
+        *position += 1;
+        Some(interaction.cloned().ok_or_else(|| {
+            WeatherError::Cassette(format!("No response recorded for {} {}", key.0, key.1))
+        }))
+    }
+
+    /// Keeps a received response in the recording and writes it.
+    fn record_response(&self, interaction: Interaction) -> io::Result<()> {
+        let mode = lock(&self.mode);
+        let Mode::Record(path) = &*mode else {
+            return Ok(());
+        };
+        let mut recording = lock(&self.recording);
+        recording.interactions.push(interaction);
+        fs::write(path, serde_json::to_string_pretty(&*recording)?)
+    }
+
+    /// Sends the request to `url`, or replays its response. The request
+    /// may be sent elsewhere, having been through the middlewares.
+    pub(crate) async fn execute(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        request: Request,
+    ) -> Result<Response, WeatherError> {
+        if let Some(interaction) = self.replayed(request.method(), url) {
+            return Ok(interaction?.to_response()?.into());
+        }
+        let method = request.method().to_string();
+        let res = client.execute(request).await?;
+        let (status, headers) = (res.status().as_u16(), header_pairs(res.headers()));
+        let interaction = Interaction {
+            method,
+            url: url.to_owned(),
+            status,
+            headers,
+            body: res.text().await?,
+        };
+        let response = interaction.to_response()?;
+        self.record_response(interaction)
+            .map_err(|err| WeatherError::Cassette(err.to_string()))?;
+        Ok(response.into())
+    }
+
+    /// Blocking version of [Cassette::execute].
+    #[cfg(feature = "blocking")]
+    pub(crate) fn execute_blocking(
+        &self,
+        client: &reqwest::blocking::Client,
+        url: &str,
+        request: reqwest::blocking::Request,
+    ) -> Result<reqwest::blocking::Response, WeatherError> {
+        if let Some(interaction) = self.replayed(request.method(), url) {
+            return Ok(interaction?.to_response()?.into());
+        }
+        let method = request.method().to_string();
+        let res = client.execute(request)?;
+        let (status, headers) = (res.status().as_u16(), header_pairs(res.headers()));
+        let interaction = Interaction {
+            method,
+            url: url.to_owned(),
+            status,
+            headers,
+            body: res.text()?,
+        };
+        let response = interaction.to_response()?;
+        self.record_response(interaction)
+            .map_err(|err| WeatherError::Cassette(err.to_string()))?;
+        Ok(response.into())
+    }
+}
+
+impl Interaction {
+    fn to_response(&self) -> Result<http::Response<String>, WeatherError> {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder
+            .body(self.body.clone())
+            .map_err(|err| WeatherError::Cassette(err.to_string()))
+    }
+}
+
+/// The headers whose value is text, as recorded. Those describing the
+/// encoding of the body are left out, since the body is recorded
+/// decoded.
+fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| ![CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING].contains(name))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+        .collect()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::tests::{serve, vogo_report, Mirror};
+    use crate::weather::NoaaApp;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("weathernoaa-cassette-{}.json", std::process::id()));
+        let (port, server) = serve(vec![vogo_report("1230")]);
+        let recording = NoaaApp::new()
+            .with_middleware(Mirror {
+                port,
+                statuses: Arc::default(),
+            })
+            .with_cassette(Cassette::record(&path));
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let recorded = rt.block_on(recording.get_weather("VOGO")).unwrap();
+        server.join().unwrap();
+
+        // The server is gone, the response comes from the cassette
+        let replaying = NoaaApp::new().with_cassette(Cassette::replay(&path).unwrap());
+        assert_eq!(
+            rt.block_on(replaying.get_weather("VOGO")).unwrap(),
+            recorded
+        );
+        assert_eq!(
+            rt.block_on(replaying.get_weather("VOGO")).unwrap(),
+            recorded
+        );
+        let err = rt.block_on(replaying.get_weather("VOBL")).unwrap_err();
+        assert!(matches!(err, WeatherError::Cassette(_)));
+        let interactions = Cassette::replay(&path).unwrap().interactions();
+        assert_eq!(interactions.len(), 1);
+        assert!(interactions[0].url.ends_with("/decoded/VOGO.TXT"));
+        fs::remove_file(path).unwrap();
+    }
+}
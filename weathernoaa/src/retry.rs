@@ -0,0 +1,138 @@
+use crate::weather::WeatherError;
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// When and how often failed requests are retried.
+///
+/// Network failures and the statuses NOAA's servers use when they are
+/// overloaded (429, 500, 502, 503 and 504) are retried. A delay sent by
+/// the server in the `Retry-After` header is honored, otherwise the
+/// delay doubles from `initial_backoff` on each attempt.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Longest delay waited before a retry. When the server asks for a
+    /// longer delay, the request fails instead of retrying.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Delay to wait before the retry following the failed `attempt`,
+    /// counting from 0 for the first one. `None` when the request
+    /// shouldn't be retried.
+    pub fn delay(&self, attempt: u32, error: &WeatherError) -> Option<Duration> {
+        if attempt >= self.max_retries || !is_retryable(error) {
+            return None;
+        }
+        match error {
+            WeatherError::Http {
+                retry_after: Some(wait),
+                ..
+            } => Some(*wait).filter(|wait| *wait <= self.max_backoff),
+            _ => {
+                let factor = 2u32.saturating_pow(attempt);
+                Some(
+                    self.initial_backoff
+                        .saturating_mul(factor)
+                        .min(self.max_backoff),
+                )
+            }
+        }
+    }
+}
+
+fn is_retryable(error: &WeatherError) -> bool {
+    match error {
+        WeatherError::Http { status, .. } => matches!(
+            *status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::INTERNAL_SERVER_ERROR
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        ),
+        WeatherError::ReqwestError(err) => err.is_timeout() || err.is_connect(),
+        _ => false,
+    }
+}
+
+/// A request about to be retried, passed to the [RetryObserver].
+#[derive(Debug)]
+pub struct RetryEvent<'a> {
+    pub url: &'a str,
+    /// The failed attempt, counting from 0 for the first one
+    pub attempt: u32,
+    /// Delay before the request is sent again
+    pub wait: Duration,
+    /// Error of the failed attempt
+    pub error: &'a WeatherError,
+}
+
+/// Callback notified before waiting to retry a request, eg: for
+/// showing that the server is throttling requests.
+pub type RetryObserver = Arc<dyn Fn(&RetryEvent) + Send + Sync>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http_error(status: StatusCode, retry_after: Option<u64>) -> WeatherError {
+        WeatherError::Http {
+            status,
+            url: "https://tgftp.nws.noaa.gov/data/nsd_cccc.txt".into(),
+            retry_after: retry_after.map(Duration::from_secs),
+            last_modified: None,
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+        };
+        let error = http_error(StatusCode::BAD_GATEWAY, None);
+        let delays: Vec<Option<u64>> = (0..6)
+            .map(|attempt| policy.delay(attempt, &error).map(|d| d.as_secs()))
+            .collect();
+        assert_eq!(
+            delays,
+            vec![Some(1), Some(2), Some(4), Some(8), Some(10), None]
+        );
+        assert_eq!(RetryPolicy::none().delay(0, &error), None);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let policy = RetryPolicy::default();
+        let throttled = http_error(StatusCode::TOO_MANY_REQUESTS, Some(30));
+        assert_eq!(policy.delay(0, &throttled), Some(Duration::from_secs(30)));
+        let unavailable = http_error(StatusCode::SERVICE_UNAVAILABLE, Some(3600));
+        assert_eq!(policy.delay(0, &unavailable), None);
+        let not_found = http_error(StatusCode::NOT_FOUND, None);
+        assert_eq!(policy.delay(0, &not_found), None);
+    }
+}
@@ -0,0 +1,321 @@
+//! Schedules of polling by station, eg: for fetching coastal stations
+//! every 20 minutes and inland ones hourly, with
+//! [NoaaApp::observe_scheduled](crate::weather::NoaaApp::observe_scheduled).
+//!
+//! Schedules are given as cron expressions in UTC, of five fields: the
+//! minute, hour, day of the month, month and day of the week, Sunday
+//! being 0 or 7. A field is `*`, a value, a range such as `1-5`, any of
+//! them followed by a step such as `*/20`, or a list of those separated
+//! by commas. As with cron, when both days are restricted, either may
+//! match.
+//!
+//! With the `serde` feature, cron expressions are (de)serialized as
+//! strings, eg: in TOML `cron = "*/20 * * * *"`.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, Timelike, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Number of steps after which [Cron::next_after] gives up, for
+/// expressions which never match, eg: the 30th of February.
+const MAX_STEPS: usize = 10_000;
+
+/// The cron expression couldn't be parsed.
+#[derive(Error, PartialEq, Eq, Debug, Clone)]
+#[error("Invalid cron expression {expression:?}: {reason}")]
+pub struct CronError {
+    pub expression: String,
+    pub reason: &'static str,
+}
+
+/// A cron expression, telling the minutes at which to poll.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct Cron {
+    expression: String,
+    /// Sets of the allowed values of each field, as bits
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day of the month, or of the week, is `*`
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    /// Every minute the hour, eg: `hourly(20)` is `20 * * * *`.
+    pub fn hourly(minute: u32) -> Self {
+        format!("{} * * * *", minute % 60).parse().unwrap()
+    }
+
+    /// The first time the expression matches strictly after `time`, to
+    /// the minute. `None` when it never matches.
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = time.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        for _ in 0..MAX_STEPS {
+            if !has(self.months, time.month()) {
+                let (year, month) = match time.month() {
+                    12 => (time.year() + 1, 1),
+                    month => (time.year(), month + 1),
+                };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !self.matches_day(time) {
+                time = (time.date_naive() + ChronoDuration::days(1))
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !has(self.hours, time.hour()) {
+                time = time.with_minute(0)? + ChronoDuration::hours(1);
+            } else if !has(self.minutes, time.minute()) {
+                time += ChronoDuration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, time: DateTime<Utc>) -> bool {
+        let day = has(self.days, time.day());
+        let weekday = has(self.weekdays, time.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parses a field of a cron expression into the set of its values.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, &'static str> {
+    let number = |value: &str| {
+        value
+            .parse::<u32>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or("value out of range")
+    };
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<usize>().ok().filter(|step| *step > 0);
+                (range, step.ok_or("invalid step")?)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (number(start)?, number(end)?),
+            // Eg: 5/15, from 5 on
+            None if step > 1 => (number(range)?, max),
+            None => (number(range)?, number(range)?),
+        };
+        if start > end {
+            return Err("range ends before its start");
+        }
+        for value in (start..=end).step_by(step) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+impl FromStr for Cron {
+    type Err = CronError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let error = |reason| CronError {
+            expression: expression.to_owned(),
+            reason,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(error("expected 5 fields"));
+        };
+        let sundays = parse_field(weekdays, 0, 7).map_err(error)?;
+        Ok(Cron {
+            expression: fields.join(" "),
+            minutes: parse_field(minutes, 0, 59).map_err(error)?,
+            hours: parse_field(hours, 0, 23).map_err(error)?,
+            days: parse_field(days, 1, 31).map_err(error)?,
+            months: parse_field(months, 1, 12).map_err(error)?,
+            // Sunday is both 0 and 7
+            weekdays: (sundays | sundays >> 7) & 0x7f,
+            any_day: days.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        })
+    }
+}
+
+impl TryFrom<String> for Cron {
+    type Error = CronError;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        expression.parse()
+    }
+}
+
+impl From<Cron> for String {
+    fn from(cron: Cron) -> Self {
+        cron.expression
+    }
+}
+
+impl fmt::Display for Cron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+/// When to poll each station: the cron expression of the first set of
+/// stations it belongs to, or else the default one. Each station is
+/// polled a little after the times of its expression, by up to the
+/// jitter, so that stations sharing a schedule aren't all fetched at
+/// once.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Schedule {
+    default: Cron,
+    stations: Vec<(Vec<String>, Cron)>,
+    jitter: Duration,
+}
+
+impl Schedule {
+    pub fn new(default: Cron) -> Self {
+        Schedule {
+            default,
+            stations: vec![],
+            jitter: Duration::ZERO,
+        }
+    }
+
+    /// Polls the stations, eg: those of a group, according to `cron`.
+    pub fn with_stations<S: Into<String>>(
+        mut self,
+        stations: impl IntoIterator<Item = S>,
+        cron: Cron,
+    ) -> Self {
+        let stations = stations.into_iter().map(Into::into).collect();
+        self.stations.push((stations, cron));
+        self
+    }
+
+    /// Delays the polls of each station by up to `jitter`, which should
+    /// be shorter than the time between polls.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The cron expression of a station, matched ignoring case.
+    pub fn cron(&self, station: &str) -> &Cron {
+        self.stations
+            .iter()
+            .find(|(stations, _)| {
+                stations
+                    .iter()
+                    .any(|code| code.eq_ignore_ascii_case(station))
+            })
+            .map_or(&self.default, |(_, cron)| cron)
+    }
+
+    /// Delay of the polls of a station after the times of its cron
+    /// expression. It's derived from the station code, so that it
+    /// stays the same from one run to the next.
+    pub fn jitter(&self, station: &str) -> Duration {
+        let millis = self.jitter.as_millis() as u64;
+        if millis == 0 {
+            return Duration::ZERO;
+        }
+        let mut hasher = DefaultHasher::new();
+        station.to_ascii_uppercase().hash(&mut hasher);
+        Duration::from_millis(hasher.finish() % (millis + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 12, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cron() {
+        let next = |expression: &str, after: DateTime<Utc>| {
+            expression.parse::<Cron>().unwrap().next_after(after)
+        };
+        assert_eq!(
+            next("*/20 * * * *", time(30, 12, 5)),
+            Some(time(30, 12, 20))
+        );
+        assert_eq!(
+            next("*/20 * * * *", time(30, 12, 40)),
+            Some(time(30, 13, 0))
+        );
+        assert_eq!(next("0 * * * *", time(30, 23, 30)), Some(time(31, 0, 0)));
+        assert_eq!(
+            next("15 6,18 * * *", time(30, 7, 0)),
+            Some(time(30, 18, 15))
+        );
+        assert_eq!(
+            next("0 0 1 1 *", time(30, 12, 0)),
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+        );
+        // 2023-12-30 is a Saturday, 7 being Sunday
+        assert_eq!(
+            next("30 9 * * 1-5", time(30, 12, 0)),
+            Some(time(31, 9, 30) + ChronoDuration::days(1))
+        );
+        assert_eq!(next("0 12 * * 7", time(30, 12, 0)), Some(time(31, 12, 0)));
+        // Either day matches when both are restricted
+        assert_eq!(next("0 12 1 * 0", time(30, 12, 0)), Some(time(31, 12, 0)));
+        assert_eq!(next("0 0 30 2 *", time(30, 12, 0)), None);
+        assert_eq!(Cron::hourly(20), "20 * * * *".parse().unwrap());
+        assert_eq!(Cron::hourly(20).to_string(), "20 * * * *");
+
+        for invalid in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(invalid.parse::<Cron>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let coastal: Cron = "*/20 * * * *".parse().unwrap();
+        let schedule = Schedule::new(Cron::hourly(0))
+            .with_stations(["KSFO", "KOAK"], coastal.clone())
+            .with_jitter(Duration::from_secs(60));
+        assert_eq!(schedule.cron("ksfo"), &coastal);
+        assert_eq!(schedule.cron("KDEN"), &Cron::hourly(0));
+        assert!(schedule.jitter("KSFO") <= Duration::from_secs(60));
+        assert_eq!(schedule.jitter("KSFO"), schedule.jitter("ksfo"));
+        assert_eq!(
+            Schedule::new(Cron::hourly(0)).jitter("KSFO"),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Severity of the weather of observations, eg: for listing the stations
+//! with the worst weather first.
+
+use crate::metar::{Descriptor, Phenomenon, WeatherGroup};
+use crate::weather::WeatherInfo;
+
+/// Scale of the severity of weather phenomena, from the mildest to the
+/// most hazardous, so that observations can be compared and sorted.
+/// Unlike [Condition](crate::icon::Condition), which picks an icon, it
+/// ignores the sky and the wind.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// No significant weather
+    None,
+    /// Mist, haze, smoke, dust, sand or spray
+    Obscuration,
+    Fog,
+    Drizzle,
+    Rain,
+    Snow,
+    /// Hail or ice pellets
+    Hail,
+    /// Freezing drizzle or rain
+    FreezingRain,
+    /// Sandstorm, duststorm, dust whirls or volcanic ash
+    Sandstorm,
+    Thunderstorm,
+    Squalls,
+    /// Funnel cloud, tornado or waterspout
+    FunnelCloud,
+}
+
+impl Severity {
+    /// Severity of a present weather group, the greatest of its
+    /// phenomena's, raised by a thunderstorm or freezing descriptor.
+    pub fn of_group(group: &WeatherGroup) -> Severity {
+        let severity = group
+            .phenomena
+            .iter()
+            .map(|phenomenon| Severity::of_phenomenon(*phenomenon))
+            .max()
+            .unwrap_or(Severity::None);
+        match group.descriptor {
+            Some(Descriptor::Thunderstorm) => severity.max(Severity::Thunderstorm),
+            Some(Descriptor::Freezing)
+                if matches!(severity, Severity::Drizzle | Severity::Rain) =>
+            {
+                Severity::FreezingRain
+            }
+            _ => severity,
+        }
+    }
+
+    fn of_phenomenon(phenomenon: Phenomenon) -> Severity {
+        match phenomenon {
+            Phenomenon::Mist
+            | Phenomenon::Smoke
+            | Phenomenon::WidespreadDust
+            | Phenomenon::Sand
+            | Phenomenon::Haze
+            | Phenomenon::Spray => Severity::Obscuration,
+            Phenomenon::Fog => Severity::Fog,
+            Phenomenon::Drizzle => Severity::Drizzle,
+            Phenomenon::Rain | Phenomenon::UnknownPrecipitation => Severity::Rain,
+            Phenomenon::Snow | Phenomenon::SnowGrains | Phenomenon::IceCrystals => Severity::Snow,
+            Phenomenon::IcePellets | Phenomenon::Hail | Phenomenon::SmallHail => Severity::Hail,
+            Phenomenon::VolcanicAsh
+            | Phenomenon::DustWhirls
+            | Phenomenon::Sandstorm
+            | Phenomenon::Duststorm => Severity::Sandstorm,
+            Phenomenon::Squalls => Severity::Squalls,
+            Phenomenon::FunnelCloud => Severity::FunnelCloud,
+        }
+    }
+
+    /// Severity of the weather of a decoded file, from its wording. Eg:
+    /// "light freezing rain; mist"
+    fn of_weather_str(weather: &str) -> Severity {
+        const KEYWORDS: [(&str, Severity); 18] = [
+            ("funnel", Severity::FunnelCloud),
+            ("tornado", Severity::FunnelCloud),
+            ("squall", Severity::Squalls),
+            ("thunder", Severity::Thunderstorm),
+            ("storm", Severity::Sandstorm),
+            ("freezing rain", Severity::FreezingRain),
+            ("freezing drizzle", Severity::FreezingRain),
+            ("hail", Severity::Hail),
+            ("pellets", Severity::Hail),
+            ("snow", Severity::Snow),
+            ("rain", Severity::Rain),
+            ("drizzle", Severity::Drizzle),
+            ("fog", Severity::Fog),
+            ("mist", Severity::Obscuration),
+            ("haze", Severity::Obscuration),
+            ("smoke", Severity::Obscuration),
+            ("dust", Severity::Obscuration),
+            ("sand", Severity::Obscuration),
+        ];
+        let weather = weather.to_lowercase();
+        KEYWORDS
+            .iter()
+            .filter(|(keyword, _)| weather.contains(keyword))
+            .map(|(_, severity)| *severity)
+            .max()
+            .unwrap_or(Severity::None)
+    }
+}
+
+impl WeatherInfo {
+    /// Severity of the weather of the observation: the greatest of its
+    /// present weather groups, or of its decoded weather when its METAR
+    /// isn't available.
+    pub fn severity(&self) -> Severity {
+        match self.weather_groups() {
+            [] => self
+                .weather
+                .as_deref()
+                .map_or(Severity::None, Severity::of_weather_str),
+            groups => groups
+                .iter()
+                .map(Severity::of_group)
+                .max()
+                .unwrap_or(Severity::None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metar::parse_metar;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+
+    #[test]
+    fn test_severity() {
+        let severity = |metar: &str| {
+            let (_, metar) = parse_metar(metar).unwrap();
+            metar.weather.iter().map(Severity::of_group).max()
+        };
+        assert_eq!(severity("KSFO 301256Z 10SM CLR"), None);
+        assert_eq!(severity("KSFO 301256Z 3SM BR"), Some(Severity::Obscuration));
+        assert_eq!(severity("KSFO 301256Z 3SM -RA BR"), Some(Severity::Rain));
+        assert_eq!(
+            severity("KSFO 301256Z 3SM FZRA"),
+            Some(Severity::FreezingRain)
+        );
+        assert_eq!(
+            severity("KSFO 301256Z 3SM TSRA"),
+            Some(Severity::Thunderstorm)
+        );
+        assert_eq!(
+            severity("KSFO 301256Z 3SM +FC"),
+            Some(Severity::FunnelCloud)
+        );
+
+        let (_, mut vogo) = parse_weather(&vogo_report("1230")).unwrap();
+        assert_eq!(vogo.severity(), Severity::Obscuration);
+        vogo.metar = None;
+        vogo.weather = Some("light freezing rain; mist".into());
+        assert_eq!(vogo.severity(), Severity::FreezingRain);
+        vogo.weather = None;
+        assert_eq!(vogo.severity(), Severity::None);
+    }
+}
@@ -0,0 +1,256 @@
+//! Destinations of polled observations, fed by
+//! [crate::hub::WeatherHub::spawn_sink].
+
+#[cfg(feature = "webhook")]
+use crate::retry::RetryPolicy;
+use crate::weather::WeatherInfo;
+use std::io::{self, Write};
+
+/// Destination of observations, eg: a log file or a message queue.
+/// Implement it for writing observations elsewhere.
+pub trait Sink: Send {
+    /// Writes the observation of a station.
+    fn write(&mut self, station: &str, info: &WeatherInfo) -> io::Result<()>;
+
+    /// Flushes the observations written so far. Called after each
+    /// observation by the hub.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for Box<S> {
+    fn write(&mut self, station: &str, info: &WeatherInfo) -> io::Result<()> {
+        (**self).write(station, info)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+/// Writes every observation to each of the sinks in turn, stopping at
+/// the first failure.
+impl Sink for Vec<Box<dyn Sink>> {
+    fn write(&mut self, station: &str, info: &WeatherInfo) -> io::Result<()> {
+        self.iter_mut()
+            .try_for_each(|sink| sink.write(station, info))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.iter_mut().try_for_each(|sink| sink.flush())
+    }
+}
+
+/// Writes the station and one line summary of each observation, as
+/// given by the `Display` implementation of [WeatherInfo]. Eg: `VOBL
+/// 23°C →8kt 7mi SCT 1017hPa`
+pub struct TextSink<W> {
+    writer: W,
+}
+
+impl<W: Write + Send> TextSink<W> {
+    /// Eg: `TextSink::new(std::io::stdout())`
+    pub fn new(writer: W) -> Self {
+        TextSink { writer }
+    }
+}
+
+impl<W: Write + Send> Sink for TextSink<W> {
+    fn write(&mut self, station: &str, info: &WeatherInfo) -> io::Result<()> {
+        writeln!(self.writer, "{} {}", station, info)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Writes each observation as a JSON object on its own line, along with
+/// its station: `{"station":"VOBL","observation":{...}}`. Requires the
+/// `serde` feature.
+#[cfg(feature = "serde")]
+pub struct JsonLinesSink<W> {
+    writer: W,
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write + Send> JsonLinesSink<W> {
+    /// Eg: a file opened in append mode
+    pub fn new(writer: W) -> Self {
+        JsonLinesSink { writer }
+    }
+}
+
+/// Observation along with its station, as written by the JSON sinks.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct Record<'a> {
+    station: &'a str,
+    observation: &'a WeatherInfo,
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write + Send> Sink for JsonLinesSink<W> {
+    fn write(&mut self, station: &str, info: &WeatherInfo) -> io::Result<()> {
+        let record = Record {
+            station,
+            observation: info,
+        };
+        serde_json::to_writer(&mut self.writer, &record)?;
+        writeln!(self.writer)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Header holding the signature of a webhook's body.
+#[cfg(feature = "webhook")]
+pub const SIGNATURE_HEADER: &str = "x-weathernoaa-signature-256";
+
+/// POSTs each observation to a URL as a JSON object, in the format of
+/// [JsonLinesSink]. Requires the `webhook` feature.
+///
+/// With a secret, the body is signed with HMAC-SHA256 and the signature
+/// sent in the [SIGNATURE_HEADER] header, as `sha256=` followed by its
+/// hexadecimal digest, for the receiver to check where it comes from.
+/// Failed requests are retried according to the retry policy, by
+/// default [RetryPolicy::default].
+#[cfg(feature = "webhook")]
+pub struct WebhookSink {
+    url: String,
+    secret: Option<Vec<u8>>,
+    retry_policy: RetryPolicy,
+    // Created on first use, since building a blocking client inside an
+    // async runtime panics.
+    client: Option<reqwest::blocking::Client>,
+}
+
+#[cfg(feature = "webhook")]
+impl WebhookSink {
+    pub fn new(url: impl Into<String>) -> Self {
+        WebhookSink {
+            url: url.into(),
+            secret: None,
+            retry_policy: RetryPolicy::default(),
+            client: None,
+        }
+    }
+
+    /// Signs the requests with `secret`.
+    pub fn with_secret(mut self, secret: impl Into<Vec<u8>>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    fn post(&mut self, body: &[u8]) -> Result<(), crate::weather::WeatherError> {
+        let client = self
+            .client
+            .get_or_insert_with(reqwest::blocking::Client::new);
+        let mut request = client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, body));
+        }
+        let res = request.send()?;
+        let url = res.url().clone();
+        crate::weather::check_blocking_status(res, &url)?;
+        Ok(())
+    }
+}
+
+/// Signature of a webhook's body, eg: sha256=5d1f...
+#[cfg(feature = "webhook")]
+fn signature(secret: &[u8], body: &[u8]) -> String {
+    let mac = hmac_sha256::HMAC::mac(body, secret);
+    let hex: String = mac.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(feature = "webhook")]
+impl Sink for WebhookSink {
+    fn write(&mut self, station: &str, info: &WeatherInfo) -> io::Result<()> {
+        let body = serde_json::to_vec(&Record {
+            station,
+            observation: info,
+        })?;
+        let mut attempt = 0;
+        loop {
+            match self.post(&body) {
+                Ok(()) => return Ok(()),
+                Err(error) => match self.retry_policy.delay(attempt, &error) {
+                    Some(wait) => std::thread::sleep(wait),
+                    None => return Err(io::Error::other(error)),
+                },
+            }
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+
+    #[test]
+    fn test_text_sink() {
+        let (_, info) = parse_weather(&vogo_report("1230")).unwrap();
+        let mut sink = TextSink::new(Vec::new());
+        sink.write("VOGO", &info).unwrap();
+        assert_eq!(
+            String::from_utf8(sink.writer).unwrap(),
+            format!("VOGO {}\n", info)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_lines_sink() {
+        let (_, info) = parse_weather(&vogo_report("1230")).unwrap();
+        let mut sink = JsonLinesSink::new(Vec::new());
+        sink.write("VOGO", &info).unwrap();
+        sink.write("VOGO", &info).unwrap();
+        let out = String::from_utf8(sink.writer).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"station":"VOGO","observation":{"#));
+    }
+
+    #[cfg(feature = "webhook")]
+    #[test]
+    fn test_webhook_sink() {
+        use crate::weather::tests::serve;
+
+        let (_, info) = parse_weather(&vogo_report("1230")).unwrap();
+        let (port, server) = serve(vec![String::new()]);
+        let mut sink = WebhookSink::new(format!("http://127.0.0.1:{}/hook", port))
+            .with_secret("secret")
+            .with_retry_policy(RetryPolicy::none());
+        sink.write("VOGO", &info).unwrap();
+        let request = server.join().unwrap().concat();
+        assert!(request.starts_with("POST /hook HTTP/1.1"));
+        let body = serde_json::to_vec(&Record {
+            station: "VOGO",
+            observation: &info,
+        })
+        .unwrap();
+        let header = format!("{}: {}", SIGNATURE_HEADER, signature(b"secret", &body));
+        assert!(request.contains(&header));
+        assert!(request.ends_with(std::str::from_utf8(&body).unwrap()));
+        assert_eq!(
+            signature(b"key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Statistics over series of observations of a station, eg: the
+//! observations of a period exported with the CLI.
+
+use crate::weather::WeatherInfo;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+/// Base temperature of degree days in celsius most commonly used in
+/// Europe. The United States use 65 °F, that is 18.3 °C.
+pub const DEGREE_DAY_BASE_CELSIUS: f64 = 18.0;
+
+/// Heating and cooling degree days of a day.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DailyDegreeDays {
+    /// Day in UTC
+    pub date: NaiveDate,
+    /// Mean temperature of the day in celsius, as the average of its
+    /// lowest and highest temperatures, rounded to a tenth of a degree
+    pub mean_celsius: f64,
+    /// How far below the base the mean temperature was
+    pub heating: f64,
+    /// How far above the base the mean temperature was
+    pub cooling: f64,
+}
+
+/// Heating and cooling degree days accumulated over the days of a series
+/// of observations, which tell the energy needed for heating and
+/// cooling buildings. More details
+/// [here](https://en.wikipedia.org/wiki/Heating_degree_day).
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DegreeDays {
+    /// Base temperature in celsius, at which neither heating nor cooling
+    /// is needed
+    pub base_celsius: f64,
+    /// Days having observations, in chronological order
+    pub days: Vec<DailyDegreeDays>,
+    /// Sum of the heating degree days
+    pub heating: f64,
+    /// Sum of the cooling degree days
+    pub cooling: f64,
+}
+
+impl DegreeDays {
+    /// Degree days of the observations, in any order, whose days are in
+    /// UTC. Observations whose time isn't known are left out. A day
+    /// with only part of its observations, such as the first or last
+    /// day of a period, has degree days estimated from those. Values are
+    /// rounded to a tenth of a degree, the precision of the observations.
+    pub fn new(observations: &[WeatherInfo], base_celsius: f64) -> Self {
+        let mut extremes: BTreeMap<NaiveDate, (f64, f64)> = BTreeMap::new();
+        for info in observations {
+            let Some(time) = info.weather_time.to_datetime() else {
+                continue;
+            };
+            let celsius = info.temperature.celsius;
+            extremes
+                .entry(time.date_naive())
+                .and_modify(|(lowest, highest)| {
+                    *lowest = lowest.min(celsius);
+                    *highest = highest.max(celsius);
+                })
+                .or_insert((celsius, celsius));
+        }
+        let days: Vec<DailyDegreeDays> = extremes
+            .into_iter()
+            .map(|(date, (lowest, highest))| {
+                let mean_celsius = round_tenth((lowest + highest) / 2.0);
+                DailyDegreeDays {
+                    date,
+                    mean_celsius,
+                    heating: round_tenth((base_celsius - mean_celsius).max(0.0)),
+                    cooling: round_tenth((mean_celsius - base_celsius).max(0.0)),
+                }
+            })
+            .collect();
+        DegreeDays {
+            base_celsius,
+            heating: round_tenth(days.iter().map(|day| day.heating).sum()),
+            cooling: round_tenth(days.iter().map(|day| day.cooling).sum()),
+            days,
+        }
+    }
+}
+
+/// Rounds away the noise of floating point arithmetic, eg: 3.6 rather
+/// than 3.5999999999999996
+fn round_tenth(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+    use crate::weather::Temperature;
+
+    #[test]
+    fn test_degree_days() {
+        let (_, vogo) = parse_weather(&vogo_report("1230")).unwrap();
+        let observation = |day: u8, celsius: f64| {
+            let mut info = vogo.clone();
+            info.weather_time.day = day;
+            info.temperature = Temperature::from_celsius(celsius);
+            info
+        };
+        let degree_days = DegreeDays::new(
+            &[
+                observation(29, 10.0),
+                observation(29, 16.0),
+                observation(30, 20.0),
+                observation(30, 24.0),
+                observation(31, 18.0),
+            ],
+            DEGREE_DAY_BASE_CELSIUS,
+        );
+        let days: Vec<(u32, f64, f64)> = degree_days
+            .days
+            .iter()
+            .map(|day| (chrono::Datelike::day(&day.date), day.heating, day.cooling))
+            .collect();
+        assert_eq!(days, [(29, 5.0, 0.0), (30, 0.0, 4.0), (31, 0.0, 0.0)]);
+        assert_eq!((degree_days.heating, degree_days.cooling), (5.0, 4.0));
+        assert_eq!(DegreeDays::new(&[], 18.0).days, []);
+
+        // Tenths of a degree, as from the T group of the remarks
+        let degree_days = DegreeDays::new(
+            &[
+                observation(29, 10.1),
+                observation(29, 18.7),
+                observation(30, 15.3),
+                observation(30, 16.1),
+                observation(31, 20.3),
+                observation(31, 21.5),
+            ],
+            DEGREE_DAY_BASE_CELSIUS,
+        );
+        let days: Vec<(f64, f64, f64)> = degree_days
+            .days
+            .iter()
+            .map(|day| (day.mean_celsius, day.heating, day.cooling))
+            .collect();
+        assert_eq!(days, [(14.4, 3.6, 0.0), (15.7, 2.3, 0.0), (20.9, 0.0, 2.9)]);
+        assert_eq!((degree_days.heating, degree_days.cooling), (5.9, 2.9));
+    }
+}
@@ -0,0 +1,119 @@
+//! Checks of the values of observations against physical limits, for
+//! catching the garbage sent by malfunctioning automated stations.
+
+use crate::weather::{ParseIssue, WeatherError, WeatherInfo};
+use std::ops::RangeInclusive;
+
+/// Plausible temperatures and dewpoints in celsius. The coldest and
+/// hottest temperatures ever recorded are -89.2 °C and 56.7 °C.
+pub const TEMPERATURE_RANGE: RangeInclusive<f64> = -95.0..=60.0;
+
+/// Plausible pressures in hectopascals.
+pub const PRESSURE_RANGE: RangeInclusive<i16> = 850..=1100;
+
+/// Plausible relative humidities in percent.
+pub const HUMIDITY_RANGE: RangeInclusive<f64> = 0.0..=100.0;
+
+/// What to do with observations holding implausible values, see
+/// [crate::weather::NoaaApp::with_validation].
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Validation {
+    /// The values aren't checked
+    #[default]
+    Off,
+    /// The implausible values are recorded in the
+    /// [warnings](WeatherInfo::warnings) of the observation
+    Warn,
+    /// Observations with an implausible value fail with
+    /// [WeatherError::Implausible]
+    Reject,
+}
+
+impl Validation {
+    /// Checks an observation according to the policy.
+    pub fn apply(self, mut info: WeatherInfo) -> Result<WeatherInfo, WeatherError> {
+        if self == Validation::Off {
+            return Ok(info);
+        }
+        let implausible = info.implausible_values();
+        match implausible.first() {
+            Some(&ParseIssue::Implausible { field, value }) if self == Validation::Reject => {
+                Err(WeatherError::Implausible { field, value })
+            }
+            _ => {
+                info.warnings.extend(implausible);
+                Ok(info)
+            }
+        }
+    }
+}
+
+impl WeatherInfo {
+    /// The values of the observation which are physically implausible,
+    /// as [ParseIssue::Implausible].
+    pub fn implausible_values(&self) -> Vec<ParseIssue> {
+        let checks = [
+            (
+                "temperature",
+                self.temperature.celsius,
+                TEMPERATURE_RANGE.contains(&self.temperature.celsius),
+            ),
+            (
+                "dew point",
+                self.dewpoint.celsius,
+                TEMPERATURE_RANGE.contains(&self.dewpoint.celsius),
+            ),
+            (
+                "relative humidity",
+                self.relative_humidity,
+                HUMIDITY_RANGE.contains(&self.relative_humidity),
+            ),
+            (
+                "pressure",
+                f64::from(self.pressure),
+                PRESSURE_RANGE.contains(&self.pressure),
+            ),
+        ];
+        checks
+            .iter()
+            .filter(|(_, _, plausible)| !plausible)
+            .map(|&(field, value, _)| ParseIssue::Implausible { field, value })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+
+    #[test]
+    fn test_validation() {
+        let (_, mut info) = parse_weather(&vogo_report("1230")).unwrap();
+        assert_eq!(info.implausible_values(), []);
+        assert_eq!(Validation::Reject.apply(info.clone()).unwrap(), info);
+
+        info.relative_humidity = 104.0;
+        info.pressure = 1200;
+        assert_eq!(Validation::Off.apply(info.clone()).unwrap(), info);
+        let warned = Validation::Warn.apply(info.clone()).unwrap();
+        assert_eq!(
+            warned.warnings,
+            [
+                ParseIssue::Implausible {
+                    field: "relative humidity",
+                    value: 104.0
+                },
+                ParseIssue::Implausible {
+                    field: "pressure",
+                    value: 1200.0
+                },
+            ]
+        );
+        assert_eq!(
+            Validation::Reject.apply(info).unwrap_err().to_string(),
+            "Implausible relative humidity: 104"
+        );
+    }
+}
@@ -1,3 +1,24 @@
+use crate::change::ChangeDetector;
+use crate::directory::{
+    parse_coordinate, DirectoryCache, StationDirectory, StationInfo, DIRECTORY_URL,
+};
+use crate::format::{humanize_duration, FormatOptions};
+use crate::geo::Point;
+use crate::icon::IconSet;
+use crate::metar::{
+    parse_archive_file, parse_cycle_file, parse_metar, parse_station_file, RawMetar, WeatherGroup,
+    MPH_PER_KNOT,
+};
+use crate::middleware::Middleware;
+use crate::normals::{normals_url, ClimateNormals};
+#[cfg(feature = "replay")]
+use crate::replay::Cassette;
+use crate::retry::{RetryEvent, RetryObserver, RetryPolicy};
+use crate::schedule::Schedule;
+use crate::validation::Validation;
+use chrono::{DateTime, NaiveDate, TimeZone, Timelike, Utc};
+use futures::future::{self, Either};
+use futures::stream::{self, Stream, StreamExt};
 use nom::bytes::complete::tag;
 use nom::bytes::complete::{tag_no_case, take_till};
 use nom::character::complete::space1;
@@ -5,15 +26,27 @@ use nom::character::complete::{char, newline};
 use nom::combinator::opt;
 use nom::error::*;
 use nom::multi::{many0, many1};
+use nom::sequence::preceded;
 use nom::IResult;
 use nom::{branch::alt, combinator::map_res};
-use reqwest::Client;
+use reqwest::header::{HeaderMap, ETAG, LAST_MODIFIED, RETRY_AFTER};
+use reqwest::{Client, Method, Request, Response, StatusCode, Url};
 use std::char;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::pin::pin;
+#[cfg(feature = "blocking")]
+use std::sync::OnceLock;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use std::{convert::TryFrom, str::FromStr};
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 /// Weather information for a particular station.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeatherInfo {
     /// Weather station code. More information about it is present in the [Station metadata page](https://www.ncdc.noaa.gov/data-access/land-based-station-data/station-metadata).
     pub station: Option<Station>,
@@ -35,10 +68,235 @@ pub struct WeatherInfo {
     pub relative_humidity: f64,
     /// Pressure in Hectopascal Pressure Unit
     pub pressure: i16,
+    /// The METAR the observation was decoded from, when available
+    pub metar: Option<RawMetar>,
+    /// Where the values of the observation come from
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub provenance: Provenance,
+    /// Problems met while parsing the report leniently, see
+    /// [NoaaApp::with_lenient_parsing]. Empty otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty", skip_deserializing)
+    )]
+    pub warnings: Vec<ParseIssue>,
+}
+
+/// Source of a value of an observation.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Source {
+    /// The decoded text of the report
+    #[default]
+    Decoded,
+    /// The main body of the METAR
+    Metar,
+    /// The remarks of the METAR, eg: the temperature in tenths of a
+    /// degree of the `T` group
+    MetarRemarks,
+    /// Computed from other values, eg: the relative humidity of a METAR
+    /// from its temperature and dewpoint
+    Derived,
+    /// Not reported, a default was used, eg: a calm wind for a METAR
+    /// without wind group
+    Defaulted,
+}
+
+/// A field of [WeatherInfo], as a key of [Provenance::fields].
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Field {
+    Time,
+    Wind,
+    Visibility,
+    SkyCondition,
+    Weather,
+    Temperature,
+    Dewpoint,
+    RelativeHumidity,
+    Pressure,
+}
+
+/// Sources of the main values of an observation. When both the decoded
+/// text and the METAR are available, the more precise one is used.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Provenance {
+    pub temperature: Source,
+    pub dewpoint: Source,
+    pub wind: Source,
+    pub pressure: Source,
+    /// Where the report was fetched from
+    pub origin: Origin,
+    /// Source of every field, as given by [WeatherInfo::field_sources].
+    /// Only filled when enabled with [NoaaApp::with_field_provenance].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub fields: Option<BTreeMap<Field, Source>>,
+}
+
+/// Where the report of an observation was fetched from.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Origin {
+    /// The files of the station, eg: its decoded file
+    #[default]
+    Station,
+    /// NOAA's hourly cycle files, covering the last [HISTORY_HOURS]
+    /// hours
+    Cycle,
+    /// The archive of the Aviation Weather Center, which
+    /// [NoaaApp::get_history] falls back to for older observations
+    Archive,
+    /// A third party archive, see [crate::iem]
+    Import,
+}
+
+/// Compact one line summary of the observation, as given by
+/// [FormatOptions::oneline]. Eg: 12°C ↗14kt 10mi OVC 1017hPa. For other
+/// units, see [crate::format::oneline_with].
+impl fmt::Display for WeatherInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&FormatOptions::default().oneline(self, IconSet::Ascii, None))
+    }
+}
+
+impl WeatherInfo {
+    /// Takes the temperature and dewpoint from the `T` group of the
+    /// METAR's remarks, which are in tenths of a degree whereas the
+    /// decoded text may round them to whole degrees.
+    fn reconcile(&mut self) {
+        let Some((celsius, dewpoint)) = self.metar.as_ref().and_then(RawMetar::precise_temperature)
+        else {
+            return;
+        };
+        self.temperature = Temperature::from_celsius(celsius);
+        self.provenance.temperature = Source::MetarRemarks;
+        if let Some(dewpoint) = dewpoint {
+            self.dewpoint = Temperature::from_celsius(dewpoint);
+            self.provenance.dewpoint = Source::MetarRemarks;
+        }
+    }
+
+    /// Source of each field of the observation, telling the measured
+    /// values from the derived and defaulted ones.
+    pub fn field_sources(&self) -> BTreeMap<Field, Source> {
+        let missing = |name: &str| {
+            self.warnings
+                .iter()
+                .any(|issue| matches!(issue, ParseIssue::Missing { field } if *field == name))
+        };
+        // Observations decoded from the METAR alone, rather than from the
+        // decoded text with some values missing
+        let metar_only = self.provenance.wind == Source::Metar && !missing("wind");
+        let from_metar = |name: &str| metar_only || missing(name);
+        let decoded_or_metar = |name: &str| {
+            if from_metar(name) {
+                Source::Metar
+            } else {
+                Source::Decoded
+            }
+        };
+        let calm_default = self
+            .metar
+            .as_ref()
+            .is_some_and(|metar| metar.wind.is_none());
+        let wind = match self.provenance.wind {
+            Source::Metar if calm_default => Source::Defaulted,
+            source => source,
+        };
+        let relative_humidity = if from_metar("relative humidity") {
+            Source::Derived
+        } else {
+            Source::Decoded
+        };
+        let optional = if metar_only {
+            Source::Metar
+        } else {
+            Source::Decoded
+        };
+        BTreeMap::from([
+            (Field::Time, decoded_or_metar("time")),
+            (Field::Wind, wind),
+            (Field::Visibility, decoded_or_metar("visibility")),
+            (Field::SkyCondition, optional),
+            (Field::Weather, optional),
+            (Field::Temperature, self.provenance.temperature),
+            (Field::Dewpoint, self.provenance.dewpoint),
+            (Field::RelativeHumidity, relative_humidity),
+            (Field::Pressure, self.provenance.pressure),
+        ])
+    }
+
+    /// The present weather groups of the METAR the observation was
+    /// decoded from. Empty when the METAR isn't available.
+    pub fn weather_groups(&self) -> &[WeatherGroup] {
+        self.metar
+            .as_ref()
+            .map_or(&[], |metar| metar.weather.as_slice())
+    }
+
+    /// Prevailing visibility in meters. Taken from the METAR when
+    /// available, which is exact for international stations reporting
+    /// in meters, otherwise converted from the miles of the decoded
+    /// text. `None` when only a bound is known, eg: greater than 7
+    /// mile(s). With CAVOK, this is the 10 km lower bound.
+    pub fn visibility_meters(&self) -> Option<f64> {
+        if let Some(metar) = &self.metar {
+            if let Some(visibility) = metar.visibility {
+                return Some(visibility.meters());
+            }
+            if metar.cavok {
+                return Some(10_000.0);
+            }
+        }
+        let miles: f64 = self
+            .visibility
+            .trim_end_matches(":0")
+            .strip_suffix(" mile(s)")?
+            .parse()
+            .ok()?;
+        Some(miles * 1609.344)
+    }
+
+    /// Height of the ceiling above ground level in feet, from the
+    /// METAR's cloud layers. See [RawMetar::ceiling_ft].
+    pub fn ceiling_ft(&self) -> Option<u32> {
+        self.metar.as_ref()?.ceiling_ft()
+    }
+
+    /// Estimate of the height of the base of cumulus clouds above ground
+    /// level in feet, from the spread between the temperature and the
+    /// dewpoint: 400 ft per degree celsius. Only an approximation, for
+    /// stations which don't report the height of the cloud layers.
+    pub fn estimated_cloud_base_ft(&self) -> f64 {
+        (self.temperature.celsius - self.dewpoint.celsius).max(0.0) * 400.0
+    }
+
+    /// Temperature below which frost forms, from the dewpoint using the
+    /// Magnus formula over ice. Only meaningful when the dewpoint is below
+    /// freezing, where it's a little warmer than the dewpoint.
+    pub fn frost_point(&self) -> Temperature {
+        let dewpoint = self.dewpoint.celsius;
+        let gamma = 17.625 * dewpoint / (243.04 + dewpoint);
+        let frost_point = 273.86 * gamma / (22.587 - gamma);
+        Temperature::from_celsius((frost_point * 10.0).round() / 10.0)
+    }
+
+    /// Whether fog is likely to form: the air is close to saturation,
+    /// with a small dewpoint spread and a light wind not mixing it.
+    pub fn fog_risk(&self) -> bool {
+        self.temperature.celsius - self.dewpoint.celsius <= FOG_MAX_SPREAD_CELSIUS
+            && self.wind.knots <= FOG_MAX_WIND_KNOTS
+            && self.relative_humidity >= FOG_MIN_HUMIDITY
+    }
 }
 
 /// The timestamp of the weather data.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeatherTime {
     pub year: u16,
     pub month: u8,
@@ -46,17 +304,88 @@ pub struct WeatherTime {
     pub time: String,
 }
 
+impl WeatherTime {
+    /// The timestamp as a date time. `None` when the time isn't in the
+    /// `HHMM UTC` format of NOAA's files.
+    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
+        let time = self.time.strip_suffix(" UTC")?;
+        if time.len() != 4 || !time.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let date = NaiveDate::from_ymd_opt(self.year.into(), self.month.into(), self.day.into())?;
+        let datetime = date.and_hms_opt(time[..2].parse().ok()?, time[2..].parse().ok()?, 0)?;
+        Some(Utc.from_utc_datetime(&datetime))
+    }
+
+    /// The timestamp converted to the timezone `tz`, eg:
+    /// `chrono_tz::Europe::Berlin` or `chrono::Local`.
+    pub fn in_timezone<Tz: TimeZone>(&self, tz: &Tz) -> Option<DateTime<Tz>> {
+        Some(self.to_datetime()?.with_timezone(tz))
+    }
+
+    /// Time elapsed between the observation and `now`. Observations
+    /// timestamped after `now` have no age.
+    pub fn age(&self, now: DateTime<Utc>) -> Option<Duration> {
+        (now - self.to_datetime()?).to_std().ok()
+    }
+
+    /// Describes how long ago the observation was made, eg: 23 minutes
+    /// ago.
+    pub fn humanize(&self, now: DateTime<Utc>) -> Option<String> {
+        self.age(now).map(humanize_duration)
+    }
+}
+
+impl fmt::Display for WeatherTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{:02}-{:02} {}",
+            self.year, self.month, self.day, self.time
+        )
+    }
+}
+
 /// Enum representing the various errors that the library can return.
 #[derive(Error, Debug)]
 pub enum WeatherError {
     #[error("Error from request: `{0}`")]
     ReqwestError(reqwest::Error),
-    #[error("Error from Nom: `{0}`")]
-    NomError(nom::Err<nom::error::Error<String>>),
+    /// NOAA's server answered with an error status.
+    #[error("HTTP status {status} for {url}")]
+    Http {
+        status: StatusCode,
+        url: String,
+        /// Delay requested by the `Retry-After` header, usually sent
+        /// along with 429 and 503 statuses
+        retry_after: Option<Duration>,
+        /// Value of the `Last-Modified` header
+        last_modified: Option<SystemTime>,
+    },
+    /// The station's file couldn't be parsed.
+    #[error("Failed to parse the observation: {0}")]
+    Parse(ParseError),
+    /// The station's file has no observation, it's empty or the report
+    /// is `NIL`. The station isn't currently reporting.
+    #[error("Station {station} is not currently reporting")]
+    NoReport { station: String },
+    /// The observation has a physically implausible value, see
+    /// [NoaaApp::with_validation].
+    #[error("Implausible {field}: {value}")]
+    Implausible { field: &'static str, value: f64 },
+    /// The operation was cancelled through the token given to
+    /// [NoaaApp::with_cancellation].
+    #[error("The operation was cancelled")]
+    Cancelled,
+    /// The [cassette](crate::replay::Cassette) has no response to
+    /// replay for a request, or recording a response failed.
+    #[error("Cassette: {0}")]
+    Cassette(String),
 }
 
 /// Temperature in both celsius and Fahrenheit units.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Temperature {
     /// Temperature in celsius
     pub celsius: f64,
@@ -64,17 +393,33 @@ pub struct Temperature {
     pub fahrenheit: f64,
 }
 
+impl Temperature {
+    /// Creates a temperature from celsius, with the Fahrenheit value
+    /// rounded to one decimal place.
+    pub fn from_celsius(celsius: f64) -> Self {
+        Temperature {
+            celsius,
+            fahrenheit: (celsius * 18.0 + 320.0).round() / 10.0,
+        }
+    }
+}
+
 /// Weather station information
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Station {
     /// Station place
     pub place: String,
     /// Country where the station is located
     pub country: String,
+    /// Location of the station, when given. Eg: 36-04N 120-20E
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub location: Option<Point>,
 }
 
 /// Wind Information
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WindInfo {
     /// Cardinal direction. More details [here](https://en.wikipedia.org/wiki/Cardinal_direction)
     pub cardinal: String,
@@ -84,6 +429,30 @@ pub struct WindInfo {
     pub mph: f64,
     /// Speed in knots. More details [here](https://en.wikipedia.org/wiki/Knot_(unit))
     pub knots: f64,
+    /// Gust speed in Miles per hour, when gusting
+    pub gust_mph: Option<f64>,
+    /// Gust speed in knots, when gusting
+    pub gust_knots: Option<f64>,
+    /// Whether the wind direction is varying
+    pub direction_variable: bool,
+    /// Range of directions in degrees the wind is varying in, clockwise.
+    /// Only available from the METAR. Eg: (180, 240)
+    pub variable_range: Option<(u16, u16)>,
+}
+
+impl WeatherError {
+    /// HTTP status of the failed request, if the server answered.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            WeatherError::Http { status, .. } => Some(*status),
+            WeatherError::ReqwestError(err) => err.status(),
+            _ => None,
+        }
+    }
+
+    fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
 }
 
 impl From<reqwest::Error> for WeatherError {
@@ -92,9 +461,154 @@ impl From<reqwest::Error> for WeatherError {
     }
 }
 
-impl From<nom::Err<nom::error::Error<&str>>> for WeatherError {
-    fn from(error: nom::Err<nom::error::Error<&str>>) -> Self {
-        WeatherError::NomError(error.map(|e| nom::error::Error::new(e.input.to_string(), e.code)))
+impl From<ParseError> for WeatherError {
+    fn from(error: ParseError) -> Self {
+        WeatherError::Parse(error)
+    }
+}
+
+/// Where and why parsing an observation file failed.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ParseError {
+    /// Line of the failure, from 1
+    pub line: usize,
+    /// Column of the failure in characters, from 1
+    pub column: usize,
+    /// The line the parser failed on
+    pub snippet: String,
+    /// What the parser expected. Eg: 'Relative Humidity: '
+    pub expected: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected {} at line {}, column {}: {:?}",
+            self.expected, self.line, self.column, self.snippet
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// Locates the failure of a nom parser in its `input`.
+    pub fn new(input: &str, error: nom::Err<nom::error::Error<&str>>) -> Self {
+        let (rest, expected) = match &error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => (e.input, expected_kind(e.code)),
+            nom::Err::Incomplete(_) => ("", "more input"),
+        };
+        // The parsers only ever fail on a suffix of their input
+        let offset = input.len().saturating_sub(rest.len());
+        let offset = (0..=offset)
+            .rev()
+            .find(|&i| input.is_char_boundary(i))
+            .unwrap_or(0);
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            snippet: input[line_start..].lines().next().unwrap_or("").to_owned(),
+            expected: expected.to_owned(),
+        }
+    }
+
+    /// Locates the failure of [parse_weather] in its `input`, telling
+    /// the line expected by the decoded format.
+    pub fn decoded(input: &str, error: nom::Err<nom::error::Error<&str>>) -> Self {
+        let mut parse_error = ParseError::new(input, error);
+        // Failing at the end of the last line, which lacks its newline,
+        // means the next line is missing
+        if !parse_error.snippet.is_empty()
+            && parse_error.column == parse_error.snippet.chars().count() + 1
+            && input.lines().count() <= parse_error.line
+        {
+            parse_error.line += 1;
+            parse_error.column = 1;
+            parse_error.snippet.clear();
+        }
+        if let Some(expected) = expected_line(input, parse_error.line) {
+            parse_error.expected = expected;
+        }
+        parse_error
+    }
+}
+
+/// What a nom parser failing with `kind` was looking for.
+fn expected_kind(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Tag => "a known token",
+        ErrorKind::Char => "a delimiter",
+        ErrorKind::MapRes | ErrorKind::Digit => "a number",
+        ErrorKind::Space | ErrorKind::MultiSpace => "a space",
+        ErrorKind::Eof => "the end of the input",
+        _ => "a valid value",
+    }
+}
+
+/// Lines of the decoded format following the station and time lines,
+/// with an example of each and whether they're optional.
+const DECODED_LINES: [(&str, &str, bool); 8] = [
+    (
+        "Wind: ",
+        "Wind: from the NNW (340 degrees) at 16 MPH (14 KT):0",
+        false,
+    ),
+    ("Visibility: ", "Visibility: 1 mile(s):0", false),
+    ("Sky conditions: ", "Sky conditions: overcast", true),
+    ("Weather: ", "Weather: widespread dust", true),
+    ("Temperature: ", "Temperature: 64 F (18 C)", false),
+    ("Dew Point: ", "Dew Point: 42 F (6 C)", false),
+    ("Relative Humidity: ", "Relative Humidity: 45%", false),
+    (
+        "Pressure (altimeter): ",
+        "Pressure (altimeter): 29.65 in. Hg (1004 hPa)",
+        false,
+    ),
+];
+
+/// The line the decoded format expects at `line`, following the lines
+/// before it, which parsed.
+fn expected_line(input: &str, line: usize) -> Option<String> {
+    let lines: Vec<&str> = input.lines().collect();
+    match line {
+        1 => return Some("the station name".to_owned()),
+        2 => {
+            return Some(
+                "the time, like 'Mar 28, 2021 - 04:00 AM EDT / 2021.03.28 0800 UTC'".to_owned(),
+            )
+        }
+        _ => {}
+    }
+    // Index in DECODED_LINES of the first line which may come next
+    let mut next = 0;
+    for text in lines.iter().take(line - 1).skip(2) {
+        next += DECODED_LINES[next..]
+            .iter()
+            .position(|(prefix, _, _)| text.starts_with(prefix))?
+            + 1;
+    }
+    let candidates = DECODED_LINES.get(next..)?;
+    let end = candidates.iter().position(|(_, _, optional)| !optional)?;
+    let candidates = &candidates[..=end];
+    let text = lines.get(line - 1).copied().unwrap_or("");
+    match candidates
+        .iter()
+        .find(|(prefix, _, _)| text.starts_with(prefix))
+    {
+        Some((_, example, _)) => Some(format!("a line like '{}'", example)),
+        None => {
+            let prefixes: Vec<String> = candidates
+                .iter()
+                .map(|(prefix, _, _)| format!("'{}'", prefix))
+                .collect();
+            Some(match prefixes.split_last() {
+                Some((last, [])) => last.clone(),
+                Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+                None => return None,
+            })
+        }
     }
 }
 
@@ -108,56 +622,990 @@ fn parse_weather_str(i: &str) -> IResult<&str, Option<String>> {
     Ok((i, Some(weather.into())))
 }
 
+/// Default number of stations fetched in parallel by the batch API.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Default time [NoaaApp::get_weather_hedged] waits for a live
+/// observation before falling back to the cached one.
+pub const DEFAULT_HEDGE_DEADLINE: Duration = Duration::from_millis(300);
+
+/// Whether the observation of a [WeatherResult] was just fetched.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Freshness {
+    /// Fetched by the call
+    Live,
+    /// Fetched by a previous call, since the live fetch took longer than
+    /// the deadline or failed
+    Cached,
+}
+
+/// Observation returned by [NoaaApp::get_weather_hedged].
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeatherResult {
+    pub value: WeatherInfo,
+    pub freshness: Freshness,
+}
+
+/// Observation returned by [NoaaApp::get_fetched_weather], with what
+/// identifies the file it was parsed from, so that mirrors and caches can
+/// tell whether they already stored it and verify their copy.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FetchedWeather {
+    pub info: WeatherInfo,
+    /// `ETag` header of the file, when sent
+    pub etag: Option<String>,
+    /// `Last-Modified` header of the file, when sent
+    pub last_modified: Option<SystemTime>,
+    /// SHA-256 of the file as received, in lowercase hexadecimal
+    pub sha256: String,
+}
+
+/// Largest dewpoint spread at which [WeatherInfo::fog_risk] tells that
+/// fog is likely.
+pub const FOG_MAX_SPREAD_CELSIUS: f64 = 2.0;
+
+/// Strongest wind at which [WeatherInfo::fog_risk] tells that fog is
+/// likely.
+pub const FOG_MAX_WIND_KNOTS: f64 = 5.0;
+
+/// Lowest relative humidity at which [WeatherInfo::fog_risk] tells
+/// that fog is likely.
+pub const FOG_MIN_HUMIDITY: f64 = 90.0;
+
+/// Number of hours of observations available from NOAA's cycle files.
+pub const HISTORY_HOURS: u32 = 24;
+
+/// Number of hours of observations available from the archive of the
+/// Aviation Weather Center, which backfills the history beyond
+/// [HISTORY_HOURS].
+pub const ARCHIVE_HOURS: u32 = 360;
+
+#[derive(Clone)]
 pub struct NoaaApp {
     client: Client,
-    blocking_client: reqwest::blocking::Client,
+    metar_fallback: bool,
+    lenient: bool,
+    field_provenance: bool,
+    validation: Validation,
+    retry_policy: RetryPolicy,
+    retry_observer: Option<RetryObserver>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    directory_cache: Option<DirectoryCache>,
+    directory_refresh: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    cancellation: Option<CancellationToken>,
+    hedge_deadline: Duration,
+    /// Latest observations of [NoaaApp::get_weather_hedged], shared by
+    /// the clones
+    hedged: Arc<Mutex<HashMap<String, WeatherInfo>>>,
+    #[cfg(feature = "replay")]
+    cassette: Option<Arc<Cassette>>,
+    #[cfg(feature = "blocking")]
+    // Created on first use, since building a blocking client inside an
+    // async runtime panics.
+    blocking_client: OnceLock<reqwest::blocking::Client>,
+}
+
+impl Default for NoaaApp {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NoaaApp {
     pub fn new() -> Self {
-        NoaaApp {
-            client: Client::new(),
-            blocking_client: reqwest::blocking::Client::new(),
-        }
+        Self::with_client(Client::new())
     }
 
     pub fn with_client(client: Client) -> Self {
         NoaaApp {
             client,
-            blocking_client: reqwest::blocking::Client::new(),
+            metar_fallback: false,
+            lenient: false,
+            field_provenance: false,
+            validation: Validation::Off,
+            retry_policy: RetryPolicy::none(),
+            retry_observer: None,
+            middlewares: Vec::new(),
+            directory_cache: None,
+            directory_refresh: Arc::default(),
+            cancellation: None,
+            hedge_deadline: DEFAULT_HEDGE_DEADLINE,
+            hedged: Arc::default(),
+            #[cfg(feature = "replay")]
+            cassette: None,
+            #[cfg(feature = "blocking")]
+            blocking_client: OnceLock::new(),
+        }
+    }
+
+    /// When enabled, stations without a decoded observation file fall
+    /// back to the raw METAR file, which is decoded locally by
+    /// [parse_station_file]. Disabled by default.
+    pub fn with_metar_fallback(mut self, metar_fallback: bool) -> Self {
+        self.metar_fallback = metar_fallback;
+        self
+    }
+
+    /// When enabled, decoded reports are parsed by
+    /// [parse_weather_resilient], which skips the lines it can't parse
+    /// rather than failing, the problems met being kept in the
+    /// [warnings](WeatherInfo::warnings) of the observations. Useful
+    /// for tracking changes of NOAA's format. Disabled by default.
+    pub fn with_lenient_parsing(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// When enabled, the [provenance](Provenance::fields) of the
+    /// observations records the source of every field, for telling the
+    /// measurements from the estimates. Disabled by default, sparing
+    /// building a map per observation.
+    pub fn with_field_provenance(mut self, field_provenance: bool) -> Self {
+        self.field_provenance = field_provenance;
+        self
+    }
+
+    /// Checks the values of the observations against physical limits,
+    /// eg: a pressure outside of 850 to 1100 hPa, and warns about the
+    /// implausible ones or rejects the observations according to
+    /// `validation`. Not checked by default.
+    pub fn with_validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Validates an observation, recording the sources of its fields
+    /// when enabled.
+    fn finish(&self, mut info: WeatherInfo) -> Result<WeatherInfo, WeatherError> {
+        if self.field_provenance {
+            info.provenance.fields = Some(info.field_sources());
         }
+        self.validation.apply(info)
+    }
+
+    /// Parser of the decoded reports.
+    fn decoded_parser(&self) -> fn(&str) -> Result<WeatherInfo, ParseError> {
+        if self.lenient {
+            parse_lenient
+        } else {
+            parse_decoded
+        }
+    }
+
+    /// Retries failed requests according to `retry_policy`. Requests
+    /// aren't retried by default.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Calls `observer` before waiting to retry a request.
+    pub fn with_retry_observer<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&RetryEvent) + Send + Sync + 'static,
+    {
+        self.retry_observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Registers a [Middleware] run around every request, after the ones
+    /// already registered.
+    pub fn with_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware + 'static,
+    {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Keeps the station directory in `cache`. A cached directory older
+    /// than its maximum age is still used, while a fresh one is downloaded
+    /// in the background for the next calls.
+    pub fn with_directory_cache(mut self, cache: DirectoryCache) -> Self {
+        self.directory_cache = Some(cache);
+        self
+    }
+
+    /// Aborts the operations in flight once `token` is cancelled, eg:
+    /// when the user of a GUI navigates away. Requests, including the
+    /// waits before retrying them, fail with [WeatherError::Cancelled],
+    /// and the streams of [NoaaApp::observe_many] end. Since the app is
+    /// cheap to clone, give each operation its own token with
+    /// `app.clone().with_cancellation(token)`.
+    ///
+    /// Dropping the future of an operation cancels it as well, the token
+    /// is for the operations whose future isn't at hand, eg: the polling
+    /// of a [WeatherHub](crate::hub::WeatherHub).
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Time [NoaaApp::get_weather_hedged] waits for a live observation
+    /// before falling back to the cached one, by default
+    /// [DEFAULT_HEDGE_DEADLINE].
+    pub fn with_hedge_deadline(mut self, deadline: Duration) -> Self {
+        self.hedge_deadline = deadline;
+        self
+    }
+
+    /// Records the responses into `cassette`, or replays them from it
+    /// instead of sending the requests, depending on how it was
+    /// created. Requires the `replay` feature.
+    #[cfg(feature = "replay")]
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Runs `future` unless the operation is cancelled first.
+    async fn cancellable<T, E>(
+        &self,
+        future: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, WeatherError>
+    where
+        E: Into<WeatherError>,
+    {
+        let Some(token) = &self.cancellation else {
+            return future.await.map_err(Into::into);
+        };
+        match future::select(pin!(future), pin!(token.cancelled())).await {
+            Either::Left((result, _)) => result.map_err(Into::into),
+            Either::Right(_) => Err(WeatherError::Cancelled),
+        }
+    }
+
+    /// Completes once the app is cancelled, if ever.
+    fn cancelled(&self) -> impl Future<Output = ()> {
+        match &self.cancellation {
+            Some(token) => Either::Left(Box::pin(token.clone().cancelled_owned())),
+            None => Either::Right(future::pending()),
+        }
+    }
+
+    /// Sends a request, retrying it according to the retry policy. Error
+    /// statuses are turned into [WeatherError::Http].
+    async fn send(&self, method: Method, url: &str) -> Result<Response, WeatherError> {
+        self.cancellable(self.send_retrying(method, url)).await
+    }
+
+    async fn send_retrying(&self, method: Method, url: &str) -> Result<Response, WeatherError> {
+        let mut attempt = 0;
+        loop {
+            let result = match self.client.request(method.clone(), url).build() {
+                Ok(mut request) => {
+                    let mut headers = std::mem::take(request.headers_mut());
+                    self.before_send(&method, request.url_mut(), &mut headers);
+                    *request.headers_mut() = headers;
+                    let request_url = request.url().clone();
+                    match self.execute(url, request).await {
+                        Ok(res) => {
+                            self.after_response(&method, &request_url, res.status(), res.headers());
+                            check_status(res, &request_url)
+                        }
+                        Err(err) => Err(err),
+                    }
+                }
+                Err(err) => Err(err.into()),
+            };
+            match result {
+                Err(error) => match self.retry_delay(url, attempt, &error) {
+                    Some(wait) => tokio::time::sleep(wait).await,
+                    None => return Err(error),
+                },
+                ok => return ok,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Blocking version of [NoaaApp::send].
+    #[cfg(feature = "blocking")]
+    fn send_blocking(&self, url: &str) -> Result<reqwest::blocking::Response, WeatherError> {
+        let client = self
+            .blocking_client
+            .get_or_init(reqwest::blocking::Client::new);
+        let mut attempt = 0;
+        loop {
+            let result = match client.get(url).build() {
+                Ok(mut request) => {
+                    let mut headers = std::mem::take(request.headers_mut());
+                    self.before_send(&Method::GET, request.url_mut(), &mut headers);
+                    *request.headers_mut() = headers;
+                    let request_url = request.url().clone();
+                    match self.execute_blocking(client, url, request) {
+                        Ok(res) => {
+                            self.after_response(
+                                &Method::GET,
+                                &request_url,
+                                res.status(),
+                                res.headers(),
+                            );
+                            check_blocking_status(res, &request_url)
+                        }
+                        Err(err) => Err(err),
+                    }
+                }
+                Err(err) => Err(err.into()),
+            };
+            match result {
+                Err(error) => match self.retry_delay(url, attempt, &error) {
+                    Some(wait) => std::thread::sleep(wait),
+                    None => return Err(error),
+                },
+                ok => return ok,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends a request to `url`, through the cassette when there's one.
+    async fn execute(&self, url: &str, request: Request) -> Result<Response, WeatherError> {
+        #[cfg(feature = "replay")]
+        if let Some(cassette) = &self.cassette {
+            return cassette.execute(&self.client, url, request).await;
+        }
+        let _ = url;
+        Ok(self.client.execute(request).await?)
+    }
+
+    /// Blocking version of [NoaaApp::execute].
+    #[cfg(feature = "blocking")]
+    fn execute_blocking(
+        &self,
+        client: &reqwest::blocking::Client,
+        url: &str,
+        request: reqwest::blocking::Request,
+    ) -> Result<reqwest::blocking::Response, WeatherError> {
+        #[cfg(feature = "replay")]
+        if let Some(cassette) = &self.cassette {
+            return cassette.execute_blocking(client, url, request);
+        }
+        let _ = url;
+        Ok(client.execute(request)?)
+    }
+
+    fn before_send(&self, method: &Method, url: &mut Url, headers: &mut HeaderMap) {
+        for middleware in &self.middlewares {
+            middleware.before_send(method, url, headers);
+        }
+    }
+
+    fn after_response(&self, method: &Method, url: &Url, status: StatusCode, headers: &HeaderMap) {
+        for middleware in &self.middlewares {
+            middleware.after_response(method, url, status, headers);
+        }
+    }
+
+    /// Delay before retrying a failed request, notifying the observer.
+    fn retry_delay(&self, url: &str, attempt: u32, error: &WeatherError) -> Option<Duration> {
+        let wait = self.retry_policy.delay(attempt, error)?;
+        if let Some(observer) = &self.retry_observer {
+            observer(&RetryEvent {
+                url,
+                attempt,
+                wait,
+                error,
+            });
+        }
+        Some(wait)
     }
 
     /// This function retrieves the weather information from from the NOAA
     /// observations.
     pub async fn get_weather(&self, station_code: &str) -> Result<WeatherInfo, WeatherError> {
-        let noaa_url = format!(
-            "https://tgftp.nws.noaa.gov/data/observations/metar/decoded/{}.TXT",
-            station_code
-        );
-        let res = self.client.get(noaa_url).send().await?.error_for_status()?;
-        let body = res.text().await?;
-        let (_, result) = parse_weather(&body)?;
-        Ok(result)
+        Ok(self.get_fetched_weather(station_code).await?.info)
+    }
+
+    /// Same as [NoaaApp::get_weather], also returning the `ETag`,
+    /// `Last-Modified` and checksum of the file the observation was
+    /// parsed from.
+    pub async fn get_fetched_weather(
+        &self,
+        station_code: &str,
+    ) -> Result<FetchedWeather, WeatherError> {
+        let (res, parser) = match self.send(Method::GET, &decoded_url(station_code)).await {
+            Ok(res) => (res, self.decoded_parser()),
+            Err(err) if self.metar_fallback && err.is_not_found() => {
+                let res = self.send(Method::GET, &metar_url(station_code)).await?;
+                let parser: fn(&str) -> Result<WeatherInfo, ParseError> = parse_metar_file;
+                (res, parser)
+            }
+            Err(err) => return Err(err),
+        };
+        let header = |name| {
+            res.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        let etag = header(ETAG);
+        let last_modified =
+            header(LAST_MODIFIED).and_then(|value| httpdate::parse_http_date(&value).ok());
+        let bytes = self.cancellable(res.bytes()).await?;
+        let body = String::from_utf8_lossy(&bytes);
+        let info = parse_report(station_code, &body, parser)?;
+        Ok(FetchedWeather {
+            info: self.finish(info)?,
+            etag,
+            last_modified,
+            sha256: sha256_hex(&bytes),
+        })
+    }
+
+    /// Retrieves the weather information for multiple stations, with
+    /// at most `concurrency` requests in flight at the same time. The
+    /// results are returned in the same order as `station_codes`.
+    pub async fn get_weather_many<S: AsRef<str>>(
+        &self,
+        station_codes: &[S],
+        concurrency: usize,
+    ) -> Vec<(String, Result<WeatherInfo, WeatherError>)> {
+        stream::iter(station_codes)
+            .map(|code| async move {
+                let code = code.as_ref();
+                (code.to_owned(), self.get_weather(code).await)
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Polls the weather of multiple stations every `interval`, merged
+    /// into a single stream that never ends, unless the app is
+    /// [cancelled](NoaaApp::with_cancellation). An observation is only
+    /// yielded when it's newer than the previous one of its station,
+    /// while errors are yielded on every poll.
+    pub fn observe_many<S: AsRef<str>>(
+        &self,
+        station_codes: &[S],
+        interval: Duration,
+    ) -> impl Stream<Item = (String, Result<WeatherInfo, WeatherError>)> + '_ {
+        let feeds = station_codes.iter().map(|code| {
+            let state = (code.as_ref().to_owned(), None, Duration::ZERO);
+            Box::pin(stream::unfold(
+                state,
+                move |(code, mut last, mut wait): (String, Option<WeatherTime>, Duration)| async move {
+                    loop {
+                        tokio::time::sleep(wait).await;
+                        wait = interval;
+                        let result = self.get_weather(&code).await;
+                        if let Ok(info) = &result {
+                            if last.as_ref() == Some(&info.weather_time) {
+                                continue;
+                            }
+                            last = Some(info.weather_time.clone());
+                        }
+                        return Some(((code.clone(), result), (code, last, wait)));
+                    }
+                },
+            ))
+        });
+        stream::select_all(feeds).take_until(self.cancelled())
+    }
+
+    /// Polls like [NoaaApp::observe_many], but at the times of the cron
+    /// expression of each station in `schedule`, delayed by its jitter.
+    /// The first poll of a station is right away, after its jitter. The
+    /// feed of a station ends when its expression never matches again.
+    pub fn observe_scheduled<S: AsRef<str>>(
+        &self,
+        station_codes: &[S],
+        schedule: &Schedule,
+    ) -> impl Stream<Item = (String, Result<WeatherInfo, WeatherError>)> + '_ {
+        let feeds: Vec<_> = station_codes
+            .iter()
+            .map(|code| {
+                let code = code.as_ref().to_owned();
+                let cron = schedule.cron(&code).clone();
+                let jitter = schedule.jitter(&code);
+                let delay =
+                    chrono::Duration::from_std(jitter).unwrap_or_else(|_| chrono::Duration::zero());
+                let state = (code, None, Some(jitter));
+                Box::pin(stream::unfold(
+                    state,
+                    move |(code, mut last, wait): (
+                        String,
+                        Option<WeatherTime>,
+                        Option<Duration>,
+                    )| {
+                        let cron = cron.clone();
+                        async move {
+                            let mut wait = wait?;
+                            loop {
+                                tokio::time::sleep(wait).await;
+                                let result = self.get_weather(&code).await;
+                                // Slots missed while fetching are skipped
+                                let slot = Utc::now() - delay;
+                                let next = cron
+                                    .next_after(slot)
+                                    .map(|next| (next - slot).to_std().unwrap_or_default());
+                                if let Ok(info) = &result {
+                                    if last.as_ref() == Some(&info.weather_time) {
+                                        wait = next?;
+                                        continue;
+                                    }
+                                    last = Some(info.weather_time.clone());
+                                }
+                                return Some(((code.clone(), result), (code, last, next)));
+                            }
+                        }
+                    },
+                ))
+            })
+            .collect();
+        stream::select_all(feeds).take_until(self.cancelled())
     }
 
-    /// Same function as `get_weather` but a blocking version.
+    /// Polls like [NoaaApp::observe_many], but only yields an
+    /// observation when it changed significantly, according to
+    /// `detector`, from the previous one yielded for its station. The
+    /// first observation of each station is always yielded.
+    pub fn observe_changes<S: AsRef<str>>(
+        &self,
+        station_codes: &[S],
+        interval: Duration,
+        detector: ChangeDetector,
+    ) -> impl Stream<Item = (String, Result<WeatherInfo, WeatherError>)> + '_ {
+        let mut previous: HashMap<String, WeatherInfo> = HashMap::new();
+        self.observe_many(station_codes, interval)
+            .filter_map(move |(station, result)| {
+                let significant = match (&result, previous.get(&station)) {
+                    (Ok(info), Some(last)) => detector.is_significant(last, info),
+                    _ => true,
+                };
+                if let (Ok(info), true) = (&result, significant) {
+                    previous.insert(station.clone(), info.clone());
+                }
+                future::ready(significant.then_some((station, result)))
+            })
+    }
+
+    /// Retrieves the weather like [NoaaApp::get_weather], but within a
+    /// bounded time once a station was fetched, for frontends that must
+    /// render quickly, eg: status bars. When the live fetch takes longer
+    /// than the [hedge deadline](NoaaApp::with_hedge_deadline), or
+    /// fails, the previous observation of the station is returned
+    /// instead. The live fetch then carries on in the background,
+    /// updating the observation returned by the next calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside of a tokio runtime.
+    pub async fn get_weather_hedged(
+        &self,
+        station_code: &str,
+    ) -> Result<WeatherResult, WeatherError> {
+        let app = self.clone();
+        let code = station_code.to_owned();
+        let mut live = tokio::spawn(async move {
+            let result = app.get_weather(&code).await;
+            if let Ok(info) = &result {
+                let mut hedged = app.hedged.lock().unwrap_or_else(|err| err.into_inner());
+                hedged.insert(code, info.clone());
+            }
+            result
+        });
+        let cached = self
+            .hedged
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(station_code)
+            .cloned();
+        let Some(cached) = cached else {
+            return match live.await {
+                Ok(result) => result.map(|value| WeatherResult {
+                    value,
+                    freshness: Freshness::Live,
+                }),
+                // The task only ends early by panicking, or when the
+                // runtime shuts down
+                Err(err) => match err.try_into_panic() {
+                    Ok(panic) => std::panic::resume_unwind(panic),
+                    Err(_) => Err(WeatherError::Cancelled),
+                },
+            };
+        };
+        match tokio::time::timeout(self.hedge_deadline, &mut live).await {
+            Ok(Ok(Ok(value))) => Ok(WeatherResult {
+                value,
+                freshness: Freshness::Live,
+            }),
+            _ => Ok(WeatherResult {
+                value: cached,
+                freshness: Freshness::Cached,
+            }),
+        }
+    }
+
+    /// Retrieves the observations of a station over the last `hours`
+    /// hours, newest first, from NOAA's hourly cycle files. Each cycle
+    /// file holds the reports of every station, so this downloads a few
+    /// megabytes per hour. Those only cover the last [HISTORY_HOURS]
+    /// hours, older observations being fetched from the archive of the
+    /// Aviation Weather Center, up to [ARCHIVE_HOURS] hours. The
+    /// [origin](Provenance::origin) of each observation tells which.
+    pub async fn get_history(
+        &self,
+        station_code: &str,
+        hours: u32,
+    ) -> Result<Vec<WeatherInfo>, WeatherError> {
+        let now = Utc::now();
+        let hours = hours.clamp(1, ARCHIVE_HOURS);
+        let cycles = hours.min(HISTORY_HOURS);
+        let bodies: Vec<Result<String, WeatherError>> = stream::iter(0..cycles)
+            .map(|ago| async move {
+                let hour = (now - chrono::Duration::hours(i64::from(ago))).hour();
+                let res = self.send(Method::GET, &cycle_url(hour)).await?;
+                self.cancellable(res.text()).await
+            })
+            .buffer_unordered(DEFAULT_CONCURRENCY)
+            .collect()
+            .await;
+        // Cycle files are overwritten daily, so the current hour's file
+        // may still hold reports of the previous day.
+        let since = now - chrono::Duration::hours(i64::from(hours));
+        let mut history = vec![];
+        for body in bodies {
+            history.extend(
+                parse_cycle_file(&body?, station_code)
+                    .into_iter()
+                    .filter(|info| info.weather_time.to_datetime().is_some_and(|t| t > since)),
+            );
+        }
+        if hours > HISTORY_HOURS {
+            let res = self
+                .send(Method::GET, &archive_url(station_code, hours))
+                .await?;
+            history.extend(
+                parse_archive_file(&self.cancellable(res.text()).await?, now)
+                    .into_iter()
+                    .filter(|info| info.weather_time.to_datetime().is_some_and(|t| t > since)),
+            );
+        }
+        // The sort being stable, the observations of the cycle files,
+        // which come first, are kept over the same ones of the archive.
+        history.sort_by_key(|info| std::cmp::Reverse(info.weather_time.to_datetime()));
+        history.dedup_by(|a, b| a.weather_time == b.weather_time);
+        if self.field_provenance {
+            for info in &mut history {
+                info.provenance.fields = Some(info.field_sources());
+            }
+        }
+        Ok(history)
+    }
+
+    /// The decoded observation file of a station, unmodified. Eg:
+    /// https://tgftp.nws.noaa.gov/data/observations/metar/decoded/VOBL.TXT
+    pub async fn get_decoded_report(&self, station_code: &str) -> Result<String, WeatherError> {
+        let res = self.send(Method::GET, &decoded_url(station_code)).await?;
+        self.cancellable(res.text()).await
+    }
+
+    /// The raw METAR file of a station, unmodified. Eg:
+    /// https://tgftp.nws.noaa.gov/data/observations/metar/stations/VOBL.TXT
+    pub async fn get_metar_report(&self, station_code: &str) -> Result<String, WeatherError> {
+        let res = self.send(Method::GET, &metar_url(station_code)).await?;
+        self.cancellable(res.text()).await
+    }
+
+    /// The raw TAF file of a station, its latest terminal aerodrome
+    /// forecast, unmodified. Only airports issue those. Eg:
+    /// https://tgftp.nws.noaa.gov/data/forecasts/taf/stations/KSFO.TXT
+    pub async fn get_taf_report(&self, station_code: &str) -> Result<String, WeatherError> {
+        let res = self.send(Method::GET, &taf_url(station_code)).await?;
+        self.cancellable(res.text()).await
+    }
+
+    /// Time at which the decoded observation file of a station was last
+    /// updated, taken from the `Last-Modified` header. Only the headers
+    /// are requested, so this works even if the observation itself
+    /// can't be parsed.
+    pub async fn get_last_modified(
+        &self,
+        station_code: &str,
+    ) -> Result<Option<SystemTime>, WeatherError> {
+        let res = self.send(Method::HEAD, &decoded_url(station_code)).await?;
+        Ok(res
+            .headers()
+            .get(LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| httpdate::parse_http_date(value).ok()))
+    }
+
+    /// Downloads NOAA's directory of stations, or reads it from the
+    /// directory cache when one is set.
+    pub async fn get_station_directory(&self) -> Result<StationDirectory, WeatherError> {
+        let Some(cache) = &self.directory_cache else {
+            let text = self.download_station_directory().await?;
+            return Ok(StationDirectory::parse(&text));
+        };
+        match cache.read() {
+            Some((directory, age)) => {
+                if age >= cache.max_age {
+                    self.refresh_directory_cache(cache.clone());
+                }
+                Ok(directory)
+            }
+            None => {
+                let text = self.download_station_directory().await?;
+                // The cache is only an optimization
+                let _ = cache.write(&text);
+                Ok(StationDirectory::parse(&text))
+            }
+        }
+    }
+
+    /// Waits for the background refresh of the directory cache, if any.
+    /// Call it before exiting, for the refresh not to be cut short.
+    pub async fn wait_for_directory_refresh(&self) {
+        let refresh = self
+            .directory_refresh
+            .lock()
+            .ok()
+            .and_then(|mut r| r.take());
+        if let Some(refresh) = refresh {
+            let _ = refresh.await;
+        }
+    }
+
+    async fn download_station_directory(&self) -> Result<String, WeatherError> {
+        let res = self.send(Method::GET, DIRECTORY_URL).await?;
+        self.cancellable(res.text()).await
+    }
+
+    /// Downloads the directory into the cache in a task, unless a refresh
+    /// is already running.
+    fn refresh_directory_cache(&self, cache: DirectoryCache) {
+        let Ok(mut refresh) = self.directory_refresh.lock() else {
+            return;
+        };
+        if refresh.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+        let app = self.clone();
+        *refresh = Some(tokio::spawn(async move {
+            if let Ok(text) = app.download_station_directory().await {
+                let _ = cache.write(&text);
+            }
+        }));
+    }
+
+    /// Downloads the 1991–2020 daily climate normals of a station, by
+    /// its GHCN-Daily identifier. Eg: USW00023234 for KSFO
+    pub async fn get_climate_normals(&self, ghcn_id: &str) -> Result<ClimateNormals, WeatherError> {
+        let res = self.send(Method::GET, &normals_url(ghcn_id)).await?;
+        Ok(ClimateNormals::parse(&self.cancellable(res.text()).await?))
+    }
+
+    /// Retrieves the weather information for all the stations of a
+    /// country in the station directory, by its name or ISO 3166 alpha-2
+    /// code. Eg: "India" or "IN". Many stations in the directory don't
+    /// report observations, so expect a number of the results to be
+    /// errors.
+    pub async fn get_weather_for_country(
+        &self,
+        country: &str,
+        concurrency: usize,
+    ) -> Result<Vec<(String, Result<WeatherInfo, WeatherError>)>, WeatherError> {
+        let directory = self.get_station_directory().await?;
+        let stations = directory.by_country(country);
+        Ok(self.get_weather_for_stations(&stations, concurrency).await)
+    }
+
+    /// Retrieves the weather information for all the stations of the
+    /// directory inside a bounding box. See
+    /// [StationDirectory::within_bbox] for the meaning of the arguments.
+    pub async fn get_weather_within_bbox(
+        &self,
+        min_lat: f64,
+        min_lon: f64,
+        max_lat: f64,
+        max_lon: f64,
+        concurrency: usize,
+    ) -> Result<Vec<(String, Result<WeatherInfo, WeatherError>)>, WeatherError> {
+        let directory = self.get_station_directory().await?;
+        let stations = directory.within_bbox(min_lat, min_lon, max_lat, max_lon);
+        Ok(self.get_weather_for_stations(&stations, concurrency).await)
+    }
+
+    async fn get_weather_for_stations(
+        &self,
+        stations: &[&StationInfo],
+        concurrency: usize,
+    ) -> Vec<(String, Result<WeatherInfo, WeatherError>)> {
+        let codes: Vec<&str> = stations.iter().map(|s| s.icao.as_str()).collect();
+        self.get_weather_many(&codes, concurrency).await
+    }
+
+    /// Same function as `get_weather` but a blocking version. Requires
+    /// the `blocking` feature.
+    #[cfg(feature = "blocking")]
     pub fn get_blocking_weather(&self, station_code: &str) -> Result<WeatherInfo, WeatherError> {
-        let noaa_url = format!(
-            "https://tgftp.nws.noaa.gov/data/observations/metar/decoded/{}.TXT",
-            station_code
-        );
-        let body = self
-            .blocking_client
-            .get(noaa_url)
-            .send()?
-            .error_for_status()?
-            .text()?;
-        let (_, result) = parse_weather(&body)?;
-        Ok(result)
+        let info = match self.send_blocking(&decoded_url(station_code)) {
+            Ok(res) => {
+                let body = res.text()?;
+                parse_report(station_code, &body, self.decoded_parser())
+            }
+            Err(err) if self.metar_fallback && err.is_not_found() => {
+                let body = self.send_blocking(&metar_url(station_code))?.text()?;
+                parse_report(station_code, &body, parse_metar_file)
+            }
+            Err(err) => Err(err),
+        }?;
+        self.finish(info)
+    }
+}
+
+fn decoded_url(station_code: &str) -> String {
+    format!(
+        "https://tgftp.nws.noaa.gov/data/observations/metar/decoded/{}.TXT",
+        station_code
+    )
+}
+
+fn metar_url(station_code: &str) -> String {
+    format!(
+        "https://tgftp.nws.noaa.gov/data/observations/metar/stations/{}.TXT",
+        station_code
+    )
+}
+
+fn taf_url(station_code: &str) -> String {
+    format!(
+        "https://tgftp.nws.noaa.gov/data/forecasts/taf/stations/{}.TXT",
+        station_code
+    )
+}
+
+fn cycle_url(hour: u32) -> String {
+    format!(
+        "https://tgftp.nws.noaa.gov/data/observations/metar/cycles/{:02}Z.TXT",
+        hour
+    )
+}
+
+fn archive_url(station_code: &str, hours: u32) -> String {
+    format!(
+        "https://aviationweather.gov/api/data/metar?ids={}&format=raw&hours={}",
+        station_code, hours
+    )
+}
+
+fn check_status(res: Response, url: &Url) -> Result<Response, WeatherError> {
+    match http_error(res.status(), url, res.headers()) {
+        Some(err) => Err(err),
+        None => Ok(res),
+    }
+}
+
+#[cfg(feature = "blocking")]
+pub(crate) fn check_blocking_status(
+    res: reqwest::blocking::Response,
+    url: &Url,
+) -> Result<reqwest::blocking::Response, WeatherError> {
+    match http_error(res.status(), url, res.headers()) {
+        Some(err) => Err(err),
+        None => Ok(res),
+    }
+}
+
+/// The error for a response with a client or server error status.
+fn http_error(status: StatusCode, url: &Url, headers: &HeaderMap) -> Option<WeatherError> {
+    if !status.is_client_error() && !status.is_server_error() {
+        return None;
+    }
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    Some(WeatherError::Http {
+        status,
+        url: url.to_string(),
+        retry_after: header(RETRY_AFTER).and_then(parse_retry_after),
+        last_modified: header(LAST_MODIFIED)
+            .and_then(|value| httpdate::parse_http_date(value).ok()),
+    })
+}
+
+/// Parses a `Retry-After` header, which is either a number of seconds
+/// or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    match value.trim().parse() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => httpdate::parse_http_date(value)
+            .ok()?
+            .duration_since(SystemTime::now())
+            .ok(),
+    }
+}
+
+/// Parses a downloaded observation file with `parser`, telling files
+/// without a report apart from malformed ones.
+pub(crate) fn parse_report(
+    station_code: &str,
+    body: &str,
+    parser: fn(&str) -> Result<WeatherInfo, ParseError>,
+) -> Result<WeatherInfo, WeatherError> {
+    if is_empty_report(body) {
+        return Err(WeatherError::NoReport {
+            station: station_code.to_owned(),
+        });
+    }
+    Ok(parser(body)?)
+}
+
+/// Parses a decoded observation file.
+pub(crate) fn parse_decoded(body: &str) -> Result<WeatherInfo, ParseError> {
+    parse_weather(body)
+        .map(|(_, info)| info)
+        .map_err(|err| ParseError::decoded(body, err))
+}
+
+/// Parses a decoded report with [parse_weather_resilient], failing with
+/// the error of [parse_weather] when the observation can't be made up.
+fn parse_lenient(body: &str) -> Result<WeatherInfo, ParseError> {
+    match parse_weather_resilient(body) {
+        (Some(info), _) => Ok(info),
+        (None, _) => parse_decoded(body),
     }
 }
 
+/// SHA-256 of `bytes` in lowercase hexadecimal.
+fn sha256_hex(bytes: &[u8]) -> String {
+    hmac_sha256::Hash::hash(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Parses a raw METAR station file.
+fn parse_metar_file(body: &str) -> Result<WeatherInfo, ParseError> {
+    parse_station_file(body)
+        .map(|(_, info)| info)
+        .map_err(|err| ParseError::new(body, err))
+}
+
+/// Whether an observation file has no report: it's empty, only has the
+/// header line or the report is `NIL`. Eg:
+///
+/// ```text
+/// 2024/01/01 12:00
+/// VOBL 011200Z NIL=
+/// ```
+pub fn is_empty_report(body: &str) -> bool {
+    let lines: Vec<&str> = body
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.len() <= 1
+        || lines.iter().any(|line| {
+            line.split_whitespace()
+                .any(|token| token.trim_end_matches('=') == "NIL")
+        })
+}
+
 // Implementation taken and adapted from
 // https://github.com/jaor/xmobar/blob/master/src/Xmobar/Plugins/Monitors/Weather.hs
 
@@ -182,7 +1630,12 @@ pub fn parse_weather(i: &str) -> IResult<&str, WeatherInfo> {
     let (i, _) = newline(i)?;
     let (i, relative_humidity) = parse_relative_humidity(i)?;
     let (i, pressure) = parse_pressure(i)?;
-    let winfo = WeatherInfo {
+    let (i, metar) = parse_ob(i)?;
+    let mut wind = wind;
+    if let Some(metar) = &metar {
+        wind.merge_metar(metar);
+    }
+    let mut winfo = WeatherInfo {
         station,
         weather_time,
         wind,
@@ -193,10 +1646,328 @@ pub fn parse_weather(i: &str) -> IResult<&str, WeatherInfo> {
         dewpoint,
         relative_humidity,
         pressure,
+        metar,
+        provenance: Provenance::default(),
+        warnings: Vec::new(),
     };
+    winfo.reconcile();
     Ok((i, winfo))
 }
 
+/// Parses the optional METAR following the decoded values. A malformed
+/// METAR is left unparsed rather than failing the whole observation.
+fn parse_ob(i: &str) -> IResult<&str, Option<RawMetar>> {
+    opt(|i| {
+        let (i, _) = newline(i)?;
+        let (i, _) = tag("ob: ")(i)?;
+        parse_metar(i)
+    })(i)
+}
+
+/// Problem met by [parse_weather_resilient], or by the
+/// [validation](NoaaApp::with_validation) of an observation. Lines are
+/// numbered from 1.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ParseIssue {
+    /// The line of a known field couldn't be parsed and was skipped.
+    Malformed {
+        line: usize,
+        field: &'static str,
+        text: String,
+    },
+    /// The line has no known prefix and was skipped.
+    UnknownLine { line: usize, text: String },
+    /// A field is missing, or all its lines were malformed. Its value is
+    /// taken from the METAR when the report has one.
+    Missing { field: &'static str },
+    /// The values of a line in two units disagree, eg: 42 F (12 C). The
+    /// one in the unit of the METAR, celsius or knots, is kept.
+    UnitMismatch {
+        line: usize,
+        field: &'static str,
+        text: String,
+    },
+    /// A value is physically implausible, see
+    /// [crate::validation::Validation].
+    Implausible { field: &'static str, value: f64 },
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseIssue::Malformed { line, field, text } => {
+                write!(f, "line {}: malformed {}: {:?}", line, field, text)
+            }
+            ParseIssue::UnknownLine { line, text } => {
+                write!(f, "line {}: unknown line: {:?}", line, text)
+            }
+            ParseIssue::Missing { field } => write!(f, "missing {}", field),
+            ParseIssue::UnitMismatch { line, field, text } => {
+                write!(f, "line {}: units of {} disagree: {:?}", line, field, text)
+            }
+            ParseIssue::Implausible { field, value } => {
+                write!(f, "implausible {}: {}", field, value)
+            }
+        }
+    }
+}
+
+/// Fields of a decoded report, as found so far by
+/// [parse_weather_resilient].
+#[derive(Default)]
+struct PartialWeather {
+    station: Option<Station>,
+    weather_time: Option<WeatherTime>,
+    wind: Option<WindInfo>,
+    visibility: Option<String>,
+    sky_condition: Option<String>,
+    weather: Option<String>,
+    temperature: Option<Temperature>,
+    dewpoint: Option<Temperature>,
+    relative_humidity: Option<f64>,
+    pressure: Option<i16>,
+    metar: Option<RawMetar>,
+}
+
+/// Lines NOAA publishes for some stations which aren't part of
+/// [WeatherInfo]. Eg: Windchill: 27 F (-3 C):1
+const IGNORED_PREFIXES: [&str; 3] = ["Windchill:", "Heat index:", "cycle:"];
+
+/// Parses [WeatherInfo] like [parse_weather], but line by line: a line
+/// that fails to parse is skipped and recorded as an issue instead of
+/// failing the whole observation. Missing fields are taken from the
+/// METAR of the `ob:` line. The observation is `None` when its time, or
+/// a field the METAR can't make up for, is missing.
+pub fn parse_weather_resilient(i: &str) -> (Option<WeatherInfo>, Vec<ParseIssue>) {
+    let mut fields = PartialWeather::default();
+    let mut issues = vec![];
+    for (index, line) in i.lines().enumerate() {
+        let text = line.trim_end();
+        if text.is_empty() || IGNORED_PREFIXES.iter().any(|p| text.starts_with(p)) {
+            continue;
+        }
+        let line = index + 1;
+        let Some(field) = fields.field_of(index, text) else {
+            issues.push(ParseIssue::UnknownLine {
+                line,
+                text: text.to_owned(),
+            });
+            continue;
+        };
+        if !fields.store(field, text) {
+            issues.push(ParseIssue::Malformed {
+                line,
+                field,
+                text: text.to_owned(),
+            });
+        } else if fields.units_disagree(field) {
+            issues.push(ParseIssue::UnitMismatch {
+                line,
+                field,
+                text: text.to_owned(),
+            });
+        }
+    }
+    let info = fields.complete(&mut issues).map(|info| WeatherInfo {
+        warnings: issues.clone(),
+        ..info
+    });
+    (info, issues)
+}
+
+/// Stores the value of a parsed field, telling whether it parsed.
+fn store<T>(slot: &mut Option<T>, result: IResult<&str, T>) -> bool {
+    match result {
+        Ok((_, value)) => {
+            *slot = Some(value);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+impl PartialWeather {
+    /// Field of a line, from its prefix. The time line has none, and
+    /// neither has the station line, which is the first one.
+    fn field_of(&self, index: usize, text: &str) -> Option<&'static str> {
+        const PREFIXES: [(&str, &str); 9] = [
+            ("Wind:", "wind"),
+            ("Visibility: ", "visibility"),
+            ("Sky conditions: ", "sky conditions"),
+            ("Weather: ", "weather"),
+            ("Temperature:", "temperature"),
+            ("Dew Point:", "dew point"),
+            ("Relative Humidity: ", "relative humidity"),
+            ("Pressure (altimeter): ", "pressure"),
+            ("ob: ", "METAR"),
+        ];
+        match PREFIXES.iter().find(|(prefix, _)| text.starts_with(prefix)) {
+            Some((_, field)) => Some(field),
+            None if self.weather_time.is_none() && text.ends_with(" UTC") => Some("time"),
+            None if index == 0 => Some("station"),
+            None => None,
+        }
+    }
+
+    /// Parses the line of a field, telling whether it parsed.
+    fn store(&mut self, field: &str, text: &str) -> bool {
+        // Parsers of the whole report expect some lines to end with a
+        // newline
+        let line = format!("{}\n", text);
+        let value = |prefix: &str| text[prefix.len()..].to_owned();
+        match field {
+            "station" => {
+                self.station = parse_station(text).ok().and_then(|(_, station)| station);
+                true
+            }
+            "time" => store(&mut self.weather_time, parse_time(text)),
+            "wind" => store(&mut self.wind, parse_windinfo(text)),
+            "visibility" => {
+                self.visibility = Some(value("Visibility: "));
+                true
+            }
+            "sky conditions" => match parse_sky_condition(&line) {
+                Ok((_, sky_condition)) => {
+                    self.sky_condition = sky_condition;
+                    true
+                }
+                Err(_) => false,
+            },
+            "weather" => {
+                self.weather = Some(value("Weather: "));
+                true
+            }
+            "temperature" => store(
+                &mut self.temperature,
+                parse_temperature(&value("Temperature:")),
+            ),
+            "dew point" => store(&mut self.dewpoint, parse_temperature(&value("Dew Point:"))),
+            "relative humidity" => {
+                store(&mut self.relative_humidity, parse_relative_humidity(&line))
+            }
+            "pressure" => store(&mut self.pressure, parse_pressure(text)),
+            _ => store(&mut self.metar, parse_metar(&value("ob: "))),
+        }
+    }
+
+    /// Whether the values of a field in two units disagree by more than
+    /// their rounding.
+    fn units_disagree(&self, field: &str) -> bool {
+        let temperature = |t: &Option<Temperature>| {
+            t.as_ref()
+                .is_some_and(|t| ((t.fahrenheit - 32.0) / 1.8 - t.celsius).abs() > 1.0)
+        };
+        match field {
+            "temperature" => temperature(&self.temperature),
+            "dew point" => temperature(&self.dewpoint),
+            "wind" => self.wind.as_ref().is_some_and(|wind| {
+                (wind.knots * MPH_PER_KNOT - wind.mph).abs() > 1.5
+                    || wind
+                        .gust_knots
+                        .zip(wind.gust_mph)
+                        .is_some_and(|(knots, mph)| (knots * MPH_PER_KNOT - mph).abs() > 1.5)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Builds the observation, filling the missing fields from the
+    /// METAR and recording them as issues.
+    fn complete(self, issues: &mut Vec<ParseIssue>) -> Option<WeatherInfo> {
+        let fallback = match (&self.metar, &self.weather_time) {
+            (Some(metar), Some(time)) => metar.to_weather_info(time.year, time.month),
+            _ => None,
+        };
+        fn field<T>(
+            value: Option<T>,
+            name: &'static str,
+            fallback: Option<T>,
+            issues: &mut Vec<ParseIssue>,
+        ) -> Option<T> {
+            if value.is_none() {
+                issues.push(ParseIssue::Missing { field: name });
+            }
+            value.or(fallback)
+        }
+        // Values missing from the decoded text come from the METAR
+        let source = |decoded: bool, pick: fn(&Provenance) -> Source| match &fallback {
+            Some(info) if !decoded => pick(&info.provenance),
+            _ => Source::Decoded,
+        };
+        let provenance = Provenance {
+            temperature: source(self.temperature.is_some(), |p| p.temperature),
+            dewpoint: source(self.dewpoint.is_some(), |p| p.dewpoint),
+            wind: source(self.wind.is_some(), |p| p.wind),
+            pressure: source(self.pressure.is_some(), |p| p.pressure),
+            origin: Origin::Station,
+            fields: None,
+        };
+        let weather_time = field(self.weather_time, "time", None, issues);
+        let wind = field(
+            self.wind,
+            "wind",
+            fallback.as_ref().map(|f| f.wind.clone()),
+            issues,
+        );
+        let visibility = field(
+            self.visibility,
+            "visibility",
+            fallback.as_ref().map(|f| f.visibility.clone()),
+            issues,
+        );
+        let temperature = field(
+            self.temperature,
+            "temperature",
+            fallback
+                .as_ref()
+                .map(|f| Temperature::from_celsius(f.temperature.celsius)),
+            issues,
+        );
+        let dewpoint = field(
+            self.dewpoint,
+            "dew point",
+            fallback
+                .as_ref()
+                .map(|f| Temperature::from_celsius(f.dewpoint.celsius)),
+            issues,
+        );
+        let relative_humidity = field(
+            self.relative_humidity,
+            "relative humidity",
+            fallback.as_ref().map(|f| f.relative_humidity),
+            issues,
+        );
+        let pressure = field(
+            self.pressure,
+            "pressure",
+            fallback.as_ref().map(|f| f.pressure),
+            issues,
+        );
+        let mut wind = wind?;
+        if let Some(metar) = &self.metar {
+            wind.merge_metar(metar);
+        }
+        let mut info = WeatherInfo {
+            station: self.station,
+            weather_time: weather_time?,
+            wind,
+            visibility: visibility?,
+            sky_condition: self.sky_condition,
+            weather: self.weather,
+            temperature: temperature?,
+            dewpoint: dewpoint?,
+            relative_humidity: relative_humidity?,
+            pressure: pressure?,
+            metar: self.metar,
+            provenance,
+            warnings: Vec::new(),
+        };
+        info.reconcile();
+        Some(info)
+    }
+}
+
 impl FromStr for Station {
     type Err = String;
 
@@ -215,9 +1986,23 @@ impl TryFrom<&str> for Station {
                 if let [c, ..] = country.split('(').collect::<Vec<&str>>()[..] {
                     country = c.trim().to_string();
                 }
+                // Eg: (ZSQD) 36-04N 120-20E 77M
+                let coordinates: Vec<&str> = s2
+                    .rsplit(')')
+                    .next()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .collect();
+                let location = match coordinates[..] {
+                    [latitude, longitude, ..] => parse_coordinate(latitude)
+                        .zip(parse_coordinate(longitude))
+                        .map(|(latitude, longitude)| Point::new(latitude, longitude)),
+                    _ => None,
+                };
                 Ok(Station {
                     place: s1.to_string(),
                     country,
+                    location,
                 })
             }
             _ => Err(format!("Failure parsing {}", i)),
@@ -225,6 +2010,25 @@ impl TryFrom<&str> for Station {
     }
 }
 
+impl WindInfo {
+    /// Completes the wind with the details only the METAR has: the gust
+    /// when the decoded text has none, and the range of a varying
+    /// direction.
+    fn merge_metar(&mut self, metar: &RawMetar) {
+        let Some(wind) = &metar.wind else {
+            return;
+        };
+        if let (None, Some(gust)) = (self.gust_knots, wind.gust_knots()) {
+            self.gust_knots = Some(gust.round());
+            self.gust_mph = Some((gust * MPH_PER_KNOT).round());
+        }
+        if let Some(range) = wind.variable_range {
+            self.variable_range = Some(range);
+            self.direction_variable = true;
+        }
+    }
+}
+
 impl Default for WindInfo {
     fn default() -> Self {
         WindInfo {
@@ -232,6 +2036,10 @@ impl Default for WindInfo {
             azimuth: 0.0,
             mph: 0.0,
             knots: 0.0,
+            gust_mph: None,
+            gust_knots: None,
+            direction_variable: false,
+            variable_range: None,
         }
     }
 }
@@ -255,6 +2063,25 @@ fn parse_windinfo(i: &str) -> IResult<&str, WindInfo> {
         Ok((i, WindInfo::default()))
     }
 
+    /// Speed in miles per hour and knots
+    type Speed = (f64, f64);
+
+    // Eg: 16 MPH (14 KT)
+    fn speed_parser(i: &str) -> IResult<&str, Speed> {
+        let (i, mph) = map_res(take_till(char::is_whitespace), |s: &str| s.parse())(i)?;
+        let (i, _) = tag(" MPH (")(i)?;
+        let (i, knots) = map_res(take_till(char::is_whitespace), |s: &str| s.parse())(i)?;
+        let (i, _) = tag(" KT)")(i)?;
+        Ok((i, (mph, knots)))
+    }
+
+    // Eg: gusting to 26 MPH (23 KT) (direction variable)
+    fn gust_parser(i: &str) -> IResult<&str, (Option<Speed>, bool)> {
+        let (i, gust) = opt(preceded(tag(" gusting to "), speed_parser))(i)?;
+        let (i, rest) = take_till(|c| c == '\n')(i)?;
+        Ok((i, (gust, rest.contains("(direction variable)"))))
+    }
+
     fn wind_from_parser(i: &str) -> IResult<&str, WindInfo> {
         let (i, _) = tag("Wind: from the ")(i)?;
         let (i, cardinal) = take_till(char::is_whitespace)(i)?;
@@ -262,28 +2089,31 @@ fn parse_windinfo(i: &str) -> IResult<&str, WindInfo> {
         let (i, _) = char('(')(i)?;
         let (i, azimuth) = map_res(take_till(char::is_whitespace), |s: &str| s.parse())(i)?;
         let (i, _) = tag(" degrees) at ")(i)?;
-        let (i, mph) = map_res(take_till(char::is_whitespace), |s: &str| s.parse())(i)?;
-        let (i, _) = tag(" MPH (")(i)?;
-        let (i, knots) = map_res(take_till(char::is_whitespace), |s: &str| s.parse())(i)?;
-        let (i, _) = take_till(|c| c == '\n')(i)?;
+        let (i, (mph, knots)) = speed_parser(i)?;
+        let (i, (gust, direction_variable)) = gust_parser(i)?;
         let wind_info = WindInfo {
             cardinal: cardinal.into(),
             azimuth,
             mph,
             knots,
+            gust_mph: gust.map(|(mph, _)| mph),
+            gust_knots: gust.map(|(_, knots)| knots),
+            direction_variable,
+            variable_range: None,
         };
         Ok((i, wind_info))
     }
 
     fn wind_var_parser(i: &str) -> IResult<&str, WindInfo> {
         let (i, _) = tag("Wind: Variable at ")(i)?;
-        let (i, mph) = map_res(take_till(char::is_whitespace), |s: &str| s.parse())(i)?;
-        let (i, _) = tag(" MPH (")(i)?;
-        let (i, knots) = map_res(take_till(char::is_whitespace), |s: &str| s.parse())(i)?;
-        let (i, _) = take_till(|c| c == '\n')(i)?;
+        let (i, (mph, knots)) = speed_parser(i)?;
+        let (i, (gust, _)) = gust_parser(i)?;
         let wind_info = WindInfo {
             knots,
             mph,
+            gust_mph: gust.map(|(mph, _)| mph),
+            gust_knots: gust.map(|(_, knots)| knots),
+            direction_variable: true,
             ..WindInfo::default()
         };
         Ok((i, wind_info))
@@ -367,7 +2197,7 @@ fn parse_time(i: &str) -> IResult<&str, WeatherTime> {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     #[test]
     fn test_station() {
@@ -375,6 +2205,7 @@ mod tests {
         let station = Station {
             place: "Qingdao".to_string(),
             country: "China".to_string(),
+            location: Some(Point::new(36.0 + 4.0 / 60.0, 120.0 + 20.0 / 60.0)),
         };
         assert_eq!(
             parse_station("Qingdao, China (ZSQD) 36-04N 120-20E 77M\n"),
@@ -396,6 +2227,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_humanize_time() {
+        let wtime = WeatherTime {
+            year: 2021,
+            month: 3,
+            day: 28,
+            time: "0800 UTC".into(),
+        };
+        assert_eq!(wtime.to_string(), "2021-03-28 0800 UTC");
+        let now = Utc.with_ymd_and_hms(2021, 3, 28, 8, 23, 30).unwrap();
+        assert_eq!(wtime.humanize(now), Some("23 minutes ago".into()));
+        let before = Utc.with_ymd_and_hms(2021, 3, 28, 7, 0, 0).unwrap();
+        assert_eq!(wtime.humanize(before), None);
+        let invalid = WeatherTime {
+            time: "8 AM".into(),
+            ..wtime
+        };
+        assert_eq!(invalid.to_datetime(), None);
+    }
+
+    #[test]
+    fn test_in_timezone() {
+        let wtime = WeatherTime {
+            year: 2021,
+            month: 3,
+            day: 28,
+            time: "0800 UTC".into(),
+        };
+        let berlin = wtime.in_timezone(&chrono_tz::Europe::Berlin).unwrap();
+        // Daylight saving time started in the night
+        assert_eq!(berlin.format("%H:%M %Z").to_string(), "10:00 CEST");
+        let kolkata = wtime.in_timezone(&chrono_tz::Asia::Kolkata).unwrap();
+        assert_eq!(kolkata.format("%H:%M").to_string(), "13:30");
+    }
+
     #[test]
     fn test_wind_info() {
         let winfo = WindInfo {
@@ -403,6 +2269,10 @@ mod tests {
             azimuth: 0.0,
             mph: 0.0,
             knots: 0.0,
+            gust_mph: None,
+            gust_knots: None,
+            direction_variable: false,
+            variable_range: None,
         };
         assert_eq!(parse_windinfo("Wind: Calm:0"), Ok(("", winfo.clone())));
         assert!(parse_windinfo("Wind: unexpected").is_err());
@@ -412,12 +2282,105 @@ mod tests {
             azimuth: 340.0,
             mph: 16.0,
             knots: 14.0,
+            gust_mph: None,
+            gust_knots: None,
+            direction_variable: false,
+            variable_range: None,
         };
 
         assert_eq!(
             parse_windinfo("Wind: from the NNW (340 degrees) at 16 MPH (14 KT):0"),
             Ok(("", china_info))
+        );
+
+        let (_, gusty) = parse_windinfo(
+            "Wind: from the W (270 degrees) at 16 MPH (14 KT) gusting to 26 MPH (23 KT):0",
         )
+        .unwrap();
+        assert_eq!((gusty.mph, gusty.knots), (16.0, 14.0));
+        assert_eq!((gusty.gust_mph, gusty.gust_knots), (Some(26.0), Some(23.0)));
+        let (_, variable) =
+            parse_windinfo("Wind: Variable at 7 MPH (6 KT) gusting to 21 MPH (18 KT):0").unwrap();
+        assert_eq!(variable.gust_knots, Some(18.0));
+    }
+
+    #[test]
+    fn test_metar_gust() {
+        let input = r#"Yakima Air Terminal, WA, United States (KYKM) 46-34N 120-32W 324M
+Jan 01, 2024 - 11:53 AM EST / 2024.01.01 1653 UTC
+Wind: from the W (270 degrees) at 16 MPH (14 KT):0
+Visibility: 10 mile(s):0
+Sky conditions: clear
+Temperature: 42.1 F (5.6 C)
+Dew Point: 37.0 F (2.8 C)
+Relative Humidity: 82%
+Pressure (altimeter): 30.21 in. Hg (1023 hPa)
+ob: KYKM 011653Z 27014G23KT 10SM CLR 06/03 A3021"#;
+        let (_, info) = parse_weather(input).unwrap();
+        assert_eq!(info.wind.gust_knots, Some(23.0));
+        assert_eq!(info.wind.gust_mph, Some(26.0));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        assert!(is_empty_report(""));
+        assert!(is_empty_report("2024/01/01 12:00\n\n"));
+        assert!(is_empty_report("2024/01/01 12:00\nVOBL 011200Z NIL=\n"));
+        assert!(!is_empty_report(
+            "2024/01/01 12:00\nVOBL 011200Z 08008KT 9999 SCT020 23/14 Q1017\n"
+        ));
+        match parse_report("VOBL", "2024/01/01 12:00\nVOBL NIL", parse_metar_file) {
+            Err(WeatherError::NoReport { station }) => assert_eq!(station, "VOBL"),
+            other => panic!("Expected NoReport, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_http_error() {
+        let url: Url = "https://tgftp.nws.noaa.gov/data/nsd_cccc.txt"
+            .parse()
+            .unwrap();
+        let mut headers = HeaderMap::new();
+        assert!(http_error(StatusCode::OK, &url, &headers).is_none());
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        headers.insert(
+            LAST_MODIFIED,
+            "Sat, 30 Dec 2023 13:56:00 GMT".parse().unwrap(),
+        );
+        let err = http_error(StatusCode::SERVICE_UNAVAILABLE, &url, &headers).unwrap();
+        assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        match err {
+            WeatherError::Http {
+                retry_after,
+                last_modified,
+                ..
+            } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(120)));
+                assert_eq!(
+                    last_modified,
+                    httpdate::parse_http_date("Sat, 30 Dec 2023 13:56:00 GMT").ok()
+                );
+            }
+            other => panic!("Expected Http, got {:?}", other),
+        }
+        assert_eq!(parse_retry_after("Sat, 30 Dec 2023 13:56:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_metar_variable_range() {
+        let input = r#"Yakima Air Terminal, WA, United States (KYKM) 46-34N 120-32W 324M
+Jan 01, 2024 - 11:53 AM EST / 2024.01.01 1653 UTC
+Wind: from the W (270 degrees) at 16 MPH (14 KT) (direction variable):0
+Visibility: 10 mile(s):0
+Sky conditions: clear
+Temperature: 42.1 F (5.6 C)
+Dew Point: 37.0 F (2.8 C)
+Relative Humidity: 82%
+Pressure (altimeter): 30.21 in. Hg (1023 hPa)
+ob: KYKM 011653Z 27014KT 240V300 10SM CLR 06/03 A3021"#;
+        let (_, info) = parse_weather(input).unwrap();
+        assert!(info.wind.direction_variable);
+        assert_eq!(info.wind.variable_range, Some((240, 300)));
     }
 
     #[test]
@@ -459,28 +2422,590 @@ mod tests {
         );
     }
 
+    /// Cassette of the responses to the requests for VOBL and an
+    /// unknown station, replayed in place of NOAA's servers
+    #[cfg(feature = "replay")]
+    const VOBL_CASSETTE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/vobl.json");
+
+    #[test]
+    #[cfg(feature = "replay")]
+    fn test_replayed_weather() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let app = NoaaApp::new().with_cassette(Cassette::replay(VOBL_CASSETTE).unwrap());
+        let vobl = rt.block_on(app.get_weather("VOBL")).unwrap();
+        assert_eq!(vobl.temperature.celsius, 23.0);
+        assert_eq!(vobl.pressure, 1017);
+        let err = rt.block_on(app.get_weather("non_existent")).unwrap_err();
+        assert!(err.is_not_found());
+    }
+
+    #[test]
+    #[cfg(all(feature = "replay", feature = "blocking"))]
+    fn test_replayed_blocking_weather() {
+        let app = NoaaApp::new().with_cassette(Cassette::replay(VOBL_CASSETTE).unwrap());
+        let vobl = app.get_blocking_weather("VOBL").unwrap();
+        assert_eq!(vobl.temperature.celsius, 23.0);
+        let err = app.get_blocking_weather("non_existent").unwrap_err();
+        assert!(err.is_not_found());
+    }
+
+    /// Same as `test_replayed_weather` against NOAA's servers, which
+    /// needs the network
     #[test]
+    #[ignore]
     fn retrieve_test_weather() {
         use tokio::runtime::Runtime;
         let rt = Runtime::new().unwrap();
-	let app = NoaaApp::new();
-        let future = rt.block_on(async { app.get_weather("VOBL".into()).await });
+        let app = NoaaApp::new();
+        let future = rt.block_on(async { app.get_weather("VOBL").await });
         assert!(future.is_ok());
 
-        let future2 = rt.block_on(async { app.get_weather("non_existent".into()).await });
+        let future2 = rt.block_on(async { app.get_weather("non_existent").await });
         assert!(future2.is_err());
     }
 
     #[test]
+    #[ignore]
+    #[cfg(feature = "blocking")]
     fn retrieve_test_blocking_weather() {
-	let app = NoaaApp::new();
-        let result = app.get_blocking_weather("VOBL".into());
+        let app = NoaaApp::new();
+        let result = app.get_blocking_weather("VOBL");
         assert!(result.is_ok());
 
-        let result2 = app.get_blocking_weather("non_existent".into());
+        let result2 = app.get_blocking_weather("non_existent");
         assert!(result2.is_err());
     }
 
+    /// Redirects requests to a local server, recording the statuses of
+    /// the responses.
+    pub(crate) struct Mirror {
+        pub(crate) port: u16,
+        pub(crate) statuses: Arc<std::sync::Mutex<Vec<StatusCode>>>,
+    }
+
+    impl crate::middleware::Middleware for Mirror {
+        fn before_send(&self, _method: &Method, url: &mut Url, headers: &mut HeaderMap) {
+            url.set_scheme("http").unwrap();
+            url.set_host(Some("127.0.0.1")).unwrap();
+            url.set_port(Some(self.port)).unwrap();
+            headers.insert("x-api-key", "secret".parse().unwrap());
+        }
+
+        fn after_response(&self, _: &Method, _: &Url, status: StatusCode, _: &HeaderMap) {
+            self.statuses.lock().unwrap().push(status);
+        }
+    }
+
+    /// Serves one body per connection on a local port, returning the
+    /// requests received once every body was served.
+    pub(crate) fn serve(bodies: Vec<String>) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for body in bodies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                // Reads the headers, then the body if any
+                let complete = |request: &[u8]| {
+                    let text = String::from_utf8_lossy(request);
+                    let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                        return false;
+                    };
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            line.to_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|l| l.trim().parse().unwrap())
+                        })
+                        .unwrap_or(0);
+                    body.len() >= length
+                };
+                while !complete(&request) {
+                    let read = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                requests.push(String::from_utf8(request).unwrap());
+            }
+            requests
+        });
+        (port, server)
+    }
+
+    pub(crate) fn vogo_report(time: &str) -> String {
+        format!(
+            "Station name not available
+Dec 30, 2023 - 07:30 AM EST / 2023.12.30 {} UTC
+Wind: from the NNW (340 degrees) at 7 MPH (6 KT):0
+Visibility: 3 mile(s):0
+Temperature: 69 F (21 C)
+Dew Point: 64 F (18 C)
+Relative Humidity: 83%
+Pressure (altimeter): 29.83 in. Hg (1010 hPa)
+ob: VOGO 301230Z 34006KT 5000 HZ NSC 21/18 Q1010
+cycle: 12
+",
+            time
+        )
+    }
+
+    #[test]
+    fn test_middleware() {
+        let (port, server) = serve(vec![vogo_report("1230")]);
+        let statuses = Arc::default();
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::clone(&statuses),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let info = rt.block_on(app.get_weather("VOGO")).unwrap();
+        assert_eq!(info.pressure, 1010);
+        let request = server.join().unwrap().concat().to_lowercase();
+        assert!(request.starts_with("get /data/observations/metar/decoded/vogo.txt "));
+        assert!(request.contains("x-api-key: secret\r\n"));
+        assert_eq!(*statuses.lock().unwrap(), vec![StatusCode::OK]);
+    }
+
+    #[test]
+    fn test_fetched_weather() {
+        let report = vogo_report("1230");
+        let (port, server) = serve(vec![report.clone()]);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let fetched = rt.block_on(app.get_fetched_weather("VOGO")).unwrap();
+        server.join().unwrap();
+        assert_eq!(fetched.info.pressure, 1010);
+        assert_eq!((fetched.etag, fetched.last_modified), (None, None));
+        assert_eq!(fetched.sha256, sha256_hex(report.as_bytes()));
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_observe_many() {
+        let reports = ["1230", "1230", "1300"].map(vogo_report).to_vec();
+        let (port, server) = serve(reports);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let observations: Vec<_> = rt.block_on(
+            app.observe_many(&["VOGO"], Duration::from_millis(1))
+                .take(2)
+                .collect(),
+        );
+        let times: Vec<_> = observations
+            .into_iter()
+            .map(|(station, result)| (station, result.unwrap().weather_time.time))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                ("VOGO".to_owned(), "1230 UTC".to_owned()),
+                ("VOGO".to_owned(), "1300 UTC".to_owned())
+            ]
+        );
+        assert_eq!(server.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_observe_scheduled() {
+        let (port, server) = serve(vec![vogo_report("1230")]);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let schedule = Schedule::new(crate::schedule::Cron::hourly(0))
+            .with_stations(["vogo"], "* * * * *".parse().unwrap());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        // Polled right away, without jitter
+        let observations: Vec<_> = rt.block_on(
+            app.observe_scheduled(&["VOGO"], &schedule)
+                .take(1)
+                .collect(),
+        );
+        assert_eq!(observations[0].0, "VOGO");
+        assert_eq!(
+            observations[0].1.as_ref().unwrap().weather_time.time,
+            "1230 UTC"
+        );
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_observe_changes() {
+        let reports = ["1230", "1300", "1330"].map(vogo_report).to_vec();
+        let mut reports = reports;
+        reports[2] = reports[2].replace("(21 C)", "(25 C)");
+        let (port, server) = serve(reports);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let observations: Vec<_> = rt.block_on(
+            app.observe_changes(
+                &["VOGO"],
+                Duration::from_millis(1),
+                ChangeDetector::default(),
+            )
+            .take(2)
+            .collect(),
+        );
+        let times: Vec<_> = observations
+            .into_iter()
+            .map(|(_, result)| result.unwrap().weather_time.time)
+            .collect();
+        assert_eq!(times, ["1230 UTC", "1330 UTC"]);
+        assert_eq!(server.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_hedged_weather() {
+        let (port, server) = serve(vec![vogo_report("1230")]);
+        let app = NoaaApp::new()
+            .with_middleware(Mirror {
+                port,
+                statuses: Arc::default(),
+            })
+            .with_hedge_deadline(Duration::from_millis(50));
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let live = rt.block_on(app.get_weather_hedged("VOGO")).unwrap();
+        assert_eq!(live.freshness, Freshness::Live);
+        server.join().unwrap();
+
+        // The server is gone, the live fetch fails
+        let cached = rt.block_on(app.get_weather_hedged("VOGO")).unwrap();
+        assert_eq!(cached.freshness, Freshness::Cached);
+        assert_eq!(cached.value, live.value);
+        assert!(rt.block_on(app.get_weather_hedged("VOBL")).is_err());
+    }
+
+    #[test]
+    fn test_cancellation() {
+        // Accepts connections without ever answering
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let token = CancellationToken::new();
+        let app = NoaaApp::new()
+            .with_middleware(Mirror {
+                port: listener.local_addr().unwrap().port(),
+                statuses: Arc::default(),
+            })
+            .with_cancellation(token.clone());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let cancel = token.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                cancel.cancel();
+            });
+            assert!(matches!(
+                app.get_weather("VOGO").await,
+                Err(WeatherError::Cancelled)
+            ));
+            // Ends rather than polling forever
+            let observations: Vec<_> = app
+                .observe_many(&["VOGO"], Duration::from_secs(60))
+                .collect()
+                .await;
+            assert!(observations.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_history() {
+        let now = Utc::now();
+        let report = |ago: i64| {
+            let time = now - chrono::Duration::minutes(ago);
+            format!(
+                "{}\nVOGO {} 34006KT 6000 NSC 29/22 Q1010\n",
+                time.format("%Y/%m/%d %H:%M"),
+                time.format("%d%H%MZ")
+            )
+        };
+        // Both cycle files are served the same reports, the last of
+        // which is too old
+        let cycle = [report(10), report(70), report(200)].join("\n");
+        let (port, server) = serve(vec![cycle.clone(), cycle]);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let history = rt.block_on(app.get_history("VOGO", 2)).unwrap();
+        let ages: Vec<_> = history
+            .iter()
+            .map(|info| info.weather_time.age(now).unwrap().as_secs() / 60)
+            .collect();
+        assert_eq!(ages.len(), 2);
+        assert!(ages[0] < ages[1]);
+        let requests = server.join().unwrap().concat();
+        assert!(requests.contains("/data/observations/metar/cycles/"));
+    }
+
+    #[test]
+    fn test_archived_history() {
+        let now = Utc::now();
+        let metar = |ago: i64| {
+            let time = now - chrono::Duration::hours(ago);
+            format!(
+                "METAR VOGO {} 34006KT 6000 NSC 29/22 Q1010",
+                time.format("%d%H%MZ")
+            )
+        };
+        // Empty cycle files, then the archive, whose last report is too
+        // old
+        let mut bodies = vec![String::new(); HISTORY_HOURS as usize];
+        bodies.push([metar(2), metar(26), metar(40)].join("\n"));
+        let (port, server) = serve(bodies);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let history = rt.block_on(app.get_history("VOGO", 30)).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history
+            .iter()
+            .all(|info| info.provenance.origin == Origin::Archive));
+        let requests = server.join().unwrap().concat();
+        assert!(requests.contains("/api/data/metar?ids=VOGO&format=raw&hours=30"));
+    }
+
+    #[test]
+    fn test_raw_reports() {
+        let metar = "2023/12/30 12:30\nVOGO 301230Z 34006KT 5000 HZ NSC 21/18 Q1010\n";
+        let (port, server) = serve(vec![vogo_report("1230"), metar.to_owned()]);
+        let app = NoaaApp::new().with_middleware(Mirror {
+            port,
+            statuses: Arc::default(),
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let decoded = rt.block_on(app.get_decoded_report("VOGO")).unwrap();
+        assert_eq!(decoded, vogo_report("1230"));
+        assert_eq!(rt.block_on(app.get_metar_report("VOGO")).unwrap(), metar);
+        let requests = server.join().unwrap();
+        assert!(requests[0].contains("/metar/decoded/VOGO.TXT"));
+        assert!(requests[1].contains("/metar/stations/VOGO.TXT"));
+    }
+
+    #[test]
+    fn test_directory_cache() {
+        let ksfo = "KSFO;72;494;San Francisco, San Francisco International Airport;CA;United States;4;37-37N;122-22W;37-37N;122-22W;5;6;P\n";
+        let vobl = "VOBL;43;295;Bangalore / Bengaluru International Airport;;India;2;13-12N;077-42E;;;915;;\n";
+        let (port, server) = serve(vec![ksfo.to_owned(), vobl.to_owned()]);
+        let dir = std::env::temp_dir().join(format!("weathernoaa-cache-{}", std::process::id()));
+        let cache = DirectoryCache::new(dir.join("nsd_cccc.txt"), Duration::ZERO);
+        let app = NoaaApp::new()
+            .with_middleware(Mirror {
+                port,
+                statuses: Arc::default(),
+            })
+            .with_directory_cache(cache.clone());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        // Downloaded on first use
+        let directory = rt.block_on(app.get_station_directory()).unwrap();
+        assert!(directory.get("KSFO").is_some());
+        // The stale copy is used while it's refreshed
+        let directory = rt.block_on(app.get_station_directory()).unwrap();
+        assert!(directory.get("KSFO").is_some());
+        rt.block_on(app.wait_for_directory_refresh());
+        let (directory, _) = cache.read().unwrap();
+        assert!(directory.get("VOBL").is_some());
+        server.join().unwrap();
+        // Fresh copies are used without any request
+        let app = NoaaApp::new().with_directory_cache(DirectoryCache::new(
+            cache.path.clone(),
+            Duration::from_secs(3600),
+        ));
+        let directory = rt.block_on(app.get_station_directory()).unwrap();
+        assert!(directory.get("VOBL").is_some());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_visibility_meters() {
+        let report = vogo_report("1230");
+        let (_, mut info) = parse_weather(&report).unwrap();
+        assert_eq!(info.visibility, "3 mile(s):0");
+        assert_eq!(info.visibility_meters(), Some(5000.0));
+        info.metar = None;
+        assert_eq!(info.visibility_meters(), Some(3.0 * 1609.344));
+        info.visibility = "greater than 7 mile(s):0".into();
+        assert_eq!(info.visibility_meters(), None);
+    }
+
+    #[test]
+    fn test_field_sources() {
+        let report = vogo_report("1230");
+        let (_, info) = parse_weather(&report).unwrap();
+        let sources = info.field_sources();
+        assert_eq!(sources.len(), 9);
+        assert!(sources.values().all(|&source| source == Source::Decoded));
+
+        // Without pressure, which is taken from the METAR
+        let (info, _) = parse_weather_resilient(
+            &report.replace("Pressure (altimeter): 29.83 in. Hg (1010 hPa)\n", ""),
+        );
+        let sources = info.unwrap().field_sources();
+        assert_eq!(sources[&Field::Pressure], Source::Metar);
+        assert_eq!(sources[&Field::Wind], Source::Decoded);
+
+        let (_, metar) =
+            crate::metar::parse_metar("VOGO 301230Z 5000 HZ NSC 21/18 Q1010 RMK T02130179")
+                .unwrap();
+        let info = metar.to_weather_info(2023, 12).unwrap();
+        let sources = info.field_sources();
+        assert_eq!(sources[&Field::Time], Source::Metar);
+        assert_eq!(sources[&Field::Wind], Source::Defaulted);
+        assert_eq!(sources[&Field::Temperature], Source::MetarRemarks);
+        assert_eq!(sources[&Field::RelativeHumidity], Source::Derived);
+
+        let app = NoaaApp::new().with_field_provenance(true);
+        let annotated = app.finish(info.clone()).unwrap();
+        assert_eq!(annotated.provenance.fields, Some(sources));
+        assert_eq!(NoaaApp::new().finish(info).unwrap().provenance.fields, None);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let parse = |report: &str| match parse_decoded(report) {
+            Err(err) => err,
+            Ok(_) => panic!("{} parsed", report),
+        };
+        let report = vogo_report("1230");
+        let err = parse(&report.replace("Relative Humidity: 83%", "Fog: dense"));
+        assert_eq!(
+            err,
+            ParseError {
+                line: 7,
+                column: 1,
+                snippet: "Fog: dense".into(),
+                expected: "'Relative Humidity: '".into(),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "expected 'Relative Humidity: ' at line 7, column 1: \"Fog: dense\""
+        );
+        let err = parse(&report.replace("69 F (21 C)", "69 F (?? C)"));
+        assert_eq!((err.line, err.column), (5, 20));
+        assert_eq!(err.expected, "a line like 'Temperature: 64 F (18 C)'");
+        let err = parse(&report.replace("Visibility: 3 mile(s):0\n", ""));
+        assert_eq!(err.expected, "'Visibility: '");
+        let err = parse(&report.replace("Temperature", "Temp"));
+        assert_eq!(
+            err.expected,
+            "'Sky conditions: ', 'Weather: ' or 'Temperature: '"
+        );
+        let err = parse(&report[..report.find("Pressure").unwrap()]);
+        assert_eq!((err.line, err.snippet.as_str()), (8, ""));
+        assert_eq!(err.expected, "'Pressure (altimeter): '");
+    }
+
+    #[test]
+    fn test_parse_weather_resilient() {
+        let report = vogo_report("1230");
+        let (info, issues) = parse_weather_resilient(&report);
+        assert_eq!(info, Some(parse_weather(&report).unwrap().1));
+        assert_eq!(issues, []);
+
+        let quirky = report
+            .replace("Wind: from the NNW (340 degrees)", "Wind: from the NNW (?)")
+            .replace(
+                "Dew Point:",
+                "Windchill: 60 F (16 C):1\nFog: dense\nDew Point:",
+            )
+            .replace("Pressure (altimeter): 29.83 in. Hg (1010 hPa)\n", "");
+        let (info, issues) = parse_weather_resilient(&quirky);
+        let info = info.unwrap();
+        assert_eq!(info.wind.azimuth, 340.0);
+        assert_eq!(info.wind.knots, 6.0);
+        assert_eq!(info.pressure, 1010);
+        assert_eq!(info.temperature.celsius, 21.0);
+        assert_eq!(
+            issues,
+            [
+                ParseIssue::Malformed {
+                    line: 3,
+                    field: "wind",
+                    text: "Wind: from the NNW (?) at 7 MPH (6 KT):0".into(),
+                },
+                ParseIssue::UnknownLine {
+                    line: 7,
+                    text: "Fog: dense".into(),
+                },
+                ParseIssue::Missing { field: "wind" },
+                ParseIssue::Missing { field: "pressure" },
+            ]
+        );
+        assert_eq!(issues[3].to_string(), "missing pressure");
+        assert_eq!(info.warnings, issues);
+
+        let mismatched = report.replace("69 F (21 C)", "69 F (12 C)");
+        let (info, issues) = parse_weather_resilient(&mismatched);
+        assert_eq!(info.unwrap().temperature.celsius, 12.0);
+        assert_eq!(
+            issues,
+            [ParseIssue::UnitMismatch {
+                line: 5,
+                field: "temperature",
+                text: "Temperature: 69 F (12 C)".into(),
+            }]
+        );
+
+        // Without the METAR, nothing makes up for the temperature
+        let broken = report
+            .replace("Temperature: 69 F (21 C)", "Temperature: N/A")
+            .replace("ob: ", "");
+        let (info, issues) = parse_weather_resilient(&broken);
+        assert_eq!(info, None);
+        assert!(issues.contains(&ParseIssue::Missing {
+            field: "temperature"
+        }));
+    }
+
+    #[test]
+    fn test_estimated_cloud_base() {
+        let (_, mut info) = parse_weather(&vogo_report("1230")).unwrap();
+        assert_eq!(info.estimated_cloud_base_ft(), 1200.0);
+        info.dewpoint = Temperature::from_celsius(21.5);
+        assert_eq!(info.estimated_cloud_base_ft(), 0.0);
+    }
+
+    #[test]
+    fn test_frost_point_and_fog_risk() {
+        let (_, mut info) = parse_weather(&vogo_report("1230")).unwrap();
+        assert!(!info.fog_risk());
+        info.dewpoint = Temperature::from_celsius(-10.0);
+        assert_eq!(info.frost_point().celsius, -8.9);
+        info.dewpoint = Temperature::from_celsius(0.0);
+        assert_eq!(info.frost_point().celsius, 0.0);
+
+        info.temperature = Temperature::from_celsius(1.5);
+        info.relative_humidity = 90.0;
+        info.wind.knots = 3.0;
+        assert!(info.fog_risk());
+        info.wind.knots = 12.0;
+        assert!(!info.fog_risk());
+    }
+
     #[test]
     fn test_kykm_weather() {
         let weather = r#"YAKIMA AIR TERMINAL, WA, United States (KYKM) 46-34N 120-32W 324M
@@ -495,7 +3020,19 @@ Relative Humidity: 88%
 Pressure (altimeter): 30.05 in. Hg (1017 hPa)
 ob: KYKM 310353Z AUTO 00000KT 5SM BR OVC025 06/04 A3005 RMK AO2 SLP185 T00560039
 cycle: 4"#;
-        parse_weather(weather).unwrap();
+        let (rest, info) = parse_weather(weather).unwrap();
+        assert_eq!(rest, "\ncycle: 4");
+        let metar = info.metar.unwrap();
+        assert_eq!(metar.station, "KYKM");
+        assert_eq!(metar.remarks, Some("AO2 SLP185 T00560039".into()));
+        assert_eq!(info.provenance.temperature, Source::MetarRemarks);
+        assert_eq!(info.provenance.dewpoint, Source::MetarRemarks);
+        assert_eq!(info.provenance.wind, Source::Decoded);
+
+        // The rounded decoded temperature gives way to the T group
+        let rounded = weather.replace("42.1 F (5.6 C)", "42 F (6 C)");
+        let (_, info) = parse_weather(&rounded).unwrap();
+        assert_eq!(info.temperature, Temperature::from_celsius(5.6));
     }
 
     #[test]
@@ -538,6 +3075,10 @@ extra";
                 azimuth: 200.0,
                 mph: 12.0,
                 knots: 10.0,
+                gust_mph: None,
+                gust_knots: None,
+                direction_variable: true,
+                variable_range: None,
             },
             visibility: "4 mile(s):0".into(),
             sky_condition: Some("partly cloudy".to_owned()),
@@ -552,6 +3093,9 @@ extra";
             },
             relative_humidity: 61.0,
             pressure: 1009,
+            metar: None,
+            provenance: Provenance::default(),
+            warnings: Vec::new(),
         };
 
         assert_eq!(parse_weather(weather), Ok(("\nextra", winfo)));
@@ -573,6 +3117,7 @@ Pressure (altimeter): 29.65 in. Hg (1004 hPa)";
             station: Some(Station {
                 place: "Qingdao".into(),
                 country: "China".into(),
+                location: Some(Point::new(36.0 + 4.0 / 60.0, 120.0 + 20.0 / 60.0)),
             }),
             weather_time: WeatherTime {
                 year: 2021,
@@ -585,6 +3130,10 @@ Pressure (altimeter): 29.65 in. Hg (1004 hPa)";
                 azimuth: 340.0,
                 mph: 16.0,
                 knots: 14.0,
+                gust_mph: None,
+                gust_knots: None,
+                direction_variable: false,
+                variable_range: None,
             },
             visibility: "1 mile(s):0".into(),
             sky_condition: Some("overcast".to_owned()),
@@ -599,6 +3148,9 @@ Pressure (altimeter): 29.65 in. Hg (1004 hPa)";
             },
             relative_humidity: 45.0,
             pressure: 1004,
+            metar: None,
+            provenance: Provenance::default(),
+            warnings: Vec::new(),
         };
 
         assert_eq!(parse_weather(weather), Ok(("", winfo)));
@@ -618,6 +3170,7 @@ extra";
             station: Some(Station {
                 place: "Qingdao".into(),
                 country: "China".into(),
+                location: Some(Point::new(36.0 + 4.0 / 60.0, 120.0 + 20.0 / 60.0)),
             }),
             weather_time: WeatherTime {
                 year: 2021,
@@ -630,6 +3183,10 @@ extra";
                 azimuth: 340.0,
                 mph: 16.0,
                 knots: 14.0,
+                gust_mph: None,
+                gust_knots: None,
+                direction_variable: false,
+                variable_range: None,
             },
             visibility: "1 mile(s):0".into(),
             sky_condition: Some("overcast".to_owned()),
@@ -644,6 +3201,9 @@ extra";
             },
             relative_humidity: 45.0,
             pressure: 1004,
+            metar: None,
+            provenance: Provenance::default(),
+            warnings: Vec::new(),
         };
 
         assert_eq!(parse_weather(weather2), Ok(("\nextra", winfo2)))
@@ -0,0 +1,112 @@
+//! Wind roses: how often the wind of a site blows from each direction,
+//! and how strongly, eg: for telling its prevailing winds.
+
+use crate::weather::WeatherInfo;
+
+/// Sectors of a wind rose, clockwise from north, each spanning 45°.
+pub const SECTORS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// Lowest speed of each speed class of a wind rose in knots, the last
+/// class having no upper bound. Slower winds are calm.
+pub const SPEED_CLASSES_KNOTS: [f64; 4] = [1.0, 10.0, 20.0, 30.0];
+
+/// Number of observations of a history by direction sector and speed
+/// class of the wind.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindRose {
+    /// Observations by [sector](SECTORS), then by
+    /// [speed class](SPEED_CLASSES_KNOTS)
+    pub bins: [[usize; SPEED_CLASSES_KNOTS.len()]; SECTORS.len()],
+    /// Observations of a calm wind
+    pub calm: usize,
+    /// Observations of a wind whose direction is variable, which have
+    /// no sector
+    pub variable: usize,
+    /// Number of observations
+    pub total: usize,
+}
+
+impl WindRose {
+    pub fn new(history: &[WeatherInfo]) -> Self {
+        let mut rose = WindRose {
+            total: history.len(),
+            ..WindRose::default()
+        };
+        for info in history {
+            let wind = &info.wind;
+            let Some(class) = SPEED_CLASSES_KNOTS
+                .iter()
+                .rposition(|&lowest| wind.knots >= lowest)
+            else {
+                rose.calm += 1;
+                continue;
+            };
+            if wind.direction_variable {
+                rose.variable += 1;
+                continue;
+            }
+            let sector = ((wind.azimuth.rem_euclid(360.0) + 22.5) / 45.0) as usize % SECTORS.len();
+            rose.bins[sector][class] += 1;
+        }
+        rose
+    }
+
+    /// Share of the observations in percent. Zero without observations.
+    pub fn percent(&self, count: usize) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        100.0 * count as f64 / self.total as f64
+    }
+
+    /// Observations of a sector, whatever their speed.
+    pub fn sector_count(&self, sector: usize) -> usize {
+        self.bins[sector].iter().sum()
+    }
+
+    /// Sector the wind blows the most often from. `None` when no
+    /// observation has a sector. The first sector clockwise from north
+    /// wins ties.
+    pub fn prevailing(&self) -> Option<&'static str> {
+        let (sector, count) = (0..SECTORS.len())
+            .map(|sector| (sector, self.sector_count(sector)))
+            .rev()
+            .max_by_key(|&(_, count)| count)?;
+        (count > 0).then_some(SECTORS[sector])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::parse_weather;
+    use crate::weather::tests::vogo_report;
+
+    #[test]
+    fn test_wind_rose() {
+        let (_, vogo) = parse_weather(&vogo_report("1230")).unwrap();
+        let wind = |azimuth: f64, knots: f64, variable: bool| {
+            let mut info = vogo.clone();
+            info.wind.azimuth = azimuth;
+            info.wind.knots = knots;
+            info.wind.direction_variable = variable;
+            info
+        };
+        let rose = WindRose::new(&[
+            // NNW at 6 kt
+            vogo.clone(),
+            wind(350.0, 12.0, false),
+            wind(10.0, 35.0, false),
+            wind(225.0, 8.0, false),
+            wind(0.0, 0.0, false),
+            wind(0.0, 4.0, true),
+        ]);
+        assert_eq!(rose.bins[0], [1, 1, 0, 1]);
+        assert_eq!(rose.bins[5], [1, 0, 0, 0]);
+        assert_eq!((rose.calm, rose.variable, rose.total), (1, 1, 6));
+        assert_eq!(rose.prevailing(), Some("N"));
+        assert_eq!(rose.percent(rose.sector_count(0)), 50.0);
+        assert_eq!(WindRose::new(&[]).prevailing(), None);
+    }
+}
@@ -0,0 +1,224 @@
+// Present weather codes of the WMO code table 4677, used by synoptic
+// reports and keyed off by meteorological databases and icon packs.
+//
+// The codes range from 0 to 99, higher codes generally describing more
+// significant weather. Codes 20 to 29 describe the weather of the
+// preceding hour and aren't produced from METARs.
+
+use crate::icon::Condition;
+use crate::metar::{Descriptor, Intensity, Phenomenon, WeatherGroup};
+use crate::weather::WeatherInfo;
+
+/// The code of a weather group, `None` when the table has no code for
+/// it. Eg: 63 for RA, 95 for TSRA
+pub fn of_group(group: &WeatherGroup) -> Option<u8> {
+    use Phenomenon::*;
+    let has = |phenomenon| group.phenomena.contains(&phenomenon);
+    let precipitation = group.phenomena.iter().any(|phenomenon| {
+        matches!(
+            phenomenon,
+            Drizzle
+                | Rain
+                | Snow
+                | SnowGrains
+                | IceCrystals
+                | IcePellets
+                | Hail
+                | SmallHail
+                | UnknownPrecipitation
+        )
+    });
+    // Codes for slight, moderate and heavy intensities
+    let by_intensity = |slight, moderate, heavy| {
+        Some(match group.intensity {
+            Intensity::Light => slight,
+            Intensity::Heavy => heavy,
+            Intensity::Moderate | Intensity::Vicinity => moderate,
+        })
+    };
+    if group.intensity == Intensity::Vicinity {
+        return match group.descriptor {
+            Some(Descriptor::Showers) => Some(16),
+            Some(Descriptor::Thunderstorm) => Some(17),
+            _ if has(Fog) => Some(40),
+            _ if has(Sandstorm) || has(Duststorm) => Some(9),
+            _ => None,
+        };
+    }
+    match group.descriptor {
+        Some(Descriptor::Thunderstorm) => {
+            let heavy = group.intensity == Intensity::Heavy;
+            let hail = has(Hail) || has(SmallHail);
+            return Some(match (precipitation, hail, heavy) {
+                (false, _, _) => 17,
+                (true, false, false) => 95,
+                (true, true, false) => 96,
+                (true, false, true) => 97,
+                (true, true, true) => 99,
+            });
+        }
+        Some(Descriptor::Showers) => {
+            return if has(Hail) {
+                by_intensity(89, 90, 90)
+            } else if has(SmallHail) {
+                by_intensity(87, 88, 88)
+            } else if has(Snow) && has(Rain) {
+                by_intensity(83, 84, 84)
+            } else if has(Snow) {
+                by_intensity(85, 86, 86)
+            } else {
+                by_intensity(80, 81, 82)
+            };
+        }
+        Some(Descriptor::Freezing) if has(Drizzle) => return by_intensity(56, 57, 57),
+        Some(Descriptor::Freezing) if has(Rain) => return by_intensity(66, 67, 67),
+        Some(Descriptor::Freezing) if has(Fog) => return Some(49),
+        Some(Descriptor::LowDrifting) if has(Snow) => return by_intensity(36, 36, 37),
+        Some(Descriptor::Blowing) if has(Snow) => return by_intensity(38, 38, 39),
+        Some(Descriptor::LowDrifting | Descriptor::Blowing) => return Some(7),
+        Some(Descriptor::Shallow) if has(Fog) => return Some(12),
+        Some(Descriptor::Patches) if has(Fog) => return Some(11),
+        Some(Descriptor::Partial) if has(Fog) => return Some(41),
+        _ => {}
+    }
+    if has(Rain) && has(Snow) {
+        by_intensity(68, 69, 69)
+    } else if has(Drizzle) && has(Rain) {
+        by_intensity(58, 59, 59)
+    } else if has(Snow) {
+        by_intensity(71, 73, 75)
+    } else if has(IcePellets) {
+        Some(79)
+    } else if has(SnowGrains) {
+        Some(77)
+    } else if has(IceCrystals) {
+        Some(76)
+    } else if has(Hail) || has(SmallHail) {
+        by_intensity(89, 90, 90)
+    } else if has(Rain) || has(UnknownPrecipitation) {
+        by_intensity(61, 63, 65)
+    } else if has(Drizzle) {
+        by_intensity(51, 53, 55)
+    } else if has(Sandstorm) || has(Duststorm) {
+        by_intensity(31, 31, 34)
+    } else if has(FunnelCloud) {
+        Some(19)
+    } else if has(Squalls) {
+        Some(18)
+    } else if has(Fog) {
+        Some(45)
+    } else if has(Mist) {
+        Some(10)
+    } else if has(DustWhirls) {
+        Some(8)
+    } else if has(Sand) {
+        Some(7)
+    } else if has(WidespreadDust) {
+        Some(6)
+    } else if has(Haze) {
+        Some(5)
+    } else if has(Smoke) || has(VolcanicAsh) {
+        Some(4)
+    } else {
+        None
+    }
+}
+
+/// The code of a sky condition in the wording of NOAA's decoded files,
+/// using codes 0 to 3 for the cloud cover as is common practice. Eg: 2
+/// for partly cloudy
+pub fn of_sky_condition(sky: &str) -> Option<u8> {
+    match sky {
+        "clear" => Some(0),
+        "mostly clear" => Some(1),
+        "partly cloudy" => Some(2),
+        "mostly cloudy" | "overcast" => Some(3),
+        _ => None,
+    }
+}
+
+impl Condition {
+    /// The condition described by a present weather code.
+    pub fn of_wmo_code(code: u8) -> Condition {
+        match code {
+            0 => Condition::Clear,
+            1 | 2 => Condition::PartlyCloudy,
+            3 => Condition::Overcast,
+            4 => Condition::Smoke,
+            5 => Condition::Haze,
+            6..=8 => Condition::Dust,
+            9 | 30..=35 => Condition::Sandstorm,
+            10..=12 | 40..=49 => Condition::Fog,
+            16 => Condition::Showers,
+            13 | 17 | 91..=99 => Condition::Thunderstorm,
+            18 => Condition::Windy,
+            19 => Condition::Tornado,
+            50..=55 | 58 | 59 => Condition::Drizzle,
+            56 | 57 | 66..=69 | 79 | 83 | 84 => Condition::Sleet,
+            60..=65 => Condition::Rain,
+            36..=39 | 70..=78 | 85 | 86 => Condition::Snow,
+            80..=82 => Condition::Showers,
+            87..=90 => Condition::Hail,
+            _ => Condition::Unknown,
+        }
+    }
+}
+
+impl WeatherInfo {
+    /// Present weather code of the observation, from the most
+    /// significant of its weather groups or, when it has none with a
+    /// code, from its sky condition.
+    pub fn wmo_code(&self) -> Option<u8> {
+        self.weather_groups()
+            .iter()
+            .filter_map(of_group)
+            .max()
+            .or_else(|| self.sky_condition.as_deref().and_then(of_sky_condition))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metar::parse_metar;
+
+    fn codes(metar: &str) -> Vec<Option<u8>> {
+        let (_, metar) = parse_metar(metar).unwrap();
+        metar.weather.iter().map(of_group).collect()
+    }
+
+    #[test]
+    fn test_of_group() {
+        assert_eq!(
+            codes("KSFO 301356Z 28012KT 2SM -RA +SN -SHRA +TSRA TS FZDZ FZFG VCSH BR HZ BLSN BCFG A3002"),
+            vec![
+                Some(61),
+                Some(75),
+                Some(80),
+                Some(97),
+                Some(17),
+                Some(57),
+                Some(49),
+                Some(16),
+                Some(10),
+                Some(5),
+                Some(38),
+                Some(11),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wmo_code() {
+        let (_, metar) =
+            parse_metar("VOBL 301330Z 08008KT 9999 BR -TSRA SCT020 23/14 Q1017").unwrap();
+        let mut info = metar.to_weather_info(2023, 12).unwrap();
+        assert_eq!(info.wmo_code(), Some(95));
+        assert_eq!(Condition::of_wmo_code(95), Condition::Thunderstorm);
+        info.metar = None;
+        assert_eq!(info.wmo_code(), Some(2));
+        assert_eq!(Condition::of_wmo_code(2), Condition::PartlyCloudy);
+        info.sky_condition = None;
+        assert_eq!(info.wmo_code(), None);
+    }
+}